@@ -122,6 +122,12 @@ const Ck3Page = ({ save, meta }: Ck3PageProps) => {
         <p>
           {`A CK3 save was detected (version ${meta.version}). At this time, CK3 functionality is limited but one can still melt binary ironman saves into plaintext`}
         </p>
+        {meta.gamestateError && (
+          <Alert
+            type="warning"
+            message={`Only the save header could be read: ${meta.gamestateError}`}
+          />
+        )}
         {meta.isMeltable && (
           <MeltButton
             worker={getCk3Worker()}
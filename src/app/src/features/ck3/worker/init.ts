@@ -11,3 +11,8 @@ export async function parseCk3() {
   const meta = wasm.save.metadata();
   return { meta };
 }
+
+/** Localizes names with the zstd compressed game data from compile_assets */
+export function ck3SetGameData(data: Uint8Array) {
+  wasm.save.set_game_data(data);
+}
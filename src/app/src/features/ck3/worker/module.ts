@@ -1,3 +1,74 @@
+import {
+  BorderFill,
+  ProvinceMapColors,
+  ProvinceRaster,
+  fillBorders,
+  transferMapColors,
+} from "@/lib/provinceMap";
+import { LedgerDataRaw, workLedgerData } from "@/features/eu4/utils/ledger";
 import { wasm } from "./common";
+import {
+  Ck3CharacterSummary,
+  Ck3FamilyTree,
+  Ck3Holdings,
+  Ck3Caveat,
+  Ck3PlayerWars,
+  Ck3RealmSize,
+  Ck3TitleHistory,
+  Ck3TitleNode,
+  Ck3TitleTier,
+  TitleHierarchy,
+} from "./types";
 export * from "./init";
 export const melt = () => wasm.melt();
+
+export function ck3GetPlayerCharacter(): Ck3CharacterSummary | undefined {
+  return wasm.save.get_player_character() as Ck3CharacterSummary | undefined;
+}
+
+export function ck3GetPlayerHoldings(): Ck3Holdings | undefined {
+  return wasm.save.get_player_holdings() as Ck3Holdings | undefined;
+}
+
+export function ck3GetTitleTree(
+  hierarchy: TitleHierarchy,
+  root?: string
+): Ck3TitleNode[] {
+  return wasm.save.get_title_tree({ hierarchy, root }) as Ck3TitleNode[];
+}
+
+export function ck3GetFamilyTree(house?: number): Ck3FamilyTree | undefined {
+  return wasm.save.get_family_tree({ house }) as Ck3FamilyTree | undefined;
+}
+
+export function ck3GetTitleHistory(
+  tiers: Ck3TitleTier[] = []
+): Ck3TitleHistory[] {
+  return wasm.save.get_title_history({ tiers }) as Ck3TitleHistory[];
+}
+
+export function ck3GetRealmSize(character?: number): Ck3RealmSize {
+  const data = wasm.save.get_realm_size({ character }) as LedgerDataRaw & {
+    caveats: Ck3Caveat[];
+  };
+  return { data: workLedgerData(data), caveats: data.caveats };
+}
+
+export function ck3GetPlayerActiveWars(): Ck3PlayerWars {
+  return wasm.save.get_player_active_wars() as Ck3PlayerWars;
+}
+
+export function ck3MapColors(
+  provinces: number,
+  baronies: Record<string, number>
+): ProvinceMapColors {
+  return transferMapColors(wasm.save.map_colors({ provinces, baronies }));
+}
+
+export function ck3FillBorders(
+  raster: ProvinceRaster,
+  colors: ProvinceMapColors,
+  fill: BorderFill
+): Uint8Array {
+  return fillBorders(wasm.module.map_fill_borders, raster, colors, fill);
+}
@@ -1,4 +1,111 @@
+import { LedgerDatum } from "@/features/eu4/types/models";
+
 export interface Ck3Metadata {
   version: string;
   isMeltable: boolean;
+  gamestateError?: string;
+}
+
+export type Ck3TitleTier =
+  | "barony"
+  | "county"
+  | "duchy"
+  | "kingdom"
+  | "empire"
+  | "other";
+
+export interface Ck3HouseRef {
+  id: number;
+  name: string;
+}
+
+export interface Ck3TitleRef {
+  id: number;
+  key: string;
+  name: string;
+  tier: Ck3TitleTier;
+}
+
+export interface Ck3CharacterSummary {
+  id: number;
+  name: string;
+  birth: string | null;
+  death: string | null;
+  age: number | null;
+  female: boolean;
+  house: Ck3HouseRef | null;
+  primaryTitle: Ck3TitleRef | null;
+}
+
+export interface Ck3Holdings {
+  character: Ck3CharacterSummary;
+  titles: Ck3TitleRef[];
+}
+
+export type TitleHierarchy = "deJure" | "deFacto";
+
+export interface Ck3TitleNode {
+  title: Ck3TitleRef;
+  holder: Ck3TitleHolder | null;
+  heldByPlayer: boolean;
+  diverges: boolean;
+  children: Ck3TitleNode[];
+}
+
+export interface Ck3FamilyMember {
+  character: Ck3CharacterSummary;
+  father: number | null;
+  mother: number | null;
+  spouses: number[];
+  children: number[];
+  isHead: boolean;
+}
+
+export interface Ck3FamilyTree {
+  house: Ck3HouseRef;
+  members: Ck3FamilyMember[];
+}
+
+export interface Ck3TitleHolder {
+  id: number;
+  name: string;
+}
+
+export interface Ck3TitleReign {
+  holder: Ck3TitleHolder | null;
+  house: Ck3HouseRef | null;
+  start: string;
+  end: string | null;
+}
+
+export interface Ck3TitleHistory {
+  title: Ck3TitleRef;
+  reigns: Ck3TitleReign[];
+}
+
+export type Ck3Caveat =
+  | "presentDayLieges"
+  | "prunedHolders"
+  | "concludedWarsDropped";
+
+export interface Ck3PlayerWar {
+  id: number;
+  name: string | null;
+  start: string | null;
+  side: "attacker" | "defender";
+  isLeader: boolean;
+  casusBelli: string | null;
+  targetedTitles: Ck3TitleRef[];
+  attackers: Ck3TitleHolder[];
+  defenders: Ck3TitleHolder[];
+}
+
+export interface Ck3PlayerWars {
+  active: Ck3PlayerWar[];
+  caveats: Ck3Caveat[];
+}
+
+export interface Ck3RealmSize {
+  data: LedgerDatum[];
+  caveats: Ck3Caveat[];
 }
@@ -1,3 +1,5 @@
+import type { DetectedDataType } from "@/features/engine/engineStore";
+
 export interface ContentMetadata {
   contentEncoding: string;
   contentType: string;
@@ -6,3 +8,12 @@ export interface ContentMetadata {
 export interface CompressionPayload extends ContentMetadata {
   data: Uint8Array;
 }
+
+/** Save format sniffed from a file's header by `detect_save` */
+export interface SaveDetection {
+  /** null for binary CK3, Imperator, and Vic3 saves, which share a header */
+  game: DetectedDataType | null;
+  encoding: "text" | "binary";
+  compression: "none" | "zip" | "zstd" | "tar";
+  patch: string | null;
+}
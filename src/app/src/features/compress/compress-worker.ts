@@ -1,7 +1,7 @@
 import { expose, transfer } from "comlink";
 import init, * as wasmModule from "../../../../wasm-compress/pkg/wasm_compress";
 import wasmPath from "../../../../wasm-compress/pkg/wasm_compress_bg.wasm";
-import { CompressionPayload } from "./compress-types";
+import { CompressionPayload, SaveDetection } from "./compress-types";
 import { timeSync } from "@/lib/timeit";
 import { formatInt } from "@/lib/format";
 import { logMs } from "@/lib/log";
@@ -29,6 +29,10 @@ const obj = {
       [deflated.data.buffer]
     );
   },
+
+  detectSave(head: Uint8Array, tail: Uint8Array, len: number): SaveDetection {
+    return wasmModule.detect_save(head, tail, len);
+  },
 };
 
 expose(obj);
@@ -0,0 +1,49 @@
+import { wrap, transfer, Remote } from "comlink";
+import type { CompressionWorker } from "./compress-worker";
+import type { SaveDetection } from "./compress-types";
+
+/** Bytes read from the start of a save, enough for its header and metadata */
+const HEAD_LEN = 1024 * 1024;
+
+/** Bytes read from the end of a save, enough for a zip's central directory */
+const TAIL_LEN = 128 * 1024;
+
+let detector: Promise<Remote<CompressionWorker>> | undefined;
+
+/** The worker is kept around so that wasm is loaded only once */
+function getDetector() {
+  if (!detector) {
+    const worker = new Worker(new URL("./compress-worker", import.meta.url));
+    const workerApi = wrap<CompressionWorker>(worker);
+    detector = workerApi.loadWasm().then(
+      () => workerApi,
+      (err) => {
+        detector = undefined;
+        worker.terminate();
+        throw err;
+      }
+    );
+  }
+
+  return detector;
+}
+
+async function readBlob(blob: Blob) {
+  return new Uint8Array(await blob.arrayBuffer());
+}
+
+/** Sniffs the game and container of a save from its start and end */
+export async function detectSave(file: Blob): Promise<SaveDetection> {
+  const tailStart = Math.max(HEAD_LEN, file.size - TAIL_LEN);
+  const [head, tail, workerApi] = await Promise.all([
+    readBlob(file.slice(0, HEAD_LEN)),
+    readBlob(file.slice(tailStart)),
+    getDetector(),
+  ]);
+
+  return workerApi.detectSave(
+    transfer(head, [head.buffer]),
+    transfer(tail, [tail.buffer]),
+    file.size
+  );
+}
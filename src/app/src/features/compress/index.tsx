@@ -1,2 +1,3 @@
 export * from "./compress-hooks";
 export * from "./compress-types";
+export * from "./detect";
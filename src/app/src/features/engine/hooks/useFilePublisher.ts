@@ -1,19 +1,37 @@
 import { FileKind } from "@/hooks/useFileDrop";
-import { extensionType, SaveGameInput, useEngineActions } from "../engineStore";
+import { detectSave } from "@/features/compress";
+import {
+  DetectedDataType,
+  extensionType,
+  SaveGameInput,
+  useEngineActions,
+} from "../engineStore";
 
 type AnalyzeInput = FileKind;
 
+/** Routes by the save's header, falling back to the file extension when the
+ * header is unrecognized or shared between games */
+async function detectGame(file: File): Promise<DetectedDataType> {
+  try {
+    const detected = await detectSave(file);
+    return detected.game ?? extensionType(file.name);
+  } catch {
+    return extensionType(file.name);
+  }
+}
+
 async function inputSaveGame(input: AnalyzeInput): Promise<SaveGameInput> {
-  const game = extensionType(input.file.name);
+  const file =
+    input.kind === "handle" ? await input.file.getFile() : input.file;
+  const game = await detectGame(file);
   if (game === "eu4") {
     if (input.kind === "handle") {
-      const name = (await input.file.getFile()).name;
       return {
         kind: game,
         data: {
           kind: "handle",
           file: input.file,
-          name,
+          name: file.name,
         },
       };
     } else {
@@ -23,8 +41,6 @@ async function inputSaveGame(input: AnalyzeInput): Promise<SaveGameInput> {
       };
     }
   } else {
-    const file =
-      input.kind === "handle" ? await input.file.getFile() : input.file;
     return {
       kind: game,
       file,
@@ -7,11 +7,13 @@ import battles from "./images/mapmode_battles.png";
 import technology from "./images/mapmode_tech.png";
 import terrain from "./images/mapmode_terrain.png";
 import { MapControls } from "../../types/map";
-import { useEu4Actions, useEu4MapMode } from "../../store";
+import { useEu4Actions, useEu4MapMode, useSelectedTag } from "../../store";
 
 export const MapModeButtonGroup = () => {
   const mapMode = useEu4MapMode();
+  const selectedTag = useSelectedTag();
   const { setMapMode: updateMapMode } = useEu4Actions();
+  const isDiplomacy = typeof mapMode === "object" && "diplomacy" in mapMode;
 
   const modes: Record<MapControls["mode"], string> = {
     political,
@@ -43,6 +45,24 @@ export const MapModeButtonGroup = () => {
           </button>
         </Tooltip>
       ))}
+      <Tooltip mouseEnterDelay={1} title={`diplomacy (${selectedTag})`}>
+        <button
+          className="m-0 select-none border-none bg-transparent p-0"
+          onClick={() => updateMapMode({ diplomacy: { tag: selectedTag } })}
+        >
+          {/* no diplomacy icon ships with the app, so tint the political one */}
+          <Image
+            alt="diplomacy mapmode"
+            src={political}
+            width={41}
+            height={31}
+            className={`hue-rotate-180 ${
+              isDiplomacy ? "brightness-200" : ""
+            }`}
+            draggable={false}
+          />
+        </button>
+      </Tooltip>
     </>
   );
 };
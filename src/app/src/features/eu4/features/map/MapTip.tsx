@@ -1,5 +1,10 @@
 import { useEffect, useLayoutEffect, useRef, useState } from "react";
-import { useEu4Map, useEu4MapMode, useSelectedDate } from "../../store";
+import {
+  useEu4Map,
+  useEu4MapMode,
+  usePaintSubjectInOverlordHue,
+  useSelectedDate,
+} from "../../store";
 import { QuickTipPayload } from "../../types/map";
 import { getEu4Worker } from "../../worker";
 import { MapTipContents } from "./MapTipContents";
@@ -20,6 +25,7 @@ export const MapTip = () => {
   const [provinceId, setProvinceId] = useState(0);
   const mapMode = useEu4MapMode();
   const currentMapDate = useSelectedDate();
+  const paintSubjectInOverlordHue = usePaintSubjectInOverlordHue();
   const map = useEu4Map();
 
   useEffect(() => {
@@ -119,7 +125,12 @@ export const MapTip = () => {
 
     tooltipTimer.current = setTimeout(async () => {
       const days = currentMapDate.enabledDays ?? undefined;
-      const data = await worker.eu4GetMapTooltip(provinceId, mapMode, days);
+      const data = await worker.eu4GetMapTooltip(
+        provinceId,
+        mapMode,
+        days,
+        paintSubjectInOverlordHue
+      );
       if (isMounted) {
         setMapTip(data);
       }
@@ -131,7 +142,7 @@ export const MapTip = () => {
         clearTimeout(tooltipTimer.current);
       }
     };
-  }, [provinceId, mapMode, currentMapDate]);
+  }, [provinceId, mapMode, currentMapDate, paintSubjectInOverlordHue]);
 
   // When we are calculating (or don't want to display) the tooltip,
   // set the opacity to 0 instead of removing the display so that the width
@@ -1,5 +1,5 @@
 import { Descriptions, Divider } from "antd";
-import { QuickTipPayload } from "../../types/map";
+import { DiplomaticRelation, QuickTipPayload } from "../../types/map";
 import { FlagAvatarCore } from "../../components/avatars";
 import classes from "./MapTipContents.module.css";
 import { formatInt } from "@/lib/format";
@@ -25,6 +25,18 @@ const MapTipFlag = ({ tag, name }: MapTipFlagProps) => {
   );
 };
 
+const diplomaticRelationLabels: Record<DiplomaticRelation, string> = {
+  selected: "Selected",
+  overlord: "Overlord",
+  subject: "Subject",
+  atWar: "At war",
+  ally: "Ally",
+  guaranteed: "Guaranteed",
+  rival: "Rival",
+  truce: "Truce",
+  neutral: "Neutral",
+};
+
 const mapTagDescriptions = ({
   owner,
   controller,
@@ -96,6 +108,30 @@ const MapTipsTable = ({ tip }: MapTipContentsProps) => {
         </Descriptions>
       );
     }
+    case "diplomacy": {
+      return (
+        <Descriptions column={1} size="small">
+          {mapTagDescriptions(tip)}
+          <Descriptions.Item label="Relation">
+            <span>
+              {`${diplomaticRelationLabels[tip.relation]} of `}
+              <MapTipFlag tag={tip.selected.tag} name={tip.selected.name} />
+            </span>
+          </Descriptions.Item>
+          {tip.via ? (
+            <Descriptions.Item label="Through Overlord">
+              <MapTipFlag tag={tip.via.tag} name={tip.via.name} />
+            </Descriptions.Item>
+          ) : null}
+          {tip.war ? (
+            <Descriptions.Item label="War">{tip.war}</Descriptions.Item>
+          ) : null}
+          {tip.since ? (
+            <Descriptions.Item label="Since">{tip.since}</Descriptions.Item>
+          ) : null}
+        </Descriptions>
+      );
+    }
     case "technology": {
       return (
         <Descriptions column={1} size="small">
@@ -5,6 +5,7 @@ import { provinceIdToColorIndexInvert } from "@/features/eu4/features/map/resour
 import { useIsDeveloper } from "@/features/account";
 import { getEu4Worker } from "../../worker";
 import {
+  mapModeName,
   selectMapPayload,
   useEu4Context,
   useEu4Map,
@@ -50,7 +51,8 @@ export const MapExportMenu = ({ setIsExporting }: MapExportMenuProps) => {
     }
 
     let outName = meta.save_game.replace(".eu4", "");
-    outName = `${outName}-${meta.date}-${mapMode}-${suffix}.${exportType}`;
+    const mode = mapModeName(mapMode);
+    outName = `${outName}-${meta.date}-${mode}-${suffix}.${exportType}`;
 
     downloadData(data, outName);
     setIsExporting(false);
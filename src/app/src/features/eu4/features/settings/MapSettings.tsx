@@ -10,6 +10,7 @@ import { Timelapse } from "./Timelapse";
 import {
   useEu4Actions,
   useEu4MapMode,
+  mapModeName,
   usePaintSubjectInOverlordHue,
   useMapShowStripes,
   useTerrainOverlay,
@@ -46,7 +47,10 @@ const PaintSubjectInOverlordHueToggleRow = () => {
   const data = usePaintSubjectInOverlordHue();
   const { setPaintSubjectInOverlordHue } = useEu4Actions();
   const mapMode = useEu4MapMode();
-  const overlordHueDisabled = mapMode != "political";
+  // Subjects take on their overlord's relation in the diplomacy map mode
+  const overlordHueDisabled = !["political", "diplomacy"].includes(
+    mapModeName(mapMode)
+  );
 
   return (
     <ToggleRow
@@ -215,8 +215,13 @@ export const createEu4Store = async ({
         get().map.redrawMapImage();
       },
 
-      setSelectedTag: (tag: string) =>
-        set({ selectedTag: tag, countryDrawerVisible: true }),
+      setSelectedTag: (tag: string) => {
+        set({ selectedTag: tag, countryDrawerVisible: true });
+        const mode = get().mapMode;
+        if (typeof mode === "object" && "diplomacy" in mode) {
+          get().actions.setMapMode({ diplomacy: { tag } });
+        }
+      },
       setPrefersPercents: (checked: boolean) =>
         set({ prefereredValueFormat: checked ? "percent" : "absolute" }),
       setShowOneTimeLineItems: (checked: boolean) =>
@@ -392,7 +397,16 @@ export const useIsDatePickerEnabled = () => {
   return dateEnabledMapMode(mode);
 };
 
+/** The name of the map mode, without the parameters of object modes */
+export const mapModeName = (mode: MapPayload["kind"]) => {
+  return typeof mode === "object" ? Object.keys(mode)[0] : mode;
+};
+
 const dateEnabledMapMode = (mode: MapPayload["kind"]) => {
+  if (typeof mode === "object") {
+    return "diplomacy" in mode;
+  }
+
   return mode === "political" || mode === "religion" || mode === "battles";
 };
 
@@ -14,11 +14,46 @@ type MapMode =
   | "technology"
   | "terrain";
 
+export type PaletteName = "default" | "colorblind" | "highContrast";
+
+export interface PalettePayload {
+  name: PaletteName;
+  gradient?: [number, number, number][];
+}
+
+// Highlights provinces colonized between the days since the start of the
+// campaign
+export interface ColonialMapMode {
+  colonial: { start: number | null; end: number | null };
+}
+
+// Paints countries by how they relate to the given country
+export interface DiplomacyMapMode {
+  diplomacy: { tag: string };
+}
+
+export type DiplomaticRelation =
+  | "selected"
+  | "overlord"
+  | "subject"
+  | "atWar"
+  | "ally"
+  | "guaranteed"
+  | "rival"
+  | "truce"
+  | "neutral";
+
 export interface MapPayload {
-  kind: MapMode;
+  kind: MapMode | ColonialMapMode | DiplomacyMapMode;
   date: number | null;
   paintSubjectInOverlordHue: boolean;
   tagFilter: CountryMatcher;
+  palette?: PalettePayload;
+}
+
+export interface MapLegend {
+  entries: { key: string; color: string }[];
+  gradient: { min: number; max: number; stops: string[] } | null;
 }
 
 export interface PoliticalQuickTip {
@@ -69,12 +104,37 @@ export interface TechnologyQuickTip {
   milTech: number;
 }
 
+export interface DiplomacyQuickTip {
+  kind: "diplomacy";
+  owner: LocalizedTag;
+  controller: LocalizedTag;
+  provinceName: string;
+  provinceId: number;
+  selected: LocalizedTag;
+  relation: DiplomaticRelation;
+  since?: string;
+  war?: string;
+  via?: LocalizedTag;
+}
+
+export interface ColonialQuickTip {
+  kind: "colonial";
+  owner: LocalizedTag;
+  controller: LocalizedTag;
+  provinceName: string;
+  provinceId: number;
+  colonized?: string;
+  colonizer?: LocalizedTag;
+}
+
 export type QuickTipPayload =
   | PoliticalQuickTip
   | ReligionQuickTip
   | DevelopmentQuickTip
   | BattlesQuickTip
-  | TechnologyQuickTip;
+  | TechnologyQuickTip
+  | DiplomacyQuickTip
+  | ColonialQuickTip;
 
 // Controls that don't need to reach out to wasm
 export interface MapOnlyControls {
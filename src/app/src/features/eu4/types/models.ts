@@ -10,8 +10,27 @@ export interface CountryMatcher {
   include: string[];
   exclude: string[];
   includeSubjects: boolean;
+  expr?: TagFilterExpr;
+  ranking?: TagRanking;
 }
 
+export type TagRanking = "development" | "score" | "income";
+
+export type TagFilterExpr =
+  | { kind: "and"; exprs: TagFilterExpr[] }
+  | { kind: "or"; exprs: TagFilterExpr[] }
+  | { kind: "not"; expr: TagFilterExpr }
+  | { kind: "religionGroup"; group: string }
+  | { kind: "cultureGroup"; group: string }
+  | { kind: "government"; government: string }
+  | { kind: "minDevelopment"; development: number }
+  | { kind: "greatPowerRank"; max: number }
+  | { kind: "continent"; continent: string }
+  | { kind: "region"; region: string }
+  | { kind: "area"; area: string }
+  | { kind: "everPlayer" }
+  | { kind: "atWar"; tag: string };
+
 export type SaveMode = "Normal" | "Multiplayer" | "IronmanOk" | "IronmanNo";
 
 export interface Meta {
@@ -74,6 +93,7 @@ export interface LedgerDatum {
 
 export interface HealthData {
   data: CountryHealth[];
+  dropped: LocalizedTag[];
 }
 
 export interface CountryHealth {
@@ -441,6 +461,120 @@ export interface CountryManaSpend {
   other: number;
 }
 
+export interface ManaAmounts {
+  adm: number;
+  dip: number;
+  mil: number;
+}
+
+export interface ManaGenerationPoint {
+  date: string;
+  base: ManaAmounts;
+  ruler: ManaAmounts;
+  advisors: ManaAmounts;
+}
+
+export interface ManaSpentCategories {
+  technology: ManaAmounts;
+  development: ManaAmounts;
+  ideas: ManaAmounts;
+  stability: ManaAmounts;
+  cores: ManaAmounts;
+  other: ManaAmounts;
+}
+
+export interface AdvisorSkillTally {
+  skill: number;
+  advisors: number;
+  months: number;
+  mana: number;
+}
+
+export interface ManaEfficiency {
+  country: LocalizedTag;
+  generation: ManaGenerationPoint[];
+  generated: ManaAmounts;
+  spent: ManaSpentCategories;
+  stockpile: ManaAmounts;
+  wasted: ManaAmounts;
+  wastedShare: number;
+  advisors: AdvisorSkillTally[];
+  advisorDucats: number;
+  ducatsPerMana?: number;
+}
+
+export interface ActiveColony {
+  province: number;
+  name: string;
+  owner: LocalizedTag;
+  founded?: string;
+  settlers: number;
+  progress: number;
+  yearlyGrowth?: number;
+  estimatedCompletion?: string;
+}
+
+export interface ColonialNation {
+  country: LocalizedTag;
+  overlord: LocalizedTag;
+  subjectType: string;
+  since?: string;
+  region?: LocalizedObj;
+  provinces: number;
+  development: number;
+}
+
+export interface ColonizedProvince {
+  date: string;
+  province: number;
+  name: string;
+}
+
+export interface CountryColonization {
+  country: LocalizedTag;
+  provinces: ColonizedProvince[];
+}
+
+export interface ProvinceConversion {
+  date: string;
+  province: number;
+  name: string;
+  from?: string;
+  to: string;
+  owner?: LocalizedTag;
+  method: "missionary" | "cultureShift" | "event" | "unknown";
+}
+
+export interface ReformationSpread {
+  kind: "protestant" | "reformed" | "counterReformation";
+  firstDate?: string;
+  centers: { province: number; name: string }[];
+  provinces: [number, number][];
+}
+
+export interface CountryReligiousUnity {
+  country: LocalizedTag;
+  points: { year: number; unity: number }[];
+}
+
+export interface ReligionDevelopmentPoint {
+  year: number;
+  religion: string;
+  development: number;
+}
+
+export interface ReligionLegend extends LocalizedObj {
+  color: string;
+}
+
+export interface ReligionAnalytics {
+  conversions: ProvinceConversion[];
+  reformations: ReformationSpread[];
+  unity: CountryReligiousUnity[];
+  development: ReligionDevelopmentPoint[];
+  religions: ReligionLegend[];
+}
+
 export interface IdeaGroup {
   groupRank: number;
   groupName: string;
@@ -481,7 +615,7 @@ export interface Losses {
 export interface CountryLossesRaw {
   tag: string;
   name: string;
-  losses: number[];
+  losses: Losses;
 }
 
 export interface CountryLosses extends Losses {
@@ -503,7 +637,7 @@ export interface WarSideRaw {
   original: string;
   original_name: string;
   members: string[];
-  losses: number[];
+  losses: Losses;
 }
 
 export type War = WarRaw & {
@@ -520,7 +654,7 @@ export type WarSide = WarSideRaw & {
 export interface RawWarParticipant {
   tag: string;
   name: string;
-  losses: number[];
+  losses: Losses;
   participation: number;
   participation_percent: number;
   joined: string | null;
@@ -549,6 +683,90 @@ export interface WarInfo {
   defender_participants: WarParticipant[];
 }
 
+export type WarTimelineSide = "attacker" | "defender";
+
+export interface WarTimeline {
+  start: string;
+  end: string | null;
+  events: WarTimelineEvent[];
+  occupations: WarOccupation[];
+  fronts: WarFront[];
+  peace: ProvinceTransfer[];
+}
+
+export type WarTimelineEvent =
+  | {
+      kind: "joined";
+      date: string;
+      country: LocalizedTag;
+      side: WarTimelineSide;
+    }
+  | {
+      kind: "left";
+      date: string;
+      country: LocalizedTag;
+      side: WarTimelineSide;
+    }
+  | {
+      kind: "battle";
+      date: string;
+      name: string;
+      location: number;
+      winner: WarTimelineSide | null;
+      losses: number;
+    };
+
+export interface WarOccupation {
+  date: string;
+  province: number;
+  name: string;
+  owner: LocalizedTag;
+  controller: LocalizedTag;
+  occupier: WarTimelineSide | null;
+}
+
+export interface WarFront {
+  date: string;
+  attackerOccupied: number;
+  defenderOccupied: number;
+}
+
+export interface ProvinceTransfer {
+  province: number;
+  name: string;
+  from: LocalizedTag;
+  to: LocalizedTag;
+}
+
+export interface CommanderRecord {
+  name: string;
+  country: LocalizedTag;
+  stats: string;
+  battles: number;
+  wins: number;
+  losses: number;
+  enemyLosses: number;
+  casualtyRatio: number | null;
+}
+
+export interface CommanderRankings {
+  generals: CommanderRecord[];
+  admirals: CommanderRecord[];
+}
+
+export interface BattleOutcome {
+  key: string;
+  battles: number;
+  wins: number;
+  losses: number;
+  enemyLosses: number;
+}
+
+export interface BattleOutcomes {
+  compositions: BattleOutcome[];
+  terrains: BattleOutcome[];
+}
+
 export interface BattleInfo {
   name: string;
   date: string;
@@ -744,7 +962,7 @@ export interface CountryCulture {
 
 export interface SingleCountryWarCasualtiesRaw {
   war: string;
-  losses: number[];
+  losses: Losses;
   participation: number;
   participation_percent: number;
   start: string | null;
@@ -815,6 +1033,83 @@ export interface InfludenceModifier {
   date: string;
 }
 
+export interface EstatePrivilege {
+  name: string;
+  granted: string;
+  daysHeld: number;
+}
+
+export interface EstateInfluencePoint {
+  date: string;
+  desc: string;
+  value: number;
+  cumulative: number;
+}
+
+export interface EstateHistory {
+  kind: string;
+  loyalty: number;
+  territory: number;
+  completedAgendas: number;
+  privileges: EstatePrivilege[];
+  influence: EstateInfluencePoint[];
+}
+
+export interface CountryEstateHistory {
+  country: LocalizedTag;
+  crownLand: number;
+  estates: EstateHistory[];
+}
+
+export interface DynastyReign {
+  name: string;
+  country: LocalizedTag;
+  start: string;
+  end?: string;
+  months: number;
+  adm: number;
+  dip: number;
+  mil: number;
+}
+
+export interface DynastyMember {
+  name: string;
+  country: LocalizedTag;
+  role: "heir" | "consort";
+  date: string;
+}
+
+export interface ThroneAccession {
+  date: string;
+  country: LocalizedTag;
+  previousDynasty: string;
+}
+
+export interface DynastyUnion {
+  senior: LocalizedTag;
+  junior: LocalizedTag;
+  start?: string;
+}
+
+export interface Dynasty {
+  name: string;
+  countries: LocalizedTag[];
+  reigns: DynastyReign[];
+  members: DynastyMember[];
+  accessions: ThroneAccession[];
+  unions: DynastyUnion[];
+  reignYears: number;
+  avgAdm: number;
+  avgDip: number;
+  avgMil: number;
+}
+
+export interface DynastyDevelopment {
+  name: string;
+  development: number;
+  countries: LocalizedTag[];
+}
+
 export type Development = {
   tax: number;
   production: number;
@@ -3,6 +3,7 @@ import { LedgerDatum, LocalizedTag } from "../types/models";
 export interface LedgerDataRaw {
   points: Omit<LedgerDatum, "name">[];
   localization: LocalizedTag[];
+  dropped: LocalizedTag[];
 }
 
 export function workLedgerData(data: LedgerDataRaw): LedgerDatum[] {
@@ -35,12 +35,22 @@ import type {
   War,
   WarInfo,
   WarRaw,
+  WarTimeline,
+  CommanderRankings,
+  BattleOutcomes,
   CountryAdvisors,
   Estate,
+  CountryEstateHistory,
+  Dynasty,
+  DynastyDevelopment,
+  ManaEfficiency,
+  ActiveColony,
+  ColonialNation,
+  CountryColonization,
+  ReligionAnalytics,
 } from "../types/models";
-import { MapPayload, QuickTipPayload } from "../types/map";
+import { MapLegend, MapPayload, QuickTipPayload } from "../types/map";
 import { LedgerDataRaw, workLedgerData } from "../utils/ledger";
-import { expandLosses } from "../utils/losses";
 import { wasm } from "./common";
 import { TimelapseIter } from "../../../../../wasm-eu4/pkg/wasm_eu4";
 export * from "./init";
@@ -64,29 +74,32 @@ export type MapColors = {
   primary: Uint8Array;
   secondary: Uint8Array;
   country?: Uint8Array;
+  legend: MapLegend;
 };
 
 export function eu4MapColors(payload: MapPayload): MapColors {
-  const arr = wasm.save.map_colors(payload);
+  const colors = wasm.save.map_colors(payload);
+  const legend = colors.legend() as MapLegend;
+  const arr = colors.data();
   if (payload.kind == "political") {
     const primary = arr.subarray(0, arr.length / 2);
     const secondary = arr.subarray(arr.length / 2);
     const country = primary;
-    return transfer({ primary, secondary, country }, [arr.buffer]);
+    return transfer({ primary, secondary, country, legend }, [arr.buffer]);
   } else if (payload.kind == "battles") {
     const primary = arr.subarray(0, arr.length / 3);
     const secondary = arr.subarray(arr.length / 3, (arr.length * 2) / 3);
     const country = arr.subarray((arr.length * 2) / 3);
-    return transfer({ primary, secondary, country }, [arr.buffer]);
+    return transfer({ primary, secondary, country, legend }, [arr.buffer]);
   } else if (payload.date != null && payload.kind == "religion") {
     const primary = arr.subarray(0, arr.length / 3);
     const secondary = arr.subarray(arr.length / 3, (arr.length * 2) / 3);
     const country = arr.subarray((arr.length * 2) / 3);
-    return transfer({ primary, secondary, country }, [arr.buffer]);
+    return transfer({ primary, secondary, country, legend }, [arr.buffer]);
   } else {
     const primary = arr.subarray(0, arr.length / 2);
     const secondary = arr.subarray(arr.length / 2);
-    return transfer({ primary, secondary }, [arr.buffer]);
+    return transfer({ primary, secondary, legend }, [arr.buffer]);
   }
 }
 
@@ -127,9 +140,10 @@ export function mapTimelapseNext(): MapTimelapseItem | undefined {
 }
 
 export function mapTimelapse(payload: {
-  kind: "political" | "religion" | "battles";
+  kind: "political" | "religion" | "battles" | "war";
   interval: "year" | "month" | "week" | "day";
   start: number | null;
+  war?: string;
 }) {
   mapCursor = wasm.save.map_cursor(payload);
 }
@@ -154,6 +168,14 @@ export function eu4GetCountryRulers(tag: string): RunningMonarch[] {
   return save.get_country_rulers(tag) as RunningMonarch[];
 }
 
+export function eu4GetDynasties(): Dynasty[] {
+  return wasm.save.get_dynasties() as Dynasty[];
+}
+
+export function eu4GetDynastyDevelopment(days?: number): DynastyDevelopment[] {
+  return wasm.save.get_dynasty_development(days) as DynastyDevelopment[];
+}
+
 export function eu4GetCountryAdvisors(tag: string): CountryAdvisors {
   const save = wasm.save;
   return save.get_country_advisors(tag);
@@ -184,6 +206,10 @@ export function eu4GetCountryEstates(tag: string): Estate[] {
   return save.get_country_estates(tag) as Estate[];
 }
 
+export function eu4GetEstateHistory(tags: string[]): CountryEstateHistory[] {
+  return wasm.save.get_estate_history(tags) as CountryEstateHistory[];
+}
+
 export function eu4InitialMapPosition() {
   const result = wasm.save.initial_map_position();
   return result as [number, number];
@@ -218,6 +244,34 @@ export function eu4GetNationIdeaGroups(matcher: CountryMatcher): IdeaGroup[] {
   }));
 }
 
+export function eu4GetManaEfficiency(
+  matcher: CountryMatcher
+): ManaEfficiency[] {
+  return wasm.save.get_mana_efficiency(matcher) as ManaEfficiency[];
+}
+
+export function eu4GetActiveColonies(matcher: CountryMatcher): ActiveColony[] {
+  return wasm.save.get_active_colonies(matcher) as ActiveColony[];
+}
+
+export function eu4GetColonialNations(): ColonialNation[] {
+  return wasm.save.get_colonial_nations() as ColonialNation[];
+}
+
+export function eu4GetColonizationTimeline(
+  matcher: CountryMatcher
+): CountryColonization[] {
+  return wasm.save.get_colonization_timeline(
+    matcher
+  ) as CountryColonization[];
+}
+
+export function eu4GetReligionAnalytics(
+  matcher: CountryMatcher
+): ReligionAnalytics {
+  return wasm.save.get_religion_analytics(matcher) as ReligionAnalytics;
+}
+
 export function eu4GetAnnualIncomeData(filter: CountryMatcher): LedgerDatum[] {
   const data = wasm.save.get_annual_income_ledger(filter) as LedgerDataRaw;
   return workLedgerData(data);
@@ -302,62 +356,42 @@ export function eu4GetCountriesWarLosses(
 ): CountryLosses[] {
   const result = wasm.save.countries_war_losses(filter) as CountryLossesRaw[];
 
-  return result.map(({ losses, ...rest }) => {
-    return {
-      ...rest,
-      ...expandLosses(losses),
-    };
-  });
+  return result.map(({ losses, ...rest }) => ({ ...rest, ...losses }));
 }
 
 export function eu4GetSingleCountryCasualties(
   tag: string
 ): SingleCountryWarCasualties[] {
-  const raw = wasm.save.get_country_casualties(
+  return wasm.save.get_country_casualties(
     tag
   ) as SingleCountryWarCasualtiesRaw[];
-  return raw.map((x) => ({
-    ...x,
-    losses: expandLosses(x.losses),
-  }));
 }
 
 export function eu4GetWars(filter: CountryMatcher): War[] {
   const data = wasm.save.wars(filter) as WarRaw[];
-  return data.map((x) => {
-    const attackerLosses = expandLosses(x.attackers.losses);
-    const defenderLosses = expandLosses(x.defenders.losses);
-    return {
-      ...x,
-      totalBattleLosses:
-        attackerLosses.totalBattle + defenderLosses.totalBattle,
-      totalAttritionLosses:
-        attackerLosses.totalAttrition + defenderLosses.totalAttrition,
-      attackers: {
-        ...x.attackers,
-        losses: expandLosses(x.attackers.losses),
-      },
-      defenders: {
-        ...x.defenders,
-        losses: expandLosses(x.defenders.losses),
-      },
-    } as War;
-  });
+  return data.map((x) => ({
+    ...x,
+    totalBattleLosses:
+      x.attackers.losses.totalBattle + x.defenders.losses.totalBattle,
+    totalAttritionLosses:
+      x.attackers.losses.totalAttrition + x.defenders.losses.totalAttrition,
+  }));
 }
 
 export function eu4GetWarInfo(war: string): WarInfo {
-  const raw = wasm.save.get_war(war) as RawWarInfo;
-  return {
-    ...raw,
-    attacker_participants: raw.attacker_participants.map((x) => ({
-      ...x,
-      losses: expandLosses(x.losses),
-    })),
-    defender_participants: raw.defender_participants.map((x) => ({
-      ...x,
-      losses: expandLosses(x.losses),
-    })),
-  };
+  return wasm.save.get_war(war) as RawWarInfo;
+}
+
+export function eu4GetWarTimeline(war: string): WarTimeline | undefined {
+  return wasm.save.get_war_timeline(war) as WarTimeline | undefined;
+}
+
+export function eu4GetCommanderRankings(): CommanderRankings {
+  return wasm.save.get_commander_rankings() as CommanderRankings;
+}
+
+export function eu4GetBattleOutcomes(): BattleOutcomes {
+  return wasm.save.get_battle_outcomes() as BattleOutcomes;
 }
 
 export function eu4DateToDays(s: string): number {
@@ -375,9 +409,17 @@ export function eu4GetProvinceDeteails(id: number): ProvinceDetails | null {
 export function eu4GetMapTooltip(
   province: number,
   payload: MapPayload["kind"],
-  date: number | undefined
+  date: number | undefined,
+  paintSubjectInOverlordHue: boolean
 ): QuickTipPayload | null {
-  return wasm.save.map_quick_tip(province, payload, date) ?? null;
+  return (
+    wasm.save.map_quick_tip(
+      province,
+      payload,
+      date,
+      paintSubjectInOverlordHue
+    ) ?? null
+  );
 }
 
 export async function eu4SaveHash(): Promise<string> {
@@ -24,6 +24,12 @@ export const Hoi4Page = ({ save, meta }: Hoi4PageProps) => {
         <p>
           {`An Hoi4 save was detected (date ${meta.date}). At this time, Hoi4 functionality is limited but one can still melt binary saves into plaintext`}
         </p>
+        {meta.gamestateError && (
+          <Alert
+            type="warning"
+            message={`Only the save header could be read: ${meta.gamestateError}`}
+          />
+        )}
         {meta.isMeltable && (
           <MeltButton
             game="hoi4"
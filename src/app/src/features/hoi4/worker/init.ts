@@ -12,3 +12,8 @@ export async function parseHoi4() {
   const meta: Hoi4Metadata = wasm.save.metadata();
   return { meta };
 }
+
+/** Localizes names with the zstd compressed game data from compile_assets */
+export function hoi4SetGameData(data: Uint8Array) {
+  wasm.save.set_game_data(data);
+}
@@ -1,3 +1,43 @@
+import {
+  BorderFill,
+  ProvinceMapColors,
+  ProvinceRaster,
+  fillBorders,
+  transferMapColors,
+} from "@/lib/provinceMap";
 import { wasm } from "./common";
+import {
+  Hoi4CountryForces,
+  Hoi4FactionOverview,
+  Hoi4StateProvinces,
+  Hoi4Territory,
+} from "./types";
 export * from "./init";
 export const melt = () => wasm.melt();
+
+export function hoi4GetFactions(): Hoi4FactionOverview[] {
+  return wasm.save.get_factions() as Hoi4FactionOverview[];
+}
+
+export function hoi4GetCountryForces(tags: string[] = []): Hoi4CountryForces[] {
+  return wasm.save.get_country_forces({ tags }) as Hoi4CountryForces[];
+}
+
+export function hoi4GetTerritory(tags: string[] = []): Hoi4Territory {
+  return wasm.save.get_territory({ tags }) as Hoi4Territory;
+}
+
+export function hoi4MapColors(
+  provinces: number,
+  states: Hoi4StateProvinces[]
+): ProvinceMapColors {
+  return transferMapColors(wasm.save.map_colors({ provinces, states }));
+}
+
+export function hoi4FillBorders(
+  raster: ProvinceRaster,
+  colors: ProvinceMapColors,
+  fill: BorderFill
+): Uint8Array {
+  return fillBorders(wasm.module.map_fill_borders, raster, colors, fill);
+}
@@ -1,4 +1,65 @@
 export interface Hoi4Metadata {
   date: string;
   isMeltable: boolean;
+  gamestateError?: string;
 }
+
+export interface EquipmentStock {
+  equipment: string;
+  amount: number;
+}
+
+export interface Hoi4CountryForces {
+  tag: string;
+  name: string;
+  divisions: number;
+  manpower: number;
+  lockedManpower: number;
+  equipment: EquipmentStock[];
+  wars: string[];
+}
+
+export interface Hoi4FactionMember {
+  tag: string;
+  name: string;
+  divisions: number;
+  manpower: number;
+  states: number;
+}
+
+export interface Hoi4FactionOverview {
+  name: string;
+  leader: string;
+  members: Hoi4FactionMember[];
+  divisions: number;
+  manpower: number;
+  states: number;
+  wars: string[];
+}
+
+export interface Hoi4StateControl {
+  id: number;
+  name: string | null;
+  owner: string;
+  controller: string;
+}
+
+export interface Hoi4CountryTerritory {
+  tag: string;
+  name: string;
+  owned: number;
+  controlled: number;
+  lost: number;
+  occupied: number;
+}
+
+export interface Hoi4Territory {
+  states: Hoi4StateControl[];
+  countries: Hoi4CountryTerritory[];
+}
+
+/** The provinces of a state (the `states.json` written by compile_assets) */
+export type Hoi4StateProvinces = {
+  id: number;
+  provinces: number[];
+};
@@ -24,6 +24,12 @@ export const ImperatorPage = ({ save, meta }: ImperatorPageProps) => {
         <p>
           {`An Imperator save was detected (date ${meta.date}). At this time, Imperator functionality is limited but one can still melt binary saves into plaintext`}
         </p>
+        {meta.gamestateError && (
+          <Alert
+            type="warning"
+            message={`Only the save header could be read: ${meta.gamestateError}`}
+          />
+        )}
         {meta.isMeltable && (
           <MeltButton
             game="imperator"
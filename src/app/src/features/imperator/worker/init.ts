@@ -12,3 +12,8 @@ export async function parseImperator() {
   const meta: ImperatorMetadata = wasm.save.metadata();
   return { meta };
 }
+
+/** Localizes names with the zstd compressed game data from compile_assets */
+export function imperatorSetGameData(data: Uint8Array) {
+  wasm.save.set_game_data(data);
+}
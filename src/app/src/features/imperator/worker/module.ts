@@ -1,3 +1,43 @@
+import {
+  BorderFill,
+  ProvinceMapColors,
+  ProvinceRaster,
+  fillBorders,
+  transferMapColors,
+} from "@/lib/provinceMap";
 import { wasm } from "./common";
+import {
+  ImperatorCountryDetails,
+  ImperatorCountryOverview,
+  ImperatorWarSummary,
+} from "./types";
 export * from "./init";
 export const melt = () => wasm.melt();
+
+export function imperatorGetCountries(): ImperatorCountryOverview[] {
+  return wasm.save.get_countries() as ImperatorCountryOverview[];
+}
+
+export function imperatorGetCountryDetails(
+  tag: string
+): ImperatorCountryDetails | undefined {
+  return wasm.save.get_country_details({ tag }) as
+    | ImperatorCountryDetails
+    | undefined;
+}
+
+export function imperatorGetWars(tags: string[] = []): ImperatorWarSummary[] {
+  return wasm.save.get_wars({ tags }) as ImperatorWarSummary[];
+}
+
+export function imperatorMapColors(provinces: number): ProvinceMapColors {
+  return transferMapColors(wasm.save.map_colors({ provinces }));
+}
+
+export function imperatorFillBorders(
+  raster: ProvinceRaster,
+  colors: ProvinceMapColors,
+  fill: BorderFill
+): Uint8Array {
+  return fillBorders(wasm.module.map_fill_borders, raster, colors, fill);
+}
@@ -2,4 +2,48 @@ export interface ImperatorMetadata {
   date: string;
   version: string;
   isMeltable: boolean;
+  gamestateError?: string;
+}
+
+export interface ImperatorCountryOverview {
+  id: number;
+  tag: string;
+  name: string;
+  ruler: string | null;
+  provinces: number;
+  pops: number;
+  civilization: number;
+  gold: number;
+  manpower: number;
+  politicalInfluence: number;
+}
+
+export interface PopCount {
+  key: string;
+  count: number;
+}
+
+export interface ImperatorFamilySummary {
+  id: number;
+  key: string;
+  prestige: number;
+  members: number;
+}
+
+export interface ImperatorCountryDetails {
+  overview: ImperatorCountryOverview;
+  popTypes: PopCount[];
+  cultures: PopCount[];
+  religions: PopCount[];
+  families: ImperatorFamilySummary[];
+  characters: number;
+}
+
+export interface ImperatorWarSummary {
+  id: number;
+  name: string | null;
+  start: string | null;
+  active: boolean;
+  attackers: string[];
+  defenders: string[];
 }
@@ -25,6 +25,12 @@ export const Vic3Page = ({ save, meta }: Vic3PageProps) => {
         <p>
           {`A Vic3 save was detected (date ${meta.date}). At this time, Vic3 functionality is limited but one can still melt binary saves into plaintext`}
         </p>
+        {meta.gamestateError && (
+          <Alert
+            type="warning"
+            message={`Only the save header could be read: ${meta.gamestateError}`}
+          />
+        )}
         {meta.isMeltable && (
           <MeltButton
             game="vic3"
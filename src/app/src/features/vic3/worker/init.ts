@@ -12,3 +12,8 @@ export async function parseVic3() {
   const meta: Vic3Metadata = wasm.save.metadata();
   return { meta };
 }
+
+/** Localizes names with the zstd compressed game data from compile_assets */
+export function vic3SetGameData(data: Uint8Array) {
+  wasm.save.set_game_data(data);
+}
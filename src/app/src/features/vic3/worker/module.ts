@@ -1,3 +1,68 @@
+import {
+  BorderFill,
+  ProvinceMapColors,
+  ProvinceRaster,
+  fillBorders,
+  transferMapColors,
+} from "@/lib/provinceMap";
 import { wasm } from "./common";
+import {
+  PopRankingKind,
+  RankedPop,
+  Vic3CountryDemographics,
+  Vic3CountryOverview,
+  Vic3CountrySeries,
+  Vic3EconomySeries,
+  Vic3Metric,
+  Vic3StateDemographics,
+} from "./types";
 export * from "./init";
 export const melt = () => wasm.melt();
+
+export function vic3GetCountries(): Vic3CountryOverview[] {
+  return wasm.save.get_countries() as Vic3CountryOverview[];
+}
+
+export function vic3GetTopCountries(
+  metric: Vic3Metric,
+  limit: number
+): Vic3CountrySeries[] {
+  return wasm.save.get_top_countries({ metric, limit }) as Vic3CountrySeries[];
+}
+
+export function vic3GetEconomySeries(tags: string[] = []): Vic3EconomySeries[] {
+  return wasm.save.get_economy_series({ tags }) as Vic3EconomySeries[];
+}
+
+export function vic3GetStateDemographics(
+  tags: string[] = []
+): Vic3StateDemographics[] {
+  return wasm.save.get_state_demographics({ tags }) as Vic3StateDemographics[];
+}
+
+export function vic3GetCountryDemographics(
+  tags: string[] = []
+): Vic3CountryDemographics[] {
+  return wasm.save.get_country_demographics({
+    tags,
+  }) as Vic3CountryDemographics[];
+}
+
+export function vic3GetPopRanking(
+  kind: PopRankingKind,
+  limit: number
+): RankedPop[] {
+  return wasm.save.get_pop_ranking({ kind, limit }) as RankedPop[];
+}
+
+export function vic3MapColors(provinces: number): ProvinceMapColors {
+  return transferMapColors(wasm.save.map_colors({ provinces }));
+}
+
+export function vic3FillBorders(
+  raster: ProvinceRaster,
+  colors: ProvinceMapColors,
+  fill: BorderFill
+): Uint8Array {
+  return fillBorders(wasm.module.map_fill_borders, raster, colors, fill);
+}
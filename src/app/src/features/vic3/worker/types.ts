@@ -1,4 +1,94 @@
 export interface Vic3Metadata {
   date: string;
   isMeltable: boolean;
+  gamestateError?: string;
+}
+
+export type Vic3Metric =
+  | "gdp"
+  | "population"
+  | "standardOfLiving"
+  | "prestige"
+  | "literacy";
+
+export interface Vic3CountryOverview {
+  id: number;
+  tag: string;
+  name: string;
+  gdp: number | null;
+  population: number;
+  standardOfLiving: number | null;
+  states: number;
+  buildings: number;
+  marketLeader: boolean;
+}
+
+export interface Vic3Sample {
+  date: string;
+  value: number;
+}
+
+export interface Vic3CountrySeries {
+  id: number;
+  tag: string;
+  name: string;
+  data: Vic3Sample[];
+}
+
+export interface Vic3EconomySeries {
+  id: number;
+  tag: string;
+  name: string;
+  gdp: Vic3Sample[];
+  prestige: Vic3Sample[];
+  literacy: Vic3Sample[];
+}
+
+export type Vic3Strata = "upper" | "middle" | "lower";
+
+export interface PopShare<T> {
+  key: T;
+  size: number;
+}
+
+export interface Vic3Demographics {
+  population: number;
+  workforce: number;
+  literacy: number;
+  wealth: number;
+  cultures: PopShare<string>[];
+  religions: PopShare<string>[];
+  professions: PopShare<string>[];
+  strata: PopShare<Vic3Strata>[];
+}
+
+export interface Vic3StateDemographics {
+  id: number;
+  tag: string;
+  countryName: string;
+  region: string | null;
+  name: string | null;
+  demographics: Vic3Demographics;
+}
+
+export interface Vic3CountryDemographics {
+  id: number;
+  tag: string;
+  name: string;
+  demographics: Vic3Demographics;
+}
+
+export type PopRankingKind = "radical" | "loyal";
+
+export interface RankedPop {
+  id: number;
+  profession: string;
+  culture: string;
+  religion: string | null;
+  state: number;
+  tag: string;
+  size: number;
+  radicals: number;
+  loyalists: number;
+  share: number;
 }
@@ -0,0 +1,81 @@
+import { transfer } from "comlink";
+
+export type BorderFill = "None" | "Provinces" | "Countries";
+
+/** RGBA province colors as returned by a save's `map_colors` */
+export type ProvinceMapColors = {
+  primary: Uint8Array;
+  secondary: Uint8Array;
+};
+
+export type ProvinceRaster = {
+  width: number;
+  height: number;
+  provinces: Uint16Array;
+};
+
+/** Splits the colors from `map_colors` and transfers them to the caller */
+export function transferMapColors(data: Uint8Array): ProvinceMapColors {
+  const primary = data.subarray(0, data.length / 2);
+  const secondary = data.subarray(data.length / 2);
+  return transfer({ primary, secondary }, [data.buffer]);
+}
+
+/** Decodes a decompressed `provinces.bin.zst` written by compile_assets */
+export function decodeProvinceRaster(data: Uint8Array): ProvinceRaster {
+  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  const width = view.getUint32(0, true);
+  const height = view.getUint32(4, true);
+  const provinces = new Uint16Array(width * height);
+  for (let i = 0; i < provinces.length; i++) {
+    provinces[i] = view.getUint16(8 + i * 2, true);
+  }
+  return { width, height, provinces };
+}
+
+/** One more than the largest province id in the raster */
+export function provinceCount(raster: ProvinceRaster) {
+  let max = 0;
+  for (const id of raster.provinces) {
+    max = Math.max(max, id);
+  }
+  return raster.provinces.length == 0 ? 0 : max + 1;
+}
+
+/** Converts RGBA province colors to the RGB layout of `map_fill_borders` */
+export function rgbColors(colors: Uint8Array): Uint8Array {
+  const result = new Uint8Array((colors.length / 4) * 3);
+  for (let i = 0; i < colors.length / 4; i++) {
+    result.set(colors.subarray(i * 4, i * 4 + 3), i * 3);
+  }
+  return result;
+}
+
+/** The `map_fill_borders` export shared by each game's wasm module */
+export type FillBordersFn = (
+  data: Uint8Array,
+  provinces: Uint16Array,
+  primary: Uint8Array,
+  secondary: Uint8Array,
+  fill: string,
+  width: number
+) => void;
+
+/** Paints the province colors onto an RGBA canvas and transfers it */
+export function fillBorders(
+  fillFn: FillBordersFn,
+  raster: ProvinceRaster,
+  colors: ProvinceMapColors,
+  fill: BorderFill
+): Uint8Array {
+  const data = new Uint8Array(raster.provinces.length * 4);
+  fillFn(
+    data,
+    raster.provinces,
+    rgbColors(colors.primary),
+    rgbColors(colors.secondary),
+    fill,
+    raster.width
+  );
+  return transfer(data, [data.buffer]);
+}
@@ -0,0 +1,69 @@
+//! Accessors for the CK3 game data written by compile_assets
+
+use schemas::binary_search_by;
+
+#[derive(Debug)]
+pub struct Game<'a> {
+    data: schemas::ck3::Game<'a>,
+}
+
+impl<'a> Game<'a> {
+    pub fn from_flatbuffer(data: &'a [u8]) -> Self {
+        let fb = schemas::ck3::root_as_game(data).unwrap();
+        Self { data: fb }
+    }
+
+    /// The localized name of a landed title (eg: `k_france`)
+    pub fn localize_title(&self, key: &str) -> Option<&'a str> {
+        let titles = self.data.titles()?;
+        let idx = binary_search_by(&titles, |x| x.key_compare_with_value(key)).ok()?;
+        titles.get(idx).value()
+    }
+
+    pub fn localize_culture(&self, key: &str) -> Option<&'a str> {
+        let cultures = self.data.cultures()?;
+        let idx = binary_search_by(&cultures, |x| x.key_compare_with_value(key)).ok()?;
+        cultures.get(idx).value()
+    }
+
+    /// The localized name of a dynasty or house from the name key the save
+    /// records (eg: `dynn_Capet`)
+    pub fn localize_dynasty(&self, key: &str) -> Option<&'a str> {
+        let dynasties = self.data.dynasties()?;
+        let idx = binary_search_by(&dynasties, |x| x.key_compare_with_value(key)).ok()?;
+        dynasties.get(idx).value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemas::builder::ck3_entries;
+    use schemas::ck3::GameArgs;
+    use schemas::flatbuffers::FlatBufferBuilder;
+
+    #[test]
+    fn test_localize() {
+        let mut buffer = FlatBufferBuilder::new();
+        let titles = ck3_entries(
+            &mut buffer,
+            [("e_france", "France"), ("k_france", "France")],
+        );
+        let dynasties = ck3_entries(&mut buffer, [("dynn_Capet", "Capet")]);
+        let game = schemas::ck3::Game::create(
+            &mut buffer,
+            &GameArgs {
+                titles: Some(titles),
+                cultures: None,
+                dynasties: Some(dynasties),
+            },
+        );
+        buffer.finish(game, None);
+
+        let game = Game::from_flatbuffer(buffer.finished_data());
+        assert_eq!(game.localize_title("k_france"), Some("France"));
+        assert_eq!(game.localize_title("d_paris"), None);
+        assert_eq!(game.localize_culture("french"), None);
+        assert_eq!(game.localize_dynasty("dynn_Capet"), Some("Capet"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Localization bundles for the games other than EU4. EU4 game data is
+//! written by [super::tarball::parse_game_dir].
+
+use super::{localization, province_raster};
+use crate::zstd_tee::ZstdTee;
+use anyhow::{bail, Context};
+use schemas::{builder, flatbuffers::FlatBufferBuilder};
+use serde::{
+    de::{self, IgnoredAny},
+    Deserialize, Deserializer,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub fn translate_game_data(
+    game: &str,
+    tmp_game_dir: &Path,
+    out_game_dir: &Path,
+) -> anyhow::Result<()> {
+    let mut buffer = FlatBufferBuilder::new();
+    match game {
+        "ck3" => ck3_game_data(&mut buffer, tmp_game_dir)?,
+        "hoi4" => hoi4_game_data(&mut buffer, tmp_game_dir)?,
+        "imperator" => imperator_game_data(&mut buffer, tmp_game_dir)?,
+        "vic3" => vic3_game_data(&mut buffer, tmp_game_dir)?,
+        _ => bail!("no game data for game: {}", game),
+    }
+
+    let mut writer = ZstdTee::create(out_game_dir.join("data"))?;
+    writer.write_all(buffer.finished_data())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// The keys of the top level objects of a file, skipping variables
+#[derive(Debug, Default, PartialEq)]
+struct TopLevelKeys(Vec<String>);
+
+impl<'de> Deserialize<'de> for TopLevelKeys {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TopLevelKeysVisitor;
+
+        impl<'de> de::Visitor<'de> for TopLevelKeysVisitor {
+            type Value = TopLevelKeys;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of game objects")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut result = Vec::new();
+                while let Some(key) = map.next_key::<&str>()? {
+                    map.next_value::<IgnoredAny>()?;
+                    if !key.starts_with('@') {
+                        result.push(String::from(key));
+                    }
+                }
+
+                Ok(TopLevelKeys(result))
+            }
+        }
+
+        deserializer.deserialize_map(TopLevelKeysVisitor)
+    }
+}
+
+fn top_level_keys(dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut result = Vec::new();
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.path().extension().map_or(true, |x| x != "txt") {
+            continue;
+        }
+
+        let data = fs::read(entry.path())?;
+        let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&data);
+        let keys: TopLevelKeys = jomini::text::de::from_utf8_slice(data)
+            .with_context(|| format!("unable to parse: {}", entry.path().display()))?;
+        result.extend(keys.0);
+    }
+
+    Ok(result)
+}
+
+/// Pairs each key with its localization, sorted and deduplicated by key as
+/// flatbuffer key lookups require
+fn localized<I>(keys: I, localization: &HashMap<String, String>) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut result: Vec<_> = keys
+        .into_iter()
+        .filter_map(|key| {
+            let value = localization.get(&key)?.clone();
+            Some((key, value))
+        })
+        .collect();
+    result.sort_unstable();
+    result.dedup_by(|a, b| a.0 == b.0);
+    result
+}
+
+/// Countries sorted by tag along with their name and adjective
+fn localized_countries(localization: &HashMap<String, String>) -> Vec<(String, String, String)> {
+    let mut result: Vec<_> = localization::country_localization(localization)
+        .into_iter()
+        .map(|(tag, country)| (String::from(tag.as_str()), country.name, country.adjective))
+        .collect();
+    result.sort_unstable();
+    result
+}
+
+/// The province id of an Imperator province localization key (eg: `PROV123`)
+fn imperator_province_id(key: &str) -> Option<u16> {
+    key.strip_prefix("PROV")?.parse().ok()
+}
+
+fn ck3_game_data(buffer: &mut FlatBufferBuilder, tmp_game_dir: &Path) -> anyhow::Result<()> {
+    use schemas::ck3::{Game, GameArgs};
+
+    let localization = localization::english_localization_tree(tmp_game_dir.join("localization"))?;
+
+    let titles = province_raster::parse_landed_titles(tmp_game_dir)?;
+    let titles = builder::ck3_entries(buffer, localized(titles.titles, &localization));
+
+    let cultures_dir = tmp_game_dir.join("common").join("culture").join("cultures");
+    let cultures = top_level_keys(&cultures_dir)?;
+    let cultures = builder::ck3_entries(buffer, localized(cultures, &localization));
+
+    // Dynasties and houses in a save refer to their name by these keys
+    let dynasties = localization
+        .keys()
+        .filter(|x| x.starts_with("dynn_"))
+        .cloned();
+    let dynasties = builder::ck3_entries(buffer, localized(dynasties, &localization));
+
+    let game = Game::create(
+        buffer,
+        &GameArgs {
+            titles: Some(titles),
+            cultures: Some(cultures),
+            dynasties: Some(dynasties),
+        },
+    );
+    buffer.finish(game, None);
+    Ok(())
+}
+
+fn hoi4_game_data(buffer: &mut FlatBufferBuilder, tmp_game_dir: &Path) -> anyhow::Result<()> {
+    use schemas::hoi4::{Game, GameArgs};
+
+    let localization = localization::english_localization_tree(tmp_game_dir.join("localisation"))?;
+    let countries = builder::hoi4_countries(buffer, localized_countries(&localization));
+
+    // States are localized by their id and parse_states sorts by id
+    let states = province_raster::parse_states(tmp_game_dir)?;
+    let states = states
+        .iter()
+        .map(|state| (state.id, localization.get(&format!("STATE_{}", state.id))));
+    let states = builder::hoi4_states(buffer, states);
+
+    let game = Game::create(
+        buffer,
+        &GameArgs {
+            countries: Some(countries),
+            states: Some(states),
+        },
+    );
+    buffer.finish(game, None);
+    Ok(())
+}
+
+fn imperator_game_data(buffer: &mut FlatBufferBuilder, tmp_game_dir: &Path) -> anyhow::Result<()> {
+    use schemas::imperator::{Game, GameArgs};
+
+    let localization = localization::english_localization_tree(tmp_game_dir.join("localization"))?;
+    let countries = builder::imperator_countries(buffer, localized_countries(&localization));
+
+    let mut names: Vec<_> = localization
+        .iter()
+        .filter_map(|(key, name)| Some((imperator_province_id(key)?, name)))
+        .collect();
+    names.sort_unstable();
+    let provinces = builder::imperator_provinces(buffer, names);
+
+    let game = Game::create(
+        buffer,
+        &GameArgs {
+            countries: Some(countries),
+            provinces: Some(provinces),
+        },
+    );
+    buffer.finish(game, None);
+    Ok(())
+}
+
+fn vic3_game_data(buffer: &mut FlatBufferBuilder, tmp_game_dir: &Path) -> anyhow::Result<()> {
+    use schemas::vic3::{Game, GameArgs};
+
+    let localization = localization::english_localization_tree(tmp_game_dir.join("localization"))?;
+    let countries = builder::vic3_countries(buffer, localized_countries(&localization));
+
+    let regions_dir = tmp_game_dir.join("map_data").join("state_regions");
+    let regions = province_raster::parse_state_region_files(&regions_dir)?;
+    let regions = regions.into_iter().map(|(key, _)| key);
+    let states = builder::vic3_entries(buffer, localized(regions, &localization));
+
+    let cultures = top_level_keys(&tmp_game_dir.join("common").join("cultures"))?;
+    let cultures = builder::vic3_entries(buffer, localized(cultures, &localization));
+
+    let game = Game::create(
+        buffer,
+        &GameArgs {
+            countries: Some(countries),
+            states: Some(states),
+            cultures: Some(cultures),
+        },
+    );
+    buffer.finish(game, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_keys() {
+        let data = br#"
+        @neg1_min = 0.4
+        french = {
+            color = { 0.2 0.2 0.8 }
+            heritage = heritage_frankish
+        }
+        norman = { }
+        "#;
+
+        let actual: TopLevelKeys = jomini::text::de::from_utf8_slice(&data[..]).unwrap();
+        assert_eq!(actual.0, vec!["french", "norman"]);
+    }
+
+    #[test]
+    fn test_localized_sorted() {
+        let mut localization = HashMap::new();
+        localization.insert(String::from("k_france"), String::from("France"));
+        localization.insert(String::from("d_paris"), String::from("Paris"));
+
+        let keys = ["k_france", "d_paris", "d_unknown", "k_france"].map(String::from);
+        let actual = localized(keys, &localization);
+        assert_eq!(
+            actual,
+            vec![
+                (String::from("d_paris"), String::from("Paris")),
+                (String::from("k_france"), String::from("France"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imperator_province_id() {
+        assert_eq!(imperator_province_id("PROV123"), Some(123));
+        assert_eq!(imperator_province_id("PROV_ADJ"), None);
+        assert_eq!(imperator_province_id("ROM"), None);
+    }
+}
@@ -1,5 +1,6 @@
 use eu4save::CountryTag;
 use std::{collections::HashMap, fs, path::Path};
+use walkdir::WalkDir;
 
 #[derive(Debug, Clone)]
 pub struct LocalizedCountry {
@@ -84,6 +85,36 @@ pub fn english_localization<P: AsRef<Path>>(dir: P) -> anyhow::Result<HashMap<St
     Ok(result)
 }
 
+/// English localization for games that nest their localization files in
+/// subdirectories. Files in a `replace` directory take precedence.
+pub fn english_localization_tree<P: AsRef<Path>>(
+    dir: P,
+) -> anyhow::Result<HashMap<String, String>> {
+    let mut files: Vec<_> = WalkDir::new(dir.as_ref())
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|x| x.into_path())
+        .filter(|x| {
+            x.file_name()
+                .map_or(false, |x| x.to_string_lossy().ends_with("english.yml"))
+        })
+        .collect();
+    files.sort_unstable_by_key(|x| {
+        (
+            x.components().any(|c| c.as_os_str() == "replace"),
+            x.clone(),
+        )
+    });
+
+    let mut result = HashMap::new();
+    for path in files {
+        let data = fs::read_to_string(&path)?;
+        result.extend(parse_localization(&data));
+    }
+
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
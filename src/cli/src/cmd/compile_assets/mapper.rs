@@ -81,6 +81,27 @@ pub fn parse_definition(data: &[u8]) -> HashMap<u16, Rgb> {
     result
 }
 
+/// Sorts the province colors, including the black void around the map as
+/// province 0, into the order of color-order.bin. A color's position in
+/// this order is the color index that the map rasters reference.
+pub fn color_order(mut definitions: HashMap<u16, Rgb>) -> Vec<(Rgb, u16)> {
+    definitions.insert(0, Rgb::from((0, 0, 0)));
+    let mut colors: Vec<_> = definitions.into_iter().map(|(id, rgb)| (rgb, id)).collect();
+    colors.sort_unstable();
+    colors
+}
+
+/// The color index of each province id (see color-index.bin), where ids
+/// missing from the definitions map to the void
+pub fn province_color_indices(colors: &[(Rgb, u16)]) -> Vec<u16> {
+    let max_id = colors.iter().map(|(_, id)| *id).max().unwrap_or_default();
+    let mut result = vec![0u16; usize::from(max_id) + 1];
+    for (index, (_, id)) in colors.iter().enumerate() {
+        result[usize::from(*id)] = index as u16;
+    }
+    result
+}
+
 pub fn parse_terrain_bmp(
     terrainbmp: &[u8],
     province_area: &[u16],
@@ -143,6 +164,27 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_color_order() {
+        let mut definitions = HashMap::new();
+        definitions.insert(1, Rgb::from((128, 34, 64)));
+        definitions.insert(2, Rgb::from((0, 36, 128)));
+        definitions.insert(4, Rgb::from((128, 38, 192)));
+
+        let colors = color_order(definitions);
+        assert_eq!(
+            colors,
+            vec![
+                (Rgb::from((0, 0, 0)), 0),
+                (Rgb::from((0, 36, 128)), 2),
+                (Rgb::from((128, 34, 64)), 1),
+                (Rgb::from((128, 38, 192)), 4),
+            ]
+        );
+
+        assert_eq!(province_color_indices(&colors), vec![0, 2, 1, 0, 3]);
+    }
+
     #[test]
     fn test_parse_terrain() {
         let data = "categories = {
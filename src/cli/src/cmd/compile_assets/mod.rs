@@ -3,9 +3,11 @@ mod area;
 mod assets;
 mod continents;
 mod cultures;
+mod game_data;
 mod localization;
 pub mod mapper;
 mod personalities;
+mod province_raster;
 mod regions;
 mod religion;
 mod sprites;
@@ -0,0 +1,421 @@
+//! Province id rasters for the games that only have a political map. EU4
+//! assets are handled by [super::tarball::translate_map].
+
+use super::{mapper, tarball::PackageOptions};
+use crate::rawbmp::{Bmp, Pixels, Rgb};
+use anyhow::{bail, Context};
+use pdxmap::ProvinceRaster;
+use serde::{
+    de::{self, IgnoredAny},
+    Deserialize, Deserializer, Serialize,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use walkdir::WalkDir;
+
+pub fn translate_province_raster(
+    game: &str,
+    tmp_game_dir: &Path,
+    out_game_dir: &Path,
+    options: &PackageOptions,
+) -> anyhow::Result<()> {
+    let map_dir = out_game_dir.join("map");
+    fs::create_dir_all(&map_dir)
+        .with_context(|| format!("unable to create: {}", map_dir.display()))?;
+
+    let out_path = map_dir.join("provinces.bin.zst");
+    if out_path.exists() && !options.regen {
+        return Ok(());
+    }
+
+    let raster = match game {
+        "ck3" | "imperator" => {
+            let data_dir = tmp_game_dir.join("map_data");
+            let colors = definition_colors(&data_dir.join("definition.csv"))?;
+            if game == "ck3" {
+                let titles = parse_landed_titles(tmp_game_dir)?;
+                let baronies: HashMap<_, _> = titles.baronies.into_iter().collect();
+                let out = fs::File::create(map_dir.join("baronies.json"))?;
+                serde_json::to_writer(out, &baronies)?;
+            }
+
+            let bmp = png_to_bmp(&data_dir.join("provinces.png"))?;
+            province_raster(&Bmp::parse(&bmp)?, &colors, 0)?
+        }
+        "hoi4" => {
+            let data_dir = tmp_game_dir.join("map");
+            let colors = definition_colors(&data_dir.join("definition.csv"))?;
+            let states = parse_states(tmp_game_dir)?;
+            let out = fs::File::create(map_dir.join("states.json"))?;
+            serde_json::to_writer(out, &states)?;
+
+            let bmp_path = data_dir.join("provinces.bmp");
+            let bmp = fs::read(&bmp_path)
+                .with_context(|| format!("unable to read: {}", bmp_path.display()))?;
+            province_raster(&Bmp::parse(&bmp)?, &colors, 0)?
+        }
+        "vic3" => {
+            let data_dir = tmp_game_dir.join("map_data");
+            let colors = parse_state_regions(&data_dir.join("state_regions"))?;
+            let unassigned =
+                u16::try_from(colors.len()).context("too many vic3 provinces for the raster")?;
+            let bmp = png_to_bmp(&data_dir.join("provinces.png"))?;
+            province_raster(&Bmp::parse(&bmp)?, &colors, unassigned)?
+        }
+        _ => bail!("no province raster for game: {}", game),
+    };
+
+    let data = zstd::encode_all(&raster.to_bytes()[..], 7)?;
+    fs::write(&out_path, data).with_context(|| format!("unable to write: {}", out_path.display()))
+}
+
+fn definition_colors(path: &Path) -> anyhow::Result<HashMap<Rgb, u16>> {
+    let data = fs::read(path).with_context(|| format!("unable to read: {}", path.display()))?;
+    let definitions = mapper::parse_definition(&data);
+    Ok(definitions.into_iter().map(|(id, rgb)| (rgb, id)).collect())
+}
+
+fn png_to_bmp(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let out_dir = tempfile::tempdir()?;
+    let out_path = out_dir.path().join("provinces.bmp");
+    let child = Command::new("convert")
+        .arg(path)
+        .arg(format!("BMP3:{}", out_path.display()))
+        .output()
+        .context("imagemagick convert failed")?;
+
+    if !child.status.success() {
+        bail!(
+            "convert failed with: {}",
+            String::from_utf8_lossy(&child.stderr)
+        );
+    }
+
+    fs::read(&out_path).with_context(|| format!("unable to read: {}", out_path.display()))
+}
+
+/// Maps each pixel to its province id with pixels of an unknown color
+/// assigned to the fallback. Bottom up bitmaps are flipped so that the
+/// raster starts from the top left.
+fn province_raster(
+    bmp: &Bmp,
+    colors: &HashMap<Rgb, u16>,
+    fallback: u16,
+) -> anyhow::Result<ProvinceRaster> {
+    let width = bmp.dib_header.width.unsigned_abs();
+    let height = bmp.dib_header.height.unsigned_abs();
+    let Pixels::Rgb(pixels) = bmp.pixels();
+    let mut provinces: Vec<u16> = pixels
+        .map(|x| colors.get(&x).copied().unwrap_or(fallback))
+        .collect();
+
+    if bmp.dib_header.height > 0 {
+        let rows: Vec<_> = provinces.chunks_exact(width as usize).rev().collect();
+        provinces = rows.concat();
+    }
+
+    Ok(ProvinceRaster::new(width, height, provinces)?)
+}
+
+fn strip_bom(data: &[u8]) -> &[u8] {
+    data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data)
+}
+
+/// A landed title and the titles nested within it
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct LandedTitle {
+    pub(super) province: Option<u16>,
+
+    /// Keys of every nested title
+    pub(super) titles: Vec<String>,
+
+    /// Province ids of nested baronies
+    pub(super) baronies: Vec<(String, u16)>,
+}
+
+fn is_title_key(key: &str) -> bool {
+    ["e_", "k_", "d_", "c_", "b_"]
+        .iter()
+        .any(|prefix| key.starts_with(prefix))
+}
+
+impl<'de> Deserialize<'de> for LandedTitle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LandedTitleVisitor;
+
+        impl<'de> de::Visitor<'de> for LandedTitleVisitor {
+            type Value = LandedTitle;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a landed title")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut result = LandedTitle::default();
+                while let Some(key) = map.next_key::<&str>()? {
+                    if key == "province" {
+                        result.province = map.next_value()?;
+                    } else if is_title_key(key) {
+                        let title: LandedTitle = map.next_value()?;
+                        if let Some(province) = title.province.filter(|_| key.starts_with("b_")) {
+                            result.baronies.push((String::from(key), province));
+                        }
+                        result.titles.push(String::from(key));
+                        result.titles.extend(title.titles);
+                        result.baronies.extend(title.baronies);
+                    } else {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+
+                Ok(result)
+            }
+        }
+
+        deserializer.deserialize_map(LandedTitleVisitor)
+    }
+}
+
+/// Every CK3 landed title across the landed title files
+pub(super) fn parse_landed_titles(tmp_game_dir: &Path) -> anyhow::Result<LandedTitle> {
+    let titles_dir = tmp_game_dir.join("common").join("landed_titles");
+    let mut result = LandedTitle::default();
+    for entry in WalkDir::new(&titles_dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.path().extension().map_or(true, |x| x != "txt") {
+            continue;
+        }
+
+        let data = fs::read(entry.path())?;
+        let titles: LandedTitle = jomini::text::de::from_utf8_slice(strip_bom(&data))
+            .with_context(|| format!("unable to parse: {}", entry.path().display()))?;
+        result.titles.extend(titles.titles);
+        result.baronies.extend(titles.baronies);
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub(super) struct StateProvinces {
+    pub(super) id: u32,
+    pub(super) provinces: Vec<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateFile {
+    state: StateProvinces,
+}
+
+/// The provinces of each HOI4 state
+pub(super) fn parse_states(tmp_game_dir: &Path) -> anyhow::Result<Vec<StateProvinces>> {
+    let states_dir = tmp_game_dir.join("history").join("states");
+    let mut result = Vec::new();
+    for entry in WalkDir::new(&states_dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.path().extension().map_or(true, |x| x != "txt") {
+            continue;
+        }
+
+        let data = fs::read(entry.path())?;
+        let file: StateFile = jomini::text::de::from_utf8_slice(strip_bom(&data))
+            .with_context(|| format!("unable to parse: {}", entry.path().display()))?;
+        result.push(file.state);
+    }
+
+    result.sort_unstable_by_key(|x| x.id);
+    Ok(result)
+}
+
+/// Parses a province color written as hex (eg: `x4B7E2A`)
+fn parse_hex_color(color: &str) -> Option<Rgb> {
+    let hex = color.strip_prefix(['x', 'X'])?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let [_, r, g, b] = value.to_be_bytes();
+    Some(Rgb { r, g, b })
+}
+
+/// Vic3 state regions and their province colors in order of appearance
+#[derive(Debug, Default, PartialEq)]
+struct StateRegions(Vec<(String, Vec<String>)>);
+
+impl<'de> Deserialize<'de> for StateRegions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct StateRegion {
+            #[serde(default)]
+            provinces: Vec<String>,
+        }
+
+        struct StateRegionsVisitor;
+
+        impl<'de> de::Visitor<'de> for StateRegionsVisitor {
+            type Value = StateRegions;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("state regions")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut result = Vec::new();
+                while let Some((key, region)) = map.next_entry::<&str, StateRegion>()? {
+                    result.push((String::from(key), region.provinces));
+                }
+
+                Ok(StateRegions(result))
+            }
+        }
+
+        deserializer.deserialize_map(StateRegionsVisitor)
+    }
+}
+
+/// Vic3 state regions with their province colors, read from the state
+/// region files in file name order
+pub(super) fn parse_state_region_files(dir: &Path) -> anyhow::Result<Vec<(String, Vec<String>)>> {
+    let mut files: Vec<_> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|x| x.path().extension().map_or(false, |x| x == "txt"))
+        .map(|x| x.into_path())
+        .collect();
+    files.sort_unstable();
+
+    let mut result = Vec::new();
+    for path in files {
+        let data = fs::read(&path)?;
+        let regions: StateRegions = jomini::text::de::from_utf8_slice(strip_bom(&data))
+            .with_context(|| format!("unable to parse: {}", path.display()))?;
+        result.extend(regions.0);
+    }
+
+    Ok(result)
+}
+
+/// Vic3 doesn't have a province definition file, so province indices are
+/// assigned in the order they appear in the state region files.
+///
+/// Known limitation: this is assumed, not verified, to be the order the game
+/// uses for the province indices of a save's states. If the game orders them
+/// differently, the political map paints states onto the wrong provinces.
+fn parse_state_regions(dir: &Path) -> anyhow::Result<HashMap<Rgb, u16>> {
+    let mut result = HashMap::new();
+    for (key, colors) in parse_state_region_files(dir)? {
+        for color in colors {
+            let rgb = parse_hex_color(&color)
+                .with_context(|| format!("invalid province color {} in {}", color, key))?;
+            let id = u16::try_from(result.len())
+                .with_context(|| format!("too many province colors at {}", key))?;
+            result.entry(rgb).or_insert(id);
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_province_raster_flipped() {
+        let data = include_bytes!("../../../tests/fixtures/24bit.bmp");
+        let bmp = Bmp::parse(&data[..]).unwrap();
+        let mut colors = HashMap::new();
+        colors.insert(Rgb::from((255, 178, 127)), 1);
+        colors.insert(Rgb::from((127, 255, 244)), 2);
+        colors.insert(Rgb::from((255, 127, 138)), 3);
+
+        let raster = province_raster(&bmp, &colors, 9).unwrap();
+        assert_eq!(raster.width(), 3);
+        assert_eq!(raster.height(), 2);
+        assert_eq!(raster.provinces(), &[9, 3, 9, 1, 2, 9]);
+    }
+
+    #[test]
+    fn test_parse_landed_titles() {
+        let data = br#"
+        @correct_culture_primary_score = 100
+        e_britannia = {
+            color = { 149 34 34 }
+            k_england = {
+                capital = c_middlesex
+                d_london = {
+                    c_middlesex = {
+                        b_london = { province = 1520 }
+                        b_windsor = { province = 1521 }
+                    }
+                }
+            }
+        }
+        "#;
+
+        let actual: LandedTitle = jomini::text::de::from_utf8_slice(&data[..]).unwrap();
+        assert_eq!(
+            actual.baronies,
+            vec![
+                (String::from("b_london"), 1520),
+                (String::from("b_windsor"), 1521)
+            ]
+        );
+        assert_eq!(
+            actual.titles,
+            vec![
+                "e_britannia",
+                "k_england",
+                "d_london",
+                "c_middlesex",
+                "b_london",
+                "b_windsor"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_state_regions() {
+        let data = br#"
+        STATE_SVEALAND = {
+            id = 1
+            provinces = { "x4B7E2A" "x0A0B0C" }
+            city = "x4B7E2A"
+        }
+        STATE_NORRLAND = {
+            id = 2
+            provinces = { "xFF0000" }
+        }
+        "#;
+
+        let actual: StateRegions = jomini::text::de::from_utf8_slice(&data[..]).unwrap();
+        assert_eq!(
+            actual.0,
+            vec![
+                (
+                    String::from("STATE_SVEALAND"),
+                    vec![String::from("x4B7E2A"), String::from("x0A0B0C")]
+                ),
+                (
+                    String::from("STATE_NORRLAND"),
+                    vec![String::from("xFF0000")]
+                )
+            ]
+        );
+        assert_eq!(
+            parse_hex_color("x4B7E2A"),
+            Some(Rgb::from((0x4b, 0x7e, 0x2a)))
+        );
+        assert_eq!(parse_hex_color("4B7E2A"), None);
+    }
+}
@@ -5,12 +5,14 @@ pub struct Religion {
     pub id: String,
     pub name: String,
     pub colors: [u8; 3],
+    pub group: String,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct RawReligion {
     pub id: String,
     pub colors: [u8; 3],
+    pub group: String,
 }
 
 pub fn parse_enhanced_religions(
@@ -23,6 +25,7 @@ pub fn parse_enhanced_religions(
             id: religion.id.clone(),
             name: String::from(localization.get(&religion.id).unwrap()),
             colors: religion.colors,
+            group: religion.group,
         })
         .collect()
 }
@@ -31,7 +34,7 @@ pub fn parse_religions(data: &[u8]) -> Vec<RawReligion> {
     let tape = jomini::TextTape::from_slice(data).unwrap();
     let reader = tape.windows1252_reader();
     let mut result = Vec::new();
-    for (_, _, value) in reader.fields() {
+    for (group_key, _, value) in reader.fields() {
         if let Ok(religion_group) = value.read_object() {
             for (key, _, value) in religion_group.fields() {
                 let religion_name = key.read_str();
@@ -47,6 +50,7 @@ pub fn parse_religions(data: &[u8]) -> Vec<RawReligion> {
                             result.push(RawReligion {
                                 id: religion_name.to_string(),
                                 colors: color,
+                                group: group_key.read_string(),
                             })
                         }
                     }
@@ -98,6 +102,7 @@ mod tests {
             vec![RawReligion {
                 id: String::from("jewish"),
                 colors: [153, 25, 102],
+                group: String::from("jewish_group"),
             }]
         );
     }
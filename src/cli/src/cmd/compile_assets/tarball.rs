@@ -1,8 +1,8 @@
 use super::{
-    achievements, area, assets, continents, cultures, localization, mapper, personalities, regions,
-    religion, sprites, superregion,
+    achievements, area, assets, continents, cultures, game_data, localization, mapper,
+    personalities, province_raster, regions, religion, sprites, superregion,
 };
-use crate::rawbmp::{self, Pixels, Rgb};
+use crate::rawbmp::{self, Pixels};
 use crate::zstd_tee::ZstdTee;
 use anyhow::{bail, Context};
 use eu4save::{CountryTag, Eu4File, ProvinceId};
@@ -32,7 +32,7 @@ pub fn parse_game_bundle(options: &PackageOptions) -> anyhow::Result<()> {
     let game_dir = Path::new(".")
         .join("assets")
         .join("game")
-        .join(game_name)
+        .join(&game_name)
         .join(&game_version);
     std::fs::create_dir_all(&game_dir).with_context(|| format!("{}", game_dir.display()))?;
 
@@ -43,7 +43,13 @@ pub fn parse_game_bundle(options: &PackageOptions) -> anyhow::Result<()> {
 
     let dir = tempfile::tempdir()?;
     archive.unpack(dir.path())?;
-    parse_game_dir(dir.path(), &game_dir, &game_version, options)
+    match game_name.as_str() {
+        "eu4" => parse_game_dir(dir.path(), &game_dir, &game_version, options),
+        game => {
+            game_data::translate_game_data(game, dir.path(), &game_dir)?;
+            province_raster::translate_province_raster(game, dir.path(), &game_dir, options)
+        }
+    }
 }
 
 pub fn parse_game_dir(
@@ -310,6 +316,7 @@ pub fn parse_game_dir(
     for religion in data.iter() {
         let key = buffer.create_string(&religion.id);
         let name = buffer.create_string(&religion.name);
+        let group = buffer.create_string(&religion.group);
         let color =
             schemas::eu4::Rgb::new(religion.colors[0], religion.colors[1], religion.colors[2]);
         let entry = schemas::eu4::Religion::create(
@@ -318,6 +325,7 @@ pub fn parse_game_dir(
                 key: Some(key),
                 name: Some(name),
                 color: Some(&color),
+                group: Some(group),
             },
         );
         religions.push(entry);
@@ -354,6 +362,8 @@ pub fn parse_game_dir(
                 || k.ends_with("_area")
                 || k.ends_with("_superregion")
                 || k.ends_with("_region")
+                || k.strip_prefix("PROV")
+                    .map_or(false, |id| id.parse::<u16>().is_ok())
         })
         .collect();
     localization.sort_unstable();
@@ -1109,33 +1119,20 @@ pub fn translate_map(
     let definitions = tmp_game_dir.join("map").join("definition.csv");
     let definitions = std::fs::read(&definitions)
         .with_context(|| format!("unable to read: {}", definitions.display()))?;
-    let mut definitions = mapper::parse_definition(&definitions);
-    definitions.insert(0, Rgb::from((0, 0, 0)));
-
-    let mut definitions: Vec<(_, _)> = definitions.iter().map(|(id, rgb)| (rgb, id)).collect();
-
-    definitions.sort_unstable();
-    let mut provs: Vec<_> = definitions
-        .iter()
-        .enumerate()
-        .map(|(i, (_rgb, id))| (id, i))
-        .collect();
-    provs.sort_unstable_by_key(|(&&id, _index)| id);
+    let colors = mapper::color_order(mapper::parse_definition(&definitions));
 
     // These files don't compress that well (and are small), so we skip compression
     let color_order_file = fs::File::create(out_game_dir.join("map").join("color-order.bin"))?;
     let mut color_order_writer = BufWriter::new(color_order_file);
-    for (rgb, _prov_id) in &definitions {
+    for (rgb, _prov_id) in &colors {
         color_order_writer.write_all(&[rgb.r, rgb.g, rgb.b])?;
     }
     color_order_writer.flush()?;
 
     let color_index_file = fs::File::create(out_game_dir.join("map").join("color-index.bin"))?;
     let mut color_index_writer = BufWriter::new(color_index_file);
-    for (_prov_id, index) in &provs {
-        let val = *index as u16;
-        let data = val.to_le_bytes();
-        color_index_writer.write_all(&data)?;
+    for index in mapper::province_color_indices(&colors) {
+        color_index_writer.write_all(&index.to_le_bytes())?;
     }
     color_index_writer.flush()?;
 
@@ -1143,7 +1140,7 @@ pub fn translate_map(
     let provinces_path = tmp_game_dir.join("map").join("provinces.bmp");
     let provinces_file_data = fs::read(&provinces_path)
         .with_context(|| format!("unable to read: {}", provinces_path.display()))?;
-    let definitions: HashMap<_, _> = definitions.into_iter().collect();
+    let definitions: HashMap<_, _> = colors.into_iter().collect();
     let mut pixel_locations: HashMap<u16, Vec<(u16, u16)>> = HashMap::new();
 
     let provinces_bmp =
@@ -1156,7 +1153,7 @@ pub fn translate_map(
         let id = definitions.get(&pix).copied().unwrap();
         let x = (i % width) as u16;
         let y = height - (i / width) as u16;
-        let coord = pixel_locations.entry(*id).or_insert_with(Vec::new);
+        let coord = pixel_locations.entry(id).or_insert_with(Vec::new);
         coord.push((x, y));
     }
 
@@ -7,6 +7,10 @@ use walkdir::WalkDir;
 
 #[derive(Args)]
 pub struct CreateBundleArgs {
+    /// The game being bundled (eg: eu4, ck3, hoi4, imperator, vic3)
+    #[arg(long, default_value = "eu4")]
+    game: String,
+
     #[arg()]
     from: PathBuf,
 
@@ -16,7 +20,7 @@ pub struct CreateBundleArgs {
 
 impl CreateBundleArgs {
     pub fn run(&self) -> anyhow::Result<ExitCode> {
-        tar_game(&self.from, &self.to)?;
+        tar_game(&self.game, &self.from, &self.to)?;
         Ok(ExitCode::SUCCESS)
     }
 }
@@ -38,6 +42,7 @@ fn normalize_path(path: &Path) -> String {
 }
 
 pub fn tar_game_directory<P, Q>(
+    game: &str,
     game_dir: P,
     output_dir: Q,
     version: &str,
@@ -60,7 +65,7 @@ where
     ])
     .context("unable to create bundle regex set")?;
 
-    let output_name = format!("eu4-{}.tar.zst", version);
+    let output_name = format!("{}-{}.tar.zst", game, version);
     let output_path = output_dir.as_ref();
     fs::create_dir_all(output_path).with_context(|| format!("{}", output_path.display()))?;
     let output_ball = output_path.join(output_name);
@@ -99,17 +104,35 @@ where
     Ok(output_ball)
 }
 
-pub fn tar_game<P, Q>(game_dir: P, output_dir: Q) -> anyhow::Result<PathBuf>
+pub fn tar_game<P, Q>(game: &str, game_dir: P, output_dir: Q) -> anyhow::Result<PathBuf>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
     let game_path = game_dir.as_ref();
-    let settings_path = game_path.join("launcher-settings.json");
+
+    // Newer games keep the launcher settings in a launcher directory
+    let settings_path = [
+        game_path.join("launcher-settings.json"),
+        game_path.join("launcher").join("launcher-settings.json"),
+    ]
+    .into_iter()
+    .find(|x| x.exists())
+    .unwrap_or_else(|| game_path.join("launcher-settings.json"));
     let settings_data =
         fs::read(&settings_path).with_context(|| format!("{}", settings_path.display()))?;
     let settings: LauncherSettings = serde_json::from_slice(&settings_data)
         .with_context(|| format!("{}", settings_path.display()))?;
     let major_minor: Vec<_> = settings.raw_version.split('.').take(2).collect();
-    tar_game_directory(game_dir, output_dir, &major_minor.join("."))
+
+    // Newer games keep their data in a game directory, which becomes the root
+    // of the bundle
+    let data_dir = game_path.join("game");
+    let data_dir = if data_dir.is_dir() {
+        data_dir
+    } else {
+        game_path.to_path_buf()
+    };
+
+    tar_game_directory(game, data_dir, output_dir, &major_minor.join("."))
 }
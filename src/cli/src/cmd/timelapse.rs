@@ -0,0 +1,218 @@
+use crate::{
+    compile_assets::mapper::{color_order, parse_definition, province_color_indices},
+    rawbmp::{Bmp, Pixels, Rgb},
+    remote_parse::remote_parse,
+};
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, ValueEnum};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use wasm_eu4::{
+    animation::{AnimationFormat, AnimationOptions},
+    render::{BorderFill, CropRegion, MapCrop},
+    Interval, MapCursorPayload, MapCursorPayloadKind, SaveFileImpl,
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Gif,
+    Apng,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    Political,
+    Religion,
+    Battles,
+    War,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Step {
+    Year,
+    Month,
+    Week,
+    Day,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Fill {
+    None,
+    Provinces,
+    Countries,
+}
+
+/// Encodes an animated map timelapse of a save
+#[derive(Args)]
+pub struct TimelapseArgs {
+    /// EU4 map directory containing provinces.bmp and definition.csv
+    #[arg(long)]
+    map_dir: PathBuf,
+
+    /// Game data flatbuffer. Defaults to the save's version in ./assets/game/eu4
+    #[arg(long)]
+    game_data: Option<PathBuf>,
+
+    /// Destination of the animation
+    #[arg(short, long)]
+    output: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = Format::Gif)]
+    format: Format,
+
+    #[arg(long, value_enum, default_value_t = Mode::Political)]
+    mode: Mode,
+
+    #[arg(long, value_enum, default_value_t = Step::Year)]
+    interval: Step,
+
+    #[arg(long, value_enum, default_value_t = Fill::Countries)]
+    fill: Fill,
+
+    /// Frames per second
+    #[arg(long, default_value_t = 4)]
+    fps: u16,
+
+    /// Width of the animation in pixels
+    #[arg(long)]
+    width: Option<u32>,
+
+    /// Name of the war to animate in the war mode
+    #[arg(long, required_if_eq("mode", "war"))]
+    war: Option<String>,
+
+    /// Fit the animation to the country and its subjects
+    #[arg(long, conflicts_with = "region")]
+    realm: Option<String>,
+
+    /// Fit the animation to a region of the map: x,y,width,height
+    #[arg(long, value_delimiter = ',', num_args = 4)]
+    region: Option<Vec<u32>>,
+
+    /// Omit the date stamp from each frame
+    #[arg(long)]
+    no_date: bool,
+
+    /// Save file to animate
+    #[arg(value_parser)]
+    file: PathBuf,
+}
+
+impl TimelapseArgs {
+    pub fn run(&self) -> anyhow::Result<ExitCode> {
+        let (save, encoding) = remote_parse(&self.file)
+            .with_context(|| format!("unable to parse: {}", self.file.display()))?;
+
+        let game_data_path = self.game_data.clone().unwrap_or_else(|| {
+            let version = &save.meta.savegame_version;
+            Path::new("./assets/game/eu4")
+                .join(format!("{}.{}", version.first, version.second))
+                .join("data-raw.bin")
+        });
+        let game_data = std::fs::read(&game_data_path)
+            .with_context(|| format!("unable to read: {}", game_data_path.display()))?;
+
+        let (color_indices, province_id_to_color_index) = self.color_indices()?;
+        let save = SaveFileImpl::new(save, encoding, game_data, province_id_to_color_index);
+        let raster = save.map_raster(&color_indices, None)?;
+
+        let crop = match (&self.realm, &self.region) {
+            (Some(tag), _) => MapCrop::Realm {
+                tag: Some(
+                    tag.parse()
+                        .map_err(|_| anyhow!("invalid country tag: {}", tag))?,
+                ),
+            },
+            (_, Some(region)) => MapCrop::Region(CropRegion {
+                x: region[0],
+                y: region[1],
+                width: region[2],
+                height: region[3],
+            }),
+            (None, None) => MapCrop::Full,
+        };
+
+        let options = AnimationOptions {
+            format: match self.format {
+                Format::Gif => AnimationFormat::Gif,
+                Format::Apng => AnimationFormat::Apng,
+            },
+            fill: match self.fill {
+                Fill::None => BorderFill::None,
+                Fill::Provinces => BorderFill::Provinces,
+                Fill::Countries => BorderFill::Countries,
+            },
+            crop,
+            width: self.width,
+            frame_rate: self.fps,
+            date_overlay: !self.no_date,
+        };
+
+        let kind = match self.mode {
+            Mode::Political => MapCursorPayloadKind::Political,
+            Mode::Religion => MapCursorPayloadKind::Religion,
+            Mode::Battles => MapCursorPayloadKind::Battles,
+            Mode::War => MapCursorPayloadKind::War,
+        };
+        let interval = match self.interval {
+            Step::Year => Interval::Year,
+            Step::Month => Interval::Month,
+            Step::Week => Interval::Week,
+            Step::Day => Interval::Day,
+        };
+
+        let mut payload = MapCursorPayload::new(kind, interval, None);
+        if let Some(war) = &self.war {
+            payload = payload.with_war(war.clone());
+        }
+
+        let cursor = save.map_cursor(payload)?;
+        log::info!("encoding {} frames", cursor.remaining());
+        let out = save.render_timelapse(cursor, &raster, &options)?;
+        std::fs::write(&self.output, out)
+            .with_context(|| format!("unable to write: {}", self.output.display()))?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+
+    /// Computes the per pixel color index of the map and the province id to
+    /// color index lookup the same way as the compiled map assets
+    fn color_indices(&self) -> anyhow::Result<(Vec<u16>, Vec<u16>)> {
+        let definitions = self.map_dir.join("definition.csv");
+        let definitions = std::fs::read(&definitions)
+            .with_context(|| format!("unable to read: {}", definitions.display()))?;
+        let colors = color_order(parse_definition(&definitions));
+        let province_id_to_color_index = province_color_indices(&colors);
+        let color_lookup: HashMap<Rgb, u16> = colors
+            .iter()
+            .enumerate()
+            .map(|(index, (rgb, _))| (*rgb, index as u16))
+            .collect();
+
+        let provinces_path = self.map_dir.join("provinces.bmp");
+        let provinces_data = std::fs::read(&provinces_path)
+            .with_context(|| format!("unable to read: {}", provinces_path.display()))?;
+        let bmp = Bmp::parse(&provinces_data).context("unable to parse bmp")?;
+        let width = bmp.dib_header.width.unsigned_abs() as usize;
+        let Pixels::Rgb(pixels) = bmp.pixels();
+        let indices = pixels
+            .map(|x| color_lookup.get(&x).copied())
+            .collect::<Option<Vec<_>>>();
+        let Some(indices) = indices else {
+            bail!("provinces.bmp contains a color not in definition.csv")
+        };
+
+        // bitmaps are stored bottom up
+        let color_indices = indices
+            .chunks_exact(width)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Ok((color_indices, province_id_to_color_index))
+    }
+}
@@ -14,6 +14,9 @@ mod fetch_assets;
 #[cfg(feature = "admin")]
 #[path = "cmd/reprocess.rs"]
 mod reprocess;
+#[cfg(feature = "timelapse")]
+#[path = "cmd/timelapse.rs"]
+mod timelapse;
 #[cfg(feature = "tokenize")]
 #[path = "cmd/tokenize.rs"]
 mod tokenize;
@@ -41,7 +44,7 @@ mod smallest_province;
 #[allow(dead_code)]
 #[cfg(any(feature = "compile_assets", feature = "fun"))]
 mod rawbmp;
-#[cfg(any(feature = "admin", feature = "fun"))]
+#[cfg(any(feature = "admin", feature = "fun", feature = "timelapse"))]
 #[path = "storage/remote_parse.rs"]
 mod remote_parse;
 #[cfg(any(feature = "tokenize", feature = "compile_assets"))]
@@ -71,6 +74,8 @@ enum Commands {
     FetchAssets(fetch_assets::FetchAssetsArgs),
     #[cfg(feature = "admin")]
     Reprocess(reprocess::ReprocessArgs),
+    #[cfg(feature = "timelapse")]
+    Timelapse(timelapse::TimelapseArgs),
     #[cfg(feature = "tokenize")]
     Tokenize(tokenize::TokenizeArgs),
     #[cfg(feature = "admin")]
@@ -102,6 +107,8 @@ fn main() -> ExitCode {
         Commands::FetchAssets(x) => x.run(),
         #[cfg(feature = "admin")]
         Commands::Reprocess(x) => x.run(),
+        #[cfg(feature = "timelapse")]
+        Commands::Timelapse(x) => x.run(),
         #[cfg(feature = "tokenize")]
         Commands::Tokenize(x) => x.run(),
         #[cfg(feature = "admin")]
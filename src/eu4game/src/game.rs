@@ -1,7 +1,7 @@
 use crate::GameProvince;
 use eu4save::{CountryTag, ProvinceId};
-use schemas::flatbuffers::{Follow, Vector};
-use std::{cmp::Ordering, collections::HashMap};
+pub use schemas::binary_search_by;
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub struct LocalizedCountry {
@@ -14,6 +14,9 @@ pub struct GameReligion<'a> {
     pub id: &'a str,
     pub name: &'a str,
     pub color: [u8; 3],
+
+    /// Absent from game data compiled before religion groups were tracked
+    pub group: Option<&'a str>,
 }
 
 #[derive(Debug)]
@@ -74,6 +77,10 @@ impl<'a> Game<'a> {
         self.localize(&needle)
     }
 
+    pub fn localize_province(&self, id: &ProvinceId) -> Option<&str> {
+        self.localize(&format!("PROV{}", id.as_u16()))
+    }
+
     pub fn localize_country_ref(&self, tag: &CountryTag) -> Option<&str> {
         let countries = self.data.countries().unwrap();
         let idx = binary_search_by(&countries, |x| x.key_compare_with_value(tag.as_str()));
@@ -158,6 +165,7 @@ impl<'a> Game<'a> {
             id: res.key(),
             name: res.name(),
             color: res.color().0,
+            group: res.group(),
         })
     }
 
@@ -318,30 +326,6 @@ impl<'a> Game<'a> {
     }
 }
 
-pub fn binary_search_by<'a, 'b: 'a, T, F>(haystack: &'b Vector<T>, mut f: F) -> Result<usize, usize>
-where
-    F: FnMut(T::Inner) -> Ordering,
-    T: Follow<'a> + 'a,
-{
-    let mut size = haystack.len();
-    let mut left = 0;
-    let mut right = size;
-    while left < right {
-        let mid = left + size / 2;
-        let cmp = f(haystack.get(mid));
-        if cmp == Ordering::Less {
-            left = mid + 1;
-        } else if cmp == Ordering::Greater {
-            right = mid;
-        } else {
-            return Ok(mid);
-        }
-
-        size = right - left;
-    }
-    Err(left)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
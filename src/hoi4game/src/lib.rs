@@ -0,0 +1,72 @@
+//! Accessors for the HOI4 game data written by compile_assets
+
+use schemas::binary_search_by;
+
+#[derive(Debug)]
+pub struct LocalizedCountry<'a> {
+    pub name: &'a str,
+    pub adjective: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Game<'a> {
+    data: schemas::hoi4::Game<'a>,
+}
+
+impl<'a> Game<'a> {
+    pub fn from_flatbuffer(data: &'a [u8]) -> Self {
+        let fb = schemas::hoi4::root_as_game(data).unwrap();
+        Self { data: fb }
+    }
+
+    pub fn localize_country(&self, tag: &str) -> Option<LocalizedCountry<'a>> {
+        let countries = self.data.countries()?;
+        let idx = binary_search_by(&countries, |x| x.key_compare_with_value(tag)).ok()?;
+        let country = countries.get(idx);
+        Some(LocalizedCountry {
+            name: country.name()?,
+            adjective: country.adjective(),
+        })
+    }
+
+    pub fn localize_state(&self, id: u32) -> Option<&'a str> {
+        let states = self.data.states()?;
+        let idx = binary_search_by(&states, |x| x.key_compare_with_value(id)).ok()?;
+        states.get(idx).name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemas::builder::{hoi4_countries, hoi4_states};
+    use schemas::flatbuffers::FlatBufferBuilder;
+    use schemas::hoi4::GameArgs;
+
+    #[test]
+    fn test_localize() {
+        let mut buffer = FlatBufferBuilder::new();
+        let countries = hoi4_countries(&mut buffer, [("FRA", "France", "French")]);
+        let states = hoi4_states(
+            &mut buffer,
+            [(1, Some("Corsica")), (16, Some("Normandy")), (17, None)],
+        );
+        let game = schemas::hoi4::Game::create(
+            &mut buffer,
+            &GameArgs {
+                countries: Some(countries),
+                states: Some(states),
+            },
+        );
+        buffer.finish(game, None);
+
+        let game = Game::from_flatbuffer(buffer.finished_data());
+        let france = game.localize_country("FRA").unwrap();
+        assert_eq!(france.name, "France");
+        assert_eq!(france.adjective, Some("French"));
+        assert!(game.localize_country("GER").is_none());
+        assert_eq!(game.localize_state(16), Some("Normandy"));
+        assert_eq!(game.localize_state(17), None);
+        assert_eq!(game.localize_state(2), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! Accessors for the Imperator game data written by compile_assets
+
+use schemas::binary_search_by;
+
+#[derive(Debug)]
+pub struct LocalizedCountry<'a> {
+    pub name: &'a str,
+    pub adjective: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Game<'a> {
+    data: schemas::imperator::Game<'a>,
+}
+
+impl<'a> Game<'a> {
+    pub fn from_flatbuffer(data: &'a [u8]) -> Self {
+        let fb = schemas::imperator::root_as_game(data).unwrap();
+        Self { data: fb }
+    }
+
+    pub fn localize_country(&self, tag: &str) -> Option<LocalizedCountry<'a>> {
+        let countries = self.data.countries()?;
+        let idx = binary_search_by(&countries, |x| x.key_compare_with_value(tag)).ok()?;
+        let country = countries.get(idx);
+        Some(LocalizedCountry {
+            name: country.name()?,
+            adjective: country.adjective(),
+        })
+    }
+
+    pub fn localize_province(&self, id: u16) -> Option<&'a str> {
+        let provinces = self.data.provinces()?;
+        let idx = binary_search_by(&provinces, |x| x.key_compare_with_value(id)).ok()?;
+        provinces.get(idx).name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemas::builder::imperator_provinces;
+    use schemas::flatbuffers::FlatBufferBuilder;
+    use schemas::imperator::GameArgs;
+
+    #[test]
+    fn test_localize_province() {
+        let mut buffer = FlatBufferBuilder::new();
+        let provinces = imperator_provinces(&mut buffer, [(1, "Roma"), (2, "Ostia")]);
+        let game = schemas::imperator::Game::create(
+            &mut buffer,
+            &GameArgs {
+                countries: None,
+                provinces: Some(provinces),
+            },
+        );
+        buffer.finish(game, None);
+
+        let game = Game::from_flatbuffer(buffer.finished_data());
+        assert_eq!(game.localize_province(2), Some("Ostia"));
+        assert_eq!(game.localize_province(3), None);
+        assert!(game.localize_country("ROM").is_none());
+    }
+}
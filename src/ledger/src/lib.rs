@@ -0,0 +1,25 @@
+//! The annual ledger returned by each game module so that the frontend can
+//! chart any game's time series with the same components. A tag identifies a
+//! series: a country tag in EU4, a house id in CK3.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LocalizedTag<T> {
+    pub tag: T,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionalLedgerPoint<T> {
+    pub tag: T,
+    pub year: u16,
+    pub value: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalizedLedger<T> {
+    pub points: Vec<OptionalLedgerPoint<T>>,
+    pub localization: Vec<LocalizedTag<T>>,
+    pub dropped: Vec<LocalizedTag<T>>,
+}
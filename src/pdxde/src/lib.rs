@@ -0,0 +1,193 @@
+//! Serde helpers for data structures shared across Paradox game saves.
+
+use serde::{
+    de::{self, value::MapAccessDeserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+/// An entry of a database that may have been replaced with `none` when the
+/// object it held was removed from the game
+struct MaybeNone<T>(Option<T>);
+
+impl<'de, T> Deserialize<'de> for MaybeNone<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MaybeNoneVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for MaybeNoneVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = MaybeNone<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object or none")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v == "none" {
+                    Ok(MaybeNone(None))
+                } else {
+                    Err(de::Error::invalid_value(de::Unexpected::Str(v), &self))
+                }
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                T::deserialize(MapAccessDeserializer::new(map)).map(|x| MaybeNone(Some(x)))
+            }
+        }
+
+        deserializer.deserialize_any(MaybeNoneVisitor(PhantomData))
+    }
+}
+
+/// Deserializes the `database` of a game object manager, skipping entries
+/// that are `none`
+pub fn deserialize_database<'de, D, T>(deserializer: D) -> Result<HashMap<u32, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct DatabaseVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for DatabaseVisitor<T>
+    where
+        T: Deserialize<'de>,
+    {
+        type Value = HashMap<u32, T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a database of objects")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut result = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some((id, entry)) = map.next_entry::<u32, MaybeNone<T>>()? {
+                if let Some(entry) = entry.0 {
+                    result.insert(id, entry);
+                }
+            }
+
+            Ok(result)
+        }
+    }
+
+    deserializer.deserialize_map(DatabaseVisitor(PhantomData))
+}
+
+/// Deserializes a map color written as a list of RGB components. Colors in
+/// another form (eg: `hsv`) deserialize as `None` instead of failing.
+pub fn deserialize_rgb<'de, D>(deserializer: D) -> Result<Option<[u8; 3]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct RgbVisitor;
+
+    impl<'de> Visitor<'de> for RgbVisitor {
+        type Value = Option<[u8; 3]>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list of rgb components")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut components = Vec::with_capacity(3);
+            while let Some(x) = seq.next_element::<f64>()? {
+                components.push(x);
+            }
+
+            let channel =
+                |x: &f64| (x.fract() == 0.0 && (0.0..=255.0).contains(x)).then_some(*x as u8);
+            let result = match components.as_slice() {
+                [r, g, b] => channel(r)
+                    .zip(channel(g))
+                    .zip(channel(b))
+                    .map(|((r, g), b)| [r, g, b]),
+                _ => None,
+            };
+
+            Ok(result)
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_str<E>(self, _v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(RgbVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Object {
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Manager {
+        #[serde(deserialize_with = "deserialize_database")]
+        database: HashMap<u32, Object>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Colored {
+        #[serde(default, deserialize_with = "deserialize_rgb")]
+        color: Option<[u8; 3]>,
+    }
+
+    #[test]
+    fn test_rgb() {
+        let data = b"color={ 10 20 30 }";
+        let colored: Colored = jomini::text::de::from_utf8_slice(data).unwrap();
+        assert_eq!(colored.color, Some([10, 20, 30]));
+
+        let data = b"color={ 0.5 0.2 0.9 }";
+        let colored: Colored = jomini::text::de::from_utf8_slice(data).unwrap();
+        assert_eq!(colored.color, None);
+
+        let colored: Colored = jomini::text::de::from_utf8_slice(b"").unwrap();
+        assert_eq!(colored.color, None);
+    }
+
+    #[test]
+    fn test_database_skips_none() {
+        let data = b"database={ 1={ name=a } 2=none 3={ name=c } }";
+        let manager: Manager = jomini::text::de::from_utf8_slice(data).unwrap();
+        assert_eq!(manager.database.len(), 2);
+        assert_eq!(manager.database[&1], Object { name: "a".into() });
+        assert_eq!(manager.database[&3], Object { name: "c".into() });
+        assert!(!manager.database.contains_key(&2));
+    }
+}
@@ -0,0 +1,34 @@
+//! Filter payloads shared by the game modules
+
+use serde::Deserialize;
+
+/// Countries to include, by tag. An empty list includes every country
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryFilterPayload {
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl CountryFilterPayload {
+    pub fn matches(&self, tag: &str) -> bool {
+        self.tags.is_empty() || self.tags.iter().any(|x| x == tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_country_filter() {
+        let all = CountryFilterPayload { tags: Vec::new() };
+        assert!(all.matches("ROM"));
+
+        let some = CountryFilterPayload {
+            tags: vec![String::from("ROM")],
+        };
+        assert!(some.matches("ROM"));
+        assert!(!some.matches("CAR"));
+    }
+}
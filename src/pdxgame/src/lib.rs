@@ -0,0 +1,64 @@
+//! Loading of the game data written by compile_assets that the wasm crates
+//! share.
+
+use std::ops::Deref;
+
+/// Game data parsed from a buffer that it owns. The parsed view borrows from
+/// the buffer, which can't be expressed in a wasm-bindgen compatible struct,
+/// so the view's lifetime is erased here and the buffer is kept alive for as
+/// long as the view.
+pub struct GameData<T> {
+    // Declared before the buffer so that it is dropped first
+    game: T,
+    _data: Vec<u8>,
+}
+
+impl<T> GameData<T> {
+    /// Decompresses the zstd game data and parses it. The parser may only
+    /// borrow from the slice that it is given.
+    ///
+    /// ```
+    /// use pdxgame::GameData;
+    /// let compressed = zstd::encode_all(&b"hello"[..], 0).unwrap();
+    /// let data = GameData::from_zstd(&compressed, |x| x.len()).unwrap();
+    /// assert_eq!(*data, 5);
+    /// ```
+    pub fn from_zstd(data: &[u8], parse: impl FnOnce(&'static [u8]) -> T) -> std::io::Result<Self> {
+        let data = zstd::decode_all(data)?;
+
+        // SAFETY: the slice points at the vector's heap allocation, which
+        // stays put when the vector is moved into the struct and is neither
+        // modified nor freed before the parsed view is dropped.
+        let bytes: &'static [u8] = unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+        Ok(GameData {
+            game: parse(bytes),
+            _data: data,
+        })
+    }
+}
+
+impl<T> Deref for GameData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_data_borrows_buffer() {
+        let compressed = zstd::encode_all(&b"abc"[..], 0).unwrap();
+        let data = GameData::from_zstd(&compressed, |x| x).unwrap();
+        let moved = data;
+        assert_eq!(*moved, b"abc");
+    }
+
+    #[test]
+    fn test_game_data_invalid() {
+        assert!(GameData::from_zstd(b"not zstd", |x| x.len()).is_err());
+    }
+}
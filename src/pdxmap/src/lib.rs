@@ -0,0 +1,381 @@
+//! Game agnostic map painting: a raster of province ids and per province
+//! colors are combined into an RGBA canvas.
+
+/// Color painted on borders by [fill_borders]
+pub const BORDER_COLOR: [u8; 3] = [30, 30, 30];
+
+/// Color of provinces without an owner (eg: wastelands and seas)
+pub const UNOWNED_COLOR: [u8; 4] = [94, 94, 94, 255];
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum RasterError {
+    #[error("raster is missing its header")]
+    MissingHeader,
+
+    #[error("expected raster of {expected} pixels but received {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+}
+
+/// Province ids laid out by pixel, starting from the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvinceRaster {
+    width: u32,
+    height: u32,
+    provinces: Vec<u16>,
+}
+
+impl ProvinceRaster {
+    const HEADER_LEN: usize = 8;
+
+    pub fn new(width: u32, height: u32, provinces: Vec<u16>) -> Result<Self, RasterError> {
+        let expected = width as usize * height as usize;
+        if provinces.len() != expected {
+            return Err(RasterError::InvalidLength {
+                expected,
+                actual: provinces.len(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            provinces,
+        })
+    }
+
+    /// Decodes a raster written by [ProvinceRaster::to_bytes]: the width and
+    /// height as little endian u32s followed by a little endian u16 province
+    /// id per pixel.
+    ///
+    /// ```
+    /// use pdxmap::ProvinceRaster;
+    /// let raster = ProvinceRaster::new(2, 1, vec![1, 300]).unwrap();
+    /// let decoded = ProvinceRaster::from_bytes(&raster.to_bytes()).unwrap();
+    /// assert_eq!(decoded, raster);
+    /// ```
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RasterError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(RasterError::MissingHeader);
+        }
+
+        let (header, body) = data.split_at(Self::HEADER_LEN);
+        let width = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let height = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let provinces = body
+            .chunks_exact(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+            .collect();
+        Self::new(width, height, provinces)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::HEADER_LEN + self.provinces.len() * 2);
+        result.extend_from_slice(&self.width.to_le_bytes());
+        result.extend_from_slice(&self.height.to_le_bytes());
+        for id in &self.provinces {
+            result.extend_from_slice(&id.to_le_bytes());
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn provinces(&self) -> &[u16] {
+        &self.provinces
+    }
+
+    /// One more than the largest province id in the raster
+    pub fn province_count(&self) -> usize {
+        self.provinces
+            .iter()
+            .max()
+            .map_or(0, |x| usize::from(*x) + 1)
+    }
+}
+
+/// Which province edges are drawn as borders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderFill {
+    None,
+    Provinces,
+
+    /// Only edges between provinces of a different primary color
+    Countries,
+}
+
+impl BorderFill {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "None" => Some(BorderFill::None),
+            "Provinces" => Some(BorderFill::Provinces),
+            "Countries" => Some(BorderFill::Countries),
+            _ => None,
+        }
+    }
+}
+
+/// Primary and secondary RGBA colors indexed by province id. The secondary
+/// color is painted in stripes over the primary (eg: to show occupation).
+#[derive(Debug, Clone)]
+pub struct ProvinceColors {
+    primary: Vec<u8>,
+    secondary: Vec<u8>,
+}
+
+impl ProvinceColors {
+    pub fn new(provinces: usize, color: [u8; 4]) -> Self {
+        let primary: Vec<u8> = std::iter::repeat_n(color, provinces).flatten().collect();
+        let secondary = primary.clone();
+        Self { primary, secondary }
+    }
+
+    /// Sets both the primary and secondary color of the province. Ids
+    /// outside of the buffer are ignored.
+    pub fn set(&mut self, id: usize, color: [u8; 4]) {
+        Self::write(&mut self.primary, id, color);
+        Self::write(&mut self.secondary, id, color);
+    }
+
+    pub fn set_secondary(&mut self, id: usize, color: [u8; 4]) {
+        Self::write(&mut self.secondary, id, color);
+    }
+
+    fn write(data: &mut [u8], id: usize, color: [u8; 4]) {
+        if let Some(x) = data.get_mut(id * 4..id * 4 + 4) {
+            x.copy_from_slice(&color);
+        }
+    }
+
+    pub fn primary(&self) -> &[u8] {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &[u8] {
+        &self.secondary
+    }
+
+    /// The primary colors followed by the secondary colors, which is the
+    /// layout the browser map expects
+    pub fn into_data(self) -> Vec<u8> {
+        let mut result = self.primary;
+        result.extend_from_slice(&self.secondary);
+        result
+    }
+}
+
+/// A stable color for a country tag for games where the save doesn't record
+/// the country's map color
+///
+/// ```
+/// use pdxmap::tag_color;
+/// assert_eq!(tag_color("FRA"), tag_color("FRA"));
+/// assert_ne!(tag_color("FRA"), tag_color("ENG"));
+/// assert_eq!(tag_color("FRA")[3], 255);
+/// ```
+pub fn tag_color(tag: &str) -> [u8; 4] {
+    // FNV-1a
+    let hash = tag.bytes().fold(0x811c9dc5u32, |hash, x| {
+        (hash ^ u32::from(x)).wrapping_mul(0x01000193)
+    });
+
+    let hue = (hash % 360) as f32;
+    let saturation = 0.45 + ((hash >> 9) % 35) as f32 / 100.0;
+    let lightness = 0.4 + ((hash >> 17) % 25) as f32 / 100.0;
+    let [r, g, b] = hsl_to_rgb(hue, saturation, lightness);
+    [r, g, b, 255]
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+#[inline]
+fn memcmp_three(a: &[u8], b: &[u8]) -> bool {
+    a[0] == b[0] && a[1] == b[1] && a[2] == b[2]
+}
+
+/// Paints the RGB province colors onto an RGBA canvas of the raster's
+/// dimensions. When the secondary colors are the same length as the primary
+/// they are painted in diagonal stripes.
+pub fn fill_borders(
+    data: &mut [u8],
+    provinces: &[u16],
+    primary: &[u8],
+    secondary: &[u8],
+    fill: BorderFill,
+    width: usize,
+) {
+    let striped = secondary.len() == primary.len();
+    let paint = |pixel: &mut [u8], prov_id: usize, x: usize, y: usize| {
+        let province_offset = prov_id * 3;
+        let colors = if striped && (x + y) % 6 < 3 {
+            secondary
+        } else {
+            primary
+        };
+
+        pixel[0..3].copy_from_slice(&colors[province_offset..province_offset + 3]);
+        pixel[3] = 255;
+    };
+
+    if fill == BorderFill::None {
+        for (y, row) in data.chunks_exact_mut(width * 4).enumerate() {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                paint(pixel, usize::from(provinces[y * width + x]), x, y);
+            }
+        }
+        return;
+    }
+
+    let is_border = |a: usize, b: usize| match fill {
+        BorderFill::Countries => !memcmp_three(&primary[a * 3..], &primary[b * 3..]),
+        _ => true,
+    };
+
+    // The alpha channel of pixels yet to be painted is used to mark pixels
+    // that border a previously painted pixel
+    let height = provinces.len() / width;
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = y * width + x;
+            let data_offset = pixel * 4;
+            let prov_id = usize::from(provinces[pixel]);
+            let mut is_edge = false;
+
+            if y + 1 < height {
+                let prov_down = usize::from(provinces[pixel + width]);
+                if prov_id != prov_down && is_border(prov_id, prov_down) {
+                    data[data_offset + 3 + width * 4] = 1;
+                    is_edge = true;
+                }
+            }
+
+            if x + 1 < width {
+                let prov_right = usize::from(provinces[pixel + 1]);
+                if prov_id != prov_right && is_border(prov_id, prov_right) {
+                    data[data_offset + 3 + 4] = 1;
+                    is_edge = true;
+                }
+            }
+
+            let out = &mut data[data_offset..data_offset + 4];
+            if is_edge || out[3] == 1 {
+                out[0..3].copy_from_slice(&BORDER_COLOR);
+                out[3] = 255;
+            } else {
+                paint(out, prov_id, x, y);
+            }
+        }
+    }
+}
+
+/// Wasm entry point of [fill_borders] for the game specific wasm crates to
+/// re-export. Unknown fills leave the canvas untouched.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn map_fill_borders(
+    data: &mut [u8],
+    provinces: &[u16],
+    primary: &[u8],
+    secondary: &[u8],
+    fill: &str,
+    width: usize,
+) {
+    let Some(fill) = BorderFill::from_name(fill) else {
+        return;
+    };
+
+    fill_borders(data, provinces, primary, secondary, fill, width);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raster_length_mismatch() {
+        let actual = ProvinceRaster::new(2, 2, vec![0, 1, 2]);
+        assert_eq!(
+            actual,
+            Err(RasterError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            ProvinceRaster::from_bytes(&[0, 1]),
+            Err(RasterError::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn test_fill_country_borders() {
+        // Provinces 1 and 2 share a color so only the edge with 3 is a border
+        let provinces = [1, 2, 3, 1, 2, 3];
+        let primary = [0, 0, 0, 10, 10, 10, 10, 10, 10, 20, 20, 20];
+        let mut data = vec![0u8; provinces.len() * 4];
+        fill_borders(
+            &mut data,
+            &provinces,
+            &primary,
+            &[],
+            BorderFill::Countries,
+            3,
+        );
+
+        let colors: Vec<_> = data.chunks_exact(4).map(|x| x[0]).collect();
+        assert_eq!(colors, vec![10, 30, 30, 10, 30, 30]);
+        assert!(data.chunks_exact(4).all(|x| x[3] == 255));
+    }
+
+    #[test]
+    fn test_fill_stripes() {
+        let provinces = [0; 6];
+        let primary = [10, 10, 10];
+        let secondary = [20, 20, 20];
+        let mut data = vec![0u8; provinces.len() * 4];
+        fill_borders(
+            &mut data,
+            &provinces,
+            &primary,
+            &secondary,
+            BorderFill::None,
+            6,
+        );
+
+        let colors: Vec<_> = data.chunks_exact(4).map(|x| x[0]).collect();
+        assert_eq!(colors, vec![20, 20, 20, 10, 10, 10]);
+    }
+
+    #[test]
+    fn test_province_colors_layout() {
+        let mut colors = ProvinceColors::new(2, [0, 0, 0, 255]);
+        colors.set(1, [1, 2, 3, 255]);
+        colors.set_secondary(1, [4, 5, 6, 255]);
+        colors.set(5, [7, 7, 7, 255]);
+        assert_eq!(
+            colors.into_data(),
+            vec![0, 0, 0, 255, 1, 2, 3, 255, 0, 0, 0, 255, 4, 5, 6, 255]
+        );
+    }
+}
@@ -0,0 +1,675 @@
+//! Identifies the game, encoding, and container of a save from its header so
+//! that a save can be routed to the right game before it is parsed. Only the
+//! header and metadata are inspected, so large saves can be detected from
+//! their [SaveParts] without reading them in full.
+
+use jomini::binary::TokenResolver;
+use schemas::resolver::{Ck3FlatTokens, ImperatorFlatTokens, Vic3FlatTokens};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::OnceLock;
+use vic3save::SaveHeader;
+use zip_next as zip;
+
+/// The most decompressed data inspected for metadata
+const WINDOW_LEN: usize = 256 * 1024;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, thiserror::Error)]
+pub enum DetectError {
+    #[error("unrecognized save format")]
+    UnknownFormat,
+
+    #[error("invalid save header")]
+    InvalidHeader,
+
+    #[error("unable to read zip: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("unable to read save: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Game {
+    Eu4,
+    Ck3,
+    Hoi4,
+    Imperator,
+    Vic3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Text,
+    Binary,
+}
+
+/// How the save data is packaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+
+    /// zip archive as written by the games
+    Zip,
+
+    /// zstd frame as stored by rakaly
+    Zstd,
+
+    /// uncompressed tar of EU4 meta, gamestate, and ai entries
+    Tar,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDetection {
+    /// Unknown when the keys of binary CK3, Imperator, and Vic3 metadata
+    /// can't be resolved by the embedded token tables, in which case callers
+    /// should fall back to the file extension.
+    pub game: Option<Game>,
+    pub encoding: Encoding,
+    pub compression: Compression,
+
+    /// The version of the game that wrote the save (eg: `1.35.3.0`)
+    pub patch: Option<String>,
+}
+
+/// The start and end of a save. Zips keep their central directory at the
+/// end, so the middle of a save can go unread.
+#[derive(Debug, Clone, Copy)]
+pub struct SaveParts<'a> {
+    pub head: &'a [u8],
+    pub tail: &'a [u8],
+
+    /// The length of the whole save
+    pub len: u64,
+}
+
+impl<'a> SaveParts<'a> {
+    pub fn whole(data: &'a [u8]) -> Self {
+        SaveParts {
+            head: data,
+            tail: &[],
+            len: data.len() as u64,
+        }
+    }
+
+    fn reader(self, start: usize) -> PartsReader<'a> {
+        PartsReader {
+            parts: self,
+            start: start as u64,
+            pos: 0,
+        }
+    }
+}
+
+/// Reads the parts of a save from the given start as if the save was whole.
+/// Reads of the unread middle fail.
+struct PartsReader<'a> {
+    parts: SaveParts<'a>,
+    start: u64,
+    pos: u64,
+}
+
+impl Read for PartsReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let SaveParts { head, tail, len } = self.parts;
+        let pos = self.start + self.pos;
+        let tail_start = len.saturating_sub(tail.len() as u64);
+        let data = if pos >= len {
+            &[][..]
+        } else if pos < head.len() as u64 {
+            &head[pos as usize..]
+        } else if pos >= tail_start {
+            &tail[(pos - tail_start) as usize..]
+        } else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "save data was not read",
+            ));
+        };
+
+        let read = buf.len().min(data.len());
+        buf[..read].copy_from_slice(&data[..read]);
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for PartsReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(x) => Some(x),
+            SeekFrom::End(x) => (self.parts.len - self.start).checked_add_signed(x),
+            SeekFrom::Current(x) => self.pos.checked_add_signed(x),
+        };
+
+        self.pos = pos
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek"))?;
+        Ok(self.pos)
+    }
+}
+
+/// Detects the save format of the given data
+///
+/// ```
+/// use savedetect::{Compression, Encoding, Game};
+/// let data = b"HOI4txt\nplayer=\"GER\"\nversion=\"Arms Against Tyranny v1.14.1.12f0 (6e89)\"";
+/// let detected = savedetect::detect(&data[..]).unwrap();
+/// assert_eq!(detected.game, Some(Game::Hoi4));
+/// assert_eq!(detected.encoding, Encoding::Text);
+/// assert_eq!(detected.compression, Compression::None);
+/// assert_eq!(detected.patch.as_deref(), Some("1.14.1"));
+/// ```
+pub fn detect(data: &[u8]) -> Result<SaveDetection, DetectError> {
+    detect_parts(SaveParts::whole(data))
+}
+
+/// Detects the save format from the parts of a save
+pub fn detect_parts(parts: SaveParts) -> Result<SaveDetection, DetectError> {
+    let data = parts.head;
+    if let Some(meta) = tarsave::extract_tarsave_meta(data) {
+        let mut result = detect_uncompressed(meta)?;
+        result.compression = Compression::Tar;
+        Ok(result)
+    } else if data.starts_with(&ZSTD_MAGIC) {
+        let mut inflated = Vec::new();
+        zstd::stream::read::Decoder::new(data)?
+            .take(WINDOW_LEN as u64)
+            .read_to_end(&mut inflated)?;
+        let mut result = detect_uncompressed(&inflated)?;
+        result.compression = Compression::Zstd;
+        Ok(result)
+    } else if data.starts_with(b"SAV") {
+        detect_sav(parts)
+    } else if let Ok(mut zip) = zip::ZipArchive::new(parts.reader(0)) {
+        let mut meta = Vec::new();
+        zip.by_name("meta")?
+            .take(WINDOW_LEN as u64)
+            .read_to_end(&mut meta)?;
+        let mut result = detect_uncompressed(&meta)?;
+        result.compression = Compression::Zip;
+        Ok(result)
+    } else {
+        detect_uncompressed(data)
+    }
+}
+
+fn detect_uncompressed(data: &[u8]) -> Result<SaveDetection, DetectError> {
+    let (game, encoding) = if data.starts_with(b"EU4txt") {
+        (Game::Eu4, Encoding::Text)
+    } else if data.starts_with(b"EU4bin") {
+        (Game::Eu4, Encoding::Binary)
+    } else if data.starts_with(b"HOI4txt") {
+        (Game::Hoi4, Encoding::Text)
+    } else if data.starts_with(b"HOI4bin") {
+        (Game::Hoi4, Encoding::Binary)
+    } else if data.starts_with(b"SAV") {
+        return detect_sav(SaveParts::whole(data));
+    } else {
+        return Err(DetectError::UnknownFormat);
+    };
+
+    let window = &data[..data.len().min(WINDOW_LEN)];
+    let patch = match (game, encoding) {
+        (Game::Eu4, Encoding::Text) => eu4_text_patch(window),
+        _ => None,
+    };
+
+    // EU4 lists every version the save was played on with the latest last
+    let patch = match game {
+        Game::Eu4 => patch.or_else(|| versions(window, encoding).last()),
+        _ => versions(window, encoding).next(),
+    };
+
+    Ok(SaveDetection {
+        game: Some(game),
+        encoding,
+        compression: Compression::None,
+        patch,
+    })
+}
+
+/// Detects CK3, Imperator, and Vic3 saves, which start with a [SaveHeader]
+fn detect_sav(parts: SaveParts) -> Result<SaveDetection, DetectError> {
+    let header = SaveHeader::from_slice(parts.head).map_err(|_| DetectError::InvalidHeader)?;
+    let encoding = if header.kind().is_binary() {
+        Encoding::Binary
+    } else {
+        Encoding::Text
+    };
+
+    let data = &parts.head[header.header_len()..];
+    let (compression, meta) = match zip::ZipArchive::new(parts.reader(header.header_len())) {
+        Ok(mut zip) if zip.offset() == 0 => {
+            let mut meta = Vec::new();
+            zip.by_name("meta")?
+                .take(WINDOW_LEN as u64)
+                .read_to_end(&mut meta)?;
+            (Compression::Zip, Cow::from(meta))
+        }
+        Ok(zip) => {
+            let preamble = &data[..data.len().min(zip.offset() as usize)];
+            (Compression::Zip, Cow::from(preamble))
+        }
+        Err(_) => {
+            let len = match header.metadata_len() as usize {
+                0 => WINDOW_LEN,
+                x => x.min(WINDOW_LEN),
+            };
+            (Compression::None, Cow::from(&data[..data.len().min(len)]))
+        }
+    };
+
+    let game = match encoding {
+        Encoding::Text => Some(sav_text_game(&meta)),
+        Encoding::Binary => sav_binary_game(&meta, &SavTokens::embedded()),
+    };
+
+    Ok(SaveDetection {
+        game,
+        encoding,
+        compression,
+        patch: versions(&meta, encoding).next(),
+    })
+}
+
+/// CK3 and Vic3 nest their metadata under `meta_data` and only Vic3 records
+/// a `game_date` within it
+fn sav_text_game(meta: &[u8]) -> Game {
+    if find_key(meta, b"meta_data").is_none() {
+        Game::Imperator
+    } else if find_key(meta, b"game_date").is_some() {
+        Game::Vic3
+    } else {
+        Game::Ck3
+    }
+}
+
+/// The token tables of the games that share the [SaveHeader]
+struct SavTokens<'a> {
+    ck3: &'a dyn TokenResolver,
+    imperator: &'a dyn TokenResolver,
+    vic3: &'a dyn TokenResolver,
+}
+
+impl SavTokens<'static> {
+    fn embedded() -> Self {
+        static CK3: OnceLock<Ck3FlatTokens> = OnceLock::new();
+        static IMPERATOR: OnceLock<ImperatorFlatTokens> = OnceLock::new();
+        static VIC3: OnceLock<Vic3FlatTokens> = OnceLock::new();
+        SavTokens {
+            ck3: CK3.get_or_init(Ck3FlatTokens::new),
+            imperator: IMPERATOR.get_or_init(ImperatorFlatTokens::new),
+            vic3: VIC3.get_or_init(Vic3FlatTokens::new),
+        }
+    }
+}
+
+/// The binary equivalent of [sav_text_game]. Binary metadata is keyed by
+/// tokens whose ids differ between games, so the game is the one whose
+/// token table resolves the leading key to the metadata it writes first.
+fn sav_binary_game(meta: &[u8], tokens: &SavTokens) -> Option<Game> {
+    let first = match meta.get(..4)? {
+        [lo, hi, 0x01, 0x00] => u16::from_le_bytes([*lo, *hi]),
+        _ => return None,
+    };
+
+    let is_vic3 = tokens.vic3.resolve(first) == Some("meta_data")
+        && binary_keys(meta).any(|x| tokens.vic3.resolve(x) == Some("game_date"));
+    if is_vic3 {
+        Some(Game::Vic3)
+    } else if tokens.ck3.resolve(first) == Some("meta_data") {
+        Some(Game::Ck3)
+    } else if tokens.imperator.resolve(first) == Some("save_game_version") {
+        Some(Game::Imperator)
+    } else {
+        None
+    }
+}
+
+/// Candidate keys of binary data: a token followed by the equals operator
+/// (0x01)
+fn binary_keys(data: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    data.windows(4).filter_map(|x| match x {
+        [lo, hi, 0x01, 0x00] => Some(u16::from_le_bytes([*lo, *hi])),
+        _ => None,
+    })
+}
+
+/// Returns the position after the first text key assignment (`key=`)
+fn find_key(data: &[u8], key: &[u8]) -> Option<usize> {
+    let mut start = 0;
+    while let Some(pos) = data[start..].windows(key.len()).position(|x| x == key) {
+        let pos = start + pos;
+        let end = pos + key.len();
+        let delimited =
+            pos == 0 || matches!(data[pos - 1], b'{' | b'}') || data[pos - 1].is_ascii_whitespace();
+        let rest = &data[end..];
+        if let Some(value) = rest.iter().position(|x| !x.is_ascii_whitespace()) {
+            if delimited && rest[value] == b'=' {
+                return Some(end + value + 1);
+            }
+        }
+        start = pos + 1;
+    }
+
+    None
+}
+
+/// The EU4 patch written in the `savegame_version` object
+fn eu4_text_patch(data: &[u8]) -> Option<String> {
+    let start = find_key(data, b"savegame_version")?;
+    let end = start + data[start..].iter().position(|&x| x == b'}')?;
+    let mut parts = [None; 4];
+    let mut tokens = data[start..end]
+        .split(|x| x.is_ascii_whitespace() || matches!(x, b'{' | b'='))
+        .filter(|x| !x.is_empty());
+
+    while let Some(key) = tokens.next() {
+        let idx = match key {
+            b"first" => 0,
+            b"second" => 1,
+            b"third" => 2,
+            b"forth" => 3,
+            _ => continue,
+        };
+
+        let value = tokens.next()?;
+        parts[idx] = std::str::from_utf8(value).ok()?.parse::<u16>().ok();
+    }
+
+    let [Some(first), Some(second), Some(third), Some(fourth)] = parts else {
+        return None;
+    };
+
+    Some(format!("{}.{}.{}.{}", first, second, third, fourth))
+}
+
+/// Versions found in the quoted strings of text data or the string scalars
+/// of binary data
+fn versions(data: &[u8], encoding: Encoding) -> impl Iterator<Item = String> + '_ {
+    let strings: Box<dyn Iterator<Item = &[u8]> + '_> = match encoding {
+        Encoding::Text => Box::new(data.split(|&x| x == b'"').skip(1).step_by(2)),
+        Encoding::Binary => Box::new(binary_strings(data)),
+    };
+
+    strings.filter_map(parse_version)
+}
+
+/// Candidate string scalars of binary data: a quoted (0x0f) or unquoted
+/// (0x17) token followed by a little endian length and the string. Keys are
+/// tokens, so candidates are found by scanning rather than parsing.
+fn binary_strings(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    (0..data.len()).filter_map(move |i| {
+        let len = match data.get(i..i + 4)? {
+            [0x0f | 0x17, 0x00, lo, hi] => u16::from_le_bytes([*lo, *hi]) as usize,
+            _ => return None,
+        };
+
+        data.get(i + 4..i + 4 + len)
+    })
+}
+
+/// Extracts a version from strings like `1.9.2` and
+/// `Arms Against Tyranny v1.14.1.12f0 (6e89)`, where trailing segments
+/// that aren't numeric are omitted
+fn parse_version(s: &[u8]) -> Option<String> {
+    let start = (0..s.len()).find(|&i| s[i].is_ascii_digit() && (i == 0 || s[i - 1] == b'v'))?;
+
+    let mut segments = Vec::new();
+    for segment in s[start..].split(|&x| x == b'.') {
+        let digits = segment.iter().take_while(|x| x.is_ascii_digit()).count();
+        let rest = &segment[digits..];
+        if digits == 0 || rest.first().is_some_and(|x| x.is_ascii_alphanumeric()) {
+            break;
+        }
+
+        segments.push(std::str::from_utf8(&segment[..digits]).ok()?);
+        if !rest.is_empty() {
+            break;
+        }
+    }
+
+    (segments.len() > 1).then(|| segments.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{Cursor, Write};
+
+    const EU4_META: &[u8] = b"EU4txt
+date=1444.11.11
+savegame_version={
+\tfirst=1
+\tsecond=35
+\tthird=3
+\tforth=0
+\tname=\"Lotharingia\"
+}
+savegame_versions={
+\t\"1.34.5.0\"
+\t\"1.35.3.0\"
+}
+";
+
+    fn sav(kind: u16, meta: &[u8]) -> Vec<u8> {
+        let mut data = format!("SAV01{:02x}0123abcd{:08x}\n", kind, meta.len()).into_bytes();
+        data.extend_from_slice(meta);
+        data.extend_from_slice(b"date=1.1.1\n");
+        data
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version(b"1.9.2").as_deref(), Some("1.9.2"));
+        assert_eq!(
+            parse_version(b"Arms Against Tyranny v1.14.1.12f0 (6e89)").as_deref(),
+            Some("1.14.1")
+        );
+        assert_eq!(parse_version(b"1.10 (Pike)").as_deref(), Some("1.10"));
+        assert_eq!(parse_version(b"Kingdom of France"), None);
+        assert_eq!(parse_version(b"GER2"), None);
+        assert_eq!(parse_version(b"3"), None);
+    }
+
+    #[test]
+    fn test_eu4_text() {
+        let actual = detect(EU4_META).unwrap();
+        let expected = SaveDetection {
+            game: Some(Game::Eu4),
+            encoding: Encoding::Text,
+            compression: Compression::None,
+            patch: Some(String::from("1.35.3.0")),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_eu4_binary() {
+        let mut data = b"EU4bin".to_vec();
+        for version in ["1.34.5.0", "1.35.3.0"] {
+            data.extend_from_slice(&[0x0f, 0x00, version.len() as u8, 0x00]);
+            data.extend_from_slice(version.as_bytes());
+        }
+
+        let actual = detect(&data).unwrap();
+        assert_eq!(actual.game, Some(Game::Eu4));
+        assert_eq!(actual.encoding, Encoding::Binary);
+        assert_eq!(actual.patch.as_deref(), Some("1.35.3.0"));
+    }
+
+    #[test]
+    fn test_hoi4_binary() {
+        let version = b"Arms Against Tyranny v1.14.1.12f0 (6e89)";
+        let mut data = b"HOI4bin".to_vec();
+        data.extend_from_slice(&[
+            0x2e,
+            0x2c,
+            0x01,
+            0x00,
+            0x0f,
+            0x00,
+            version.len() as u8,
+            0x00,
+        ]);
+        data.extend_from_slice(version);
+
+        let actual = detect(&data).unwrap();
+        assert_eq!(actual.game, Some(Game::Hoi4));
+        assert_eq!(actual.encoding, Encoding::Binary);
+        assert_eq!(actual.patch.as_deref(), Some("1.14.1"));
+    }
+
+    #[test]
+    fn test_sav_text_games() {
+        let ck3 = b"meta_data={\n\tversion=\"1.9.2\"\n\tmeta_title_name=\"Kingdom of France\"\n}\n";
+        let vic3 = b"meta_data={\n\tversion=\"1.1.2\"\n\tgame_date=1836.1.1\n}\n";
+        let imperator = b"save_game_version=3\nversion=\"2.0.4\"\ndate=450.10.1\n";
+        for (meta, game, patch) in [
+            (&ck3[..], Game::Ck3, "1.9.2"),
+            (&vic3[..], Game::Vic3, "1.1.2"),
+            (&imperator[..], Game::Imperator, "2.0.4"),
+        ] {
+            let actual = detect(&sav(0, meta)).unwrap();
+            let expected = SaveDetection {
+                game: Some(game),
+                encoding: Encoding::Text,
+                compression: Compression::None,
+                patch: Some(String::from(patch)),
+            };
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_sav_binary() {
+        let mut meta = vec![0x01, 0x00, 0x0f, 0x00, 0x05, 0x00];
+        meta.extend_from_slice(b"1.9.2");
+
+        let actual = detect(&sav(1, &meta)).unwrap();
+        assert_eq!(actual.game, None);
+        assert_eq!(actual.encoding, Encoding::Binary);
+        assert_eq!(actual.patch.as_deref(), Some("1.9.2"));
+    }
+
+    #[test]
+    fn test_sav_binary_games() {
+        let ck3: HashMap<u16, &str> = HashMap::from([(0x2c4e, "meta_data")]);
+        let vic3: HashMap<u16, &str> =
+            HashMap::from([(0x2c4e, "meta_data"), (0x2d01, "game_date")]);
+        let imperator: HashMap<u16, &str> = HashMap::from([(0x2a10, "save_game_version")]);
+        let tokens = SavTokens {
+            ck3: &ck3,
+            imperator: &imperator,
+            vic3: &vic3,
+        };
+
+        let ck3_meta = [0x4e, 0x2c, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00];
+        let vic3_meta = [
+            0x4e, 0x2c, 0x01, 0x00, 0x03, 0x00, 0x01, 0x2d, 0x01, 0x00, 0x0c, 0x00,
+        ];
+        assert_eq!(sav_binary_game(&vic3_meta, &tokens), Some(Game::Vic3));
+        assert_eq!(sav_binary_game(&ck3_meta, &tokens), Some(Game::Ck3));
+
+        let imperator_meta = [0x10, 0x2a, 0x01, 0x00, 0x0c, 0x00, 0x03, 0x00];
+        assert_eq!(
+            sav_binary_game(&imperator_meta, &tokens),
+            Some(Game::Imperator)
+        );
+
+        let empty: HashMap<u16, &str> = HashMap::new();
+        let tokens = SavTokens {
+            ck3: &empty,
+            imperator: &empty,
+            vic3: &empty,
+        };
+        assert_eq!(sav_binary_game(&ck3_meta, &tokens), None);
+    }
+
+    #[test]
+    fn test_zstd() {
+        let data = zstd::bulk::compress(EU4_META, 0).unwrap();
+        let actual = detect(&data).unwrap();
+        assert_eq!(actual.game, Some(Game::Eu4));
+        assert_eq!(actual.compression, Compression::Zstd);
+        assert_eq!(actual.patch.as_deref(), Some("1.35.3.0"));
+    }
+
+    #[test]
+    fn test_zip() {
+        let mut out_zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, data) in [
+            ("meta", EU4_META),
+            ("gamestate", b"EU4txt\n"),
+            ("ai", b"EU4txt\n"),
+        ] {
+            out_zip.start_file(String::from(name), options).unwrap();
+            out_zip.write_all(data).unwrap();
+        }
+        let data = out_zip.finish().unwrap().into_inner();
+
+        let actual = detect(&data).unwrap();
+        assert_eq!(actual.game, Some(Game::Eu4));
+        assert_eq!(actual.encoding, Encoding::Text);
+        assert_eq!(actual.compression, Compression::Zip);
+        assert_eq!(actual.patch.as_deref(), Some("1.35.3.0"));
+    }
+
+    #[test]
+    fn test_zip_parts() {
+        let mut out_zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let gamestate = vec![b' '; 512 * 1024];
+        for (name, data) in [("meta", EU4_META), ("gamestate", &gamestate[..])] {
+            out_zip.start_file(String::from(name), options).unwrap();
+            out_zip.write_all(data).unwrap();
+        }
+        let data = out_zip.finish().unwrap().into_inner();
+
+        let parts = SaveParts {
+            head: &data[..4096],
+            tail: &data[data.len() - 1024..],
+            len: data.len() as u64,
+        };
+        let actual = detect_parts(parts).unwrap();
+        assert_eq!(actual.game, Some(Game::Eu4));
+        assert_eq!(actual.compression, Compression::Zip);
+        assert_eq!(actual.patch.as_deref(), Some("1.35.3.0"));
+    }
+
+    #[test]
+    fn test_tarsave() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in [
+            ("meta", EU4_META),
+            ("gamestate", b"EU4txt\n"),
+            ("ai", b"EU4txt\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        let data = builder.into_inner().unwrap();
+
+        let actual = detect(&data).unwrap();
+        assert_eq!(actual.game, Some(Game::Eu4));
+        assert_eq!(actual.compression, Compression::Tar);
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert!(matches!(detect(b"hello"), Err(DetectError::UnknownFormat)));
+    }
+}
@@ -7,9 +7,20 @@ use std::{
 
 fn main() {
     if std::env::var("XARGO_HOME").is_err() {
-        println!("cargo:rerun-if-changed=src/eu4.fbs");
+        let schemas = [
+            Path::new("src/eu4.fbs"),
+            Path::new("src/ck3.fbs"),
+            Path::new("src/hoi4.fbs"),
+            Path::new("src/imperator.fbs"),
+            Path::new("src/vic3.fbs"),
+        ];
+
+        for schema in &schemas {
+            println!("cargo:rerun-if-changed={}", schema.display());
+        }
+
         flatc_rust::run(flatc_rust::Args {
-            inputs: &[Path::new("src/eu4.fbs")],
+            inputs: &schemas,
             out_dir: Path::new("target/flatbuffers/"),
             ..Default::default()
         })
@@ -0,0 +1,177 @@
+//! Builders for the vectors of the non-EU4 game data. They are used by
+//! compile_assets to write the game data and by the accessor crates to test
+//! against it. Keyed vectors must be given in key order.
+
+use crate::{ck3, hoi4, imperator, vic3};
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Vector, WIPOffset};
+
+type Offsets<'fbb, T> = WIPOffset<Vector<'fbb, ForwardsUOffset<T>>>;
+
+pub fn ck3_entries<'fbb, I, K, V>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    entries: I,
+) -> Offsets<'fbb, ck3::EntryString<'fbb>>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(key, value)| {
+            let key = buffer.create_string(key.as_ref());
+            let value = buffer.create_string(value.as_ref());
+            let args = ck3::EntryStringArgs {
+                key: Some(key),
+                value: Some(value),
+            };
+            ck3::EntryString::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&entries)
+}
+
+pub fn vic3_entries<'fbb, I, K, V>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    entries: I,
+) -> Offsets<'fbb, vic3::EntryString<'fbb>>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(key, value)| {
+            let key = buffer.create_string(key.as_ref());
+            let value = buffer.create_string(value.as_ref());
+            let args = vic3::EntryStringArgs {
+                key: Some(key),
+                value: Some(value),
+            };
+            vic3::EntryString::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&entries)
+}
+
+/// Countries from their tag, name, and adjective
+pub fn hoi4_countries<'fbb, I, S>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    countries: I,
+) -> Offsets<'fbb, hoi4::Country<'fbb>>
+where
+    I: IntoIterator<Item = (S, S, S)>,
+    S: AsRef<str>,
+{
+    let countries: Vec<_> = countries
+        .into_iter()
+        .map(|(tag, name, adjective)| {
+            let tag = buffer.create_string(tag.as_ref());
+            let name = buffer.create_string(name.as_ref());
+            let adjective = buffer.create_string(adjective.as_ref());
+            let args = hoi4::CountryArgs {
+                tag: Some(tag),
+                name: Some(name),
+                adjective: Some(adjective),
+            };
+            hoi4::Country::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&countries)
+}
+
+/// Countries from their tag, name, and adjective
+pub fn imperator_countries<'fbb, I, S>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    countries: I,
+) -> Offsets<'fbb, imperator::Country<'fbb>>
+where
+    I: IntoIterator<Item = (S, S, S)>,
+    S: AsRef<str>,
+{
+    let countries: Vec<_> = countries
+        .into_iter()
+        .map(|(tag, name, adjective)| {
+            let tag = buffer.create_string(tag.as_ref());
+            let name = buffer.create_string(name.as_ref());
+            let adjective = buffer.create_string(adjective.as_ref());
+            let args = imperator::CountryArgs {
+                tag: Some(tag),
+                name: Some(name),
+                adjective: Some(adjective),
+            };
+            imperator::Country::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&countries)
+}
+
+/// Countries from their tag, name, and adjective
+pub fn vic3_countries<'fbb, I, S>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    countries: I,
+) -> Offsets<'fbb, vic3::Country<'fbb>>
+where
+    I: IntoIterator<Item = (S, S, S)>,
+    S: AsRef<str>,
+{
+    let countries: Vec<_> = countries
+        .into_iter()
+        .map(|(tag, name, adjective)| {
+            let tag = buffer.create_string(tag.as_ref());
+            let name = buffer.create_string(name.as_ref());
+            let adjective = buffer.create_string(adjective.as_ref());
+            let args = vic3::CountryArgs {
+                tag: Some(tag),
+                name: Some(name),
+                adjective: Some(adjective),
+            };
+            vic3::Country::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&countries)
+}
+
+/// States from their id and, when localized, their name
+pub fn hoi4_states<'fbb, I, S>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    states: I,
+) -> Offsets<'fbb, hoi4::State<'fbb>>
+where
+    I: IntoIterator<Item = (u32, Option<S>)>,
+    S: AsRef<str>,
+{
+    let states: Vec<_> = states
+        .into_iter()
+        .map(|(id, name)| {
+            let name = name.map(|x| buffer.create_string(x.as_ref()));
+            let args = hoi4::StateArgs { id, name };
+            hoi4::State::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&states)
+}
+
+/// Provinces from their id and name
+pub fn imperator_provinces<'fbb, I, S>(
+    buffer: &mut FlatBufferBuilder<'fbb>,
+    provinces: I,
+) -> Offsets<'fbb, imperator::Province<'fbb>>
+where
+    I: IntoIterator<Item = (u16, S)>,
+    S: AsRef<str>,
+{
+    let provinces: Vec<_> = provinces
+        .into_iter()
+        .map(|(id, name)| {
+            let name = buffer.create_string(name.as_ref());
+            let args = imperator::ProvinceArgs {
+                id,
+                name: Some(name),
+            };
+            imperator::Province::create(buffer, &args)
+        })
+        .collect();
+    buffer.create_vector(&provinces)
+}
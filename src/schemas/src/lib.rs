@@ -2,8 +2,31 @@
 #[path = "../target/flatbuffers/eu4_generated.rs"]
 mod eu4_flatbuffers;
 
+#[allow(non_snake_case, unused_imports, clippy::all)]
+#[path = "../target/flatbuffers/ck3_generated.rs"]
+mod ck3_flatbuffers;
+
+#[allow(non_snake_case, unused_imports, clippy::all)]
+#[path = "../target/flatbuffers/hoi4_generated.rs"]
+mod hoi4_flatbuffers;
+
+#[allow(non_snake_case, unused_imports, clippy::all)]
+#[path = "../target/flatbuffers/imperator_generated.rs"]
+mod imperator_flatbuffers;
+
+#[allow(non_snake_case, unused_imports, clippy::all)]
+#[path = "../target/flatbuffers/vic3_generated.rs"]
+mod vic3_flatbuffers;
+
+pub mod builder;
 pub mod resolver;
+mod search;
 
+pub use ck3_flatbuffers::rakaly::ck_3 as ck3;
 pub use eu4_flatbuffers::rakaly::eu_4 as eu4;
 pub use flatbuffers;
+pub use hoi4_flatbuffers::rakaly::hoi_4 as hoi4;
+pub use imperator_flatbuffers::rakaly::imperator;
 pub use resolver::{FlatResolver, BREAKPOINT};
+pub use search::binary_search_by;
+pub use vic3_flatbuffers::rakaly::vic_3 as vic3;
@@ -0,0 +1,28 @@
+use flatbuffers::{Follow, Vector};
+use std::cmp::Ordering;
+
+/// Binary search over a flatbuffer vector sorted by its key, as the
+/// generated `key_compare_with_value` only compares a single element
+pub fn binary_search_by<'a, 'b: 'a, T, F>(haystack: &'b Vector<T>, mut f: F) -> Result<usize, usize>
+where
+    F: FnMut(T::Inner) -> Ordering,
+    T: Follow<'a> + 'a,
+{
+    let mut size = haystack.len();
+    let mut left = 0;
+    let mut right = size;
+    while left < right {
+        let mid = left + size / 2;
+        let cmp = f(haystack.get(mid));
+        if cmp == Ordering::Less {
+            left = mid + 1;
+        } else if cmp == Ordering::Greater {
+            right = mid;
+        } else {
+            return Ok(mid);
+        }
+
+        size = right - left;
+    }
+    Err(left)
+}
@@ -46,3 +46,23 @@ pub fn extract_tarsave(data: &[u8]) -> Option<TarSave> {
         ai,
     })
 }
+
+/// Extracts only the meta entry, which allows the data to be truncated
+/// after it
+pub fn extract_tarsave_meta(data: &[u8]) -> Option<&[u8]> {
+    if !is_tarsave(data) {
+        return None;
+    }
+
+    let mut archive = tar::Archive::new(data);
+    for entry in archive.entries().ok()? {
+        let entry = entry.ok()?;
+        if entry.path().ok()?.to_str() == Some("meta") {
+            let pos = entry.raw_file_position() as usize;
+            let len = entry.size() as usize;
+            return data.get(pos..pos + len);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,75 @@
+//! Accessors for the Vic3 game data written by compile_assets
+
+use schemas::binary_search_by;
+
+#[derive(Debug)]
+pub struct LocalizedCountry<'a> {
+    pub name: &'a str,
+    pub adjective: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Game<'a> {
+    data: schemas::vic3::Game<'a>,
+}
+
+impl<'a> Game<'a> {
+    pub fn from_flatbuffer(data: &'a [u8]) -> Self {
+        let fb = schemas::vic3::root_as_game(data).unwrap();
+        Self { data: fb }
+    }
+
+    pub fn localize_country(&self, tag: &str) -> Option<LocalizedCountry<'a>> {
+        let countries = self.data.countries()?;
+        let idx = binary_search_by(&countries, |x| x.key_compare_with_value(tag)).ok()?;
+        let country = countries.get(idx);
+        Some(LocalizedCountry {
+            name: country.name()?,
+            adjective: country.adjective(),
+        })
+    }
+
+    /// The localized name of a state region (eg: `STATE_SVEALAND`)
+    pub fn localize_state(&self, key: &str) -> Option<&'a str> {
+        let states = self.data.states()?;
+        let idx = binary_search_by(&states, |x| x.key_compare_with_value(key)).ok()?;
+        states.get(idx).value()
+    }
+
+    pub fn localize_culture(&self, key: &str) -> Option<&'a str> {
+        let cultures = self.data.cultures()?;
+        let idx = binary_search_by(&cultures, |x| x.key_compare_with_value(key)).ok()?;
+        cultures.get(idx).value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemas::builder::{vic3_countries, vic3_entries};
+    use schemas::flatbuffers::FlatBufferBuilder;
+    use schemas::vic3::GameArgs;
+
+    #[test]
+    fn test_localize() {
+        let mut buffer = FlatBufferBuilder::new();
+        let countries = vic3_countries(&mut buffer, [("SWE", "Sweden", "Swedish")]);
+        let states = vic3_entries(&mut buffer, [("STATE_SVEALAND", "Svealand")]);
+        let game = schemas::vic3::Game::create(
+            &mut buffer,
+            &GameArgs {
+                countries: Some(countries),
+                states: Some(states),
+                cultures: None,
+            },
+        );
+        buffer.finish(game, None);
+
+        let game = Game::from_flatbuffer(buffer.finished_data());
+        let sweden = game.localize_country("SWE").unwrap();
+        assert_eq!(sweden.name, "Sweden");
+        assert_eq!(sweden.adjective, Some("Swedish"));
+        assert_eq!(game.localize_state("STATE_SVEALAND"), Some("Svealand"));
+        assert_eq!(game.localize_culture("swedish"), None);
+    }
+}
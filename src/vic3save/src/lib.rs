@@ -4,6 +4,7 @@ pub mod file;
 pub(crate) mod flavor;
 mod header;
 mod melt;
+pub mod models;
 pub mod tokens;
 mod vic3date;
 
@@ -0,0 +1,290 @@
+use crate::Vic3Date;
+use pdxde::deserialize_database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The subset of the gamestate that is modeled
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Vic3Gamestate {
+    #[serde(default)]
+    pub country_manager: Vic3Database<Vic3Country>,
+
+    #[serde(default)]
+    pub states: Vic3Database<Vic3State>,
+
+    #[serde(default)]
+    pub pops: Vic3Database<Vic3Pop>,
+
+    #[serde(default)]
+    pub cultures: Vic3Database<Vic3Culture>,
+
+    #[serde(default)]
+    pub building_manager: Vic3Database<Vic3Building>,
+
+    #[serde(default)]
+    pub market_manager: Vic3Database<Vic3Market>,
+}
+
+/// Game objects keyed by their id. Objects that have been removed from the
+/// game are omitted.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct Vic3Database<T> {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub database: HashMap<u32, T>,
+}
+
+impl<T> Default for Vic3Database<T> {
+    fn default() -> Self {
+        Self {
+            database: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Vic3Database<T> {
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.database.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &T)> {
+        self.database.iter().map(|(id, x)| (*id, x))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3Country {
+    /// The country's tag
+    pub definition: String,
+
+    #[serde(default)]
+    pub capital: Option<u32>,
+
+    #[serde(default)]
+    pub gdp: Vic3TrackedValue,
+
+    /// Average standard of living
+    #[serde(default)]
+    pub avgsoltrend: Vic3TrackedValue,
+
+    #[serde(default)]
+    pub prestige: Vic3TrackedValue,
+
+    /// Share of the population that is literate
+    #[serde(default)]
+    pub literacy: Vic3TrackedValue,
+
+    #[serde(default)]
+    pub pop_statistics: Vic3CountryPopStatistics,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Vic3CountryPopStatistics {
+    #[serde(default)]
+    pub trend_population: Vic3TrackedValue,
+}
+
+/// A value that the game samples at a regular interval to draw its own
+/// graphs
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Vic3TrackedValue {
+    #[serde(default)]
+    pub channels: HashMap<u32, Vic3TrackedChannel>,
+
+    /// Days between samples
+    #[serde(default)]
+    pub sample_rate: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3TrackedChannel {
+    /// Date the sampling is relative to
+    pub date: Vic3Date,
+
+    /// Number of sample intervals between the date and the first value
+    #[serde(default)]
+    pub index: i32,
+
+    #[serde(default)]
+    pub values: Vec<f64>,
+}
+
+impl Vic3TrackedValue {
+    /// The game samples weekly unless told otherwise
+    pub const DEFAULT_SAMPLE_RATE: i32 = 7;
+
+    /// Samples of the primary channel in chronological order. Sample dates
+    /// keep the hour of the channel's date.
+    ///
+    /// ```
+    /// use vic3save::{models::{Vic3TrackedChannel, Vic3TrackedValue}, Vic3Date};
+    /// let channel = Vic3TrackedChannel {
+    ///     date: Vic3Date::from_ymdh(1836, 1, 1, 6),
+    ///     index: 1,
+    ///     values: vec![1.0, 2.0],
+    /// };
+    /// let value = Vic3TrackedValue {
+    ///     channels: [(0, channel)].into_iter().collect(),
+    ///     sample_rate: None,
+    /// };
+    /// let samples: Vec<_> = value.iter().collect();
+    /// assert_eq!(samples[0], (Vic3Date::from_ymdh(1836, 1, 8, 6), 1.0));
+    /// assert_eq!(samples[1], (Vic3Date::from_ymdh(1836, 1, 15, 6), 2.0));
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Vic3Date, f64)> + '_ {
+        let sample_rate = self.sample_rate.unwrap_or(Self::DEFAULT_SAMPLE_RATE);
+        self.channels.get(&0).into_iter().flat_map(move |channel| {
+            channel.values.iter().enumerate().map(move |(i, value)| {
+                let offset = (channel.index + i as i32) * sample_rate;
+                (channel.date.add_days(offset), *value)
+            })
+        })
+    }
+
+    /// The most recent sample
+    pub fn last(&self) -> Option<(Vic3Date, f64)> {
+        self.iter().last()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3State {
+    /// Id of the owning country
+    pub country: u32,
+
+    /// The strategic region key
+    #[serde(default)]
+    pub region: Option<String>,
+
+    #[serde(default)]
+    pub infrastructure: f64,
+
+    #[serde(default)]
+    pub provinces: Vic3StateProvinces,
+}
+
+/// The map provinces of a state stored as pairs of the first province index
+/// and the number of provinces that follow it
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Vic3StateProvinces {
+    #[serde(default)]
+    pub provinces: Vec<u32>,
+}
+
+impl Vic3StateProvinces {
+    /// Expands the ranges into province indices
+    ///
+    /// ```
+    /// use vic3save::models::Vic3StateProvinces;
+    /// let provinces = Vic3StateProvinces {
+    ///     provinces: vec![10, 2, 20, 0],
+    /// };
+    /// let ids: Vec<_> = provinces.iter().collect();
+    /// assert_eq!(ids, vec![10, 11, 12, 20]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.provinces
+            .chunks_exact(2)
+            .flat_map(|x| x[0]..=x[0] + x[1])
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3Pop {
+    /// Pop type (eg: `laborers`, `aristocrats`)
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Id of the state the pop lives in
+    pub location: u32,
+
+    pub culture: u32,
+
+    #[serde(default)]
+    pub religion: Option<String>,
+
+    #[serde(default)]
+    pub workforce: i64,
+
+    #[serde(default)]
+    pub dependents: i64,
+
+    #[serde(default)]
+    pub wealth: f64,
+
+    /// Number of literate people in the pop's workforce
+    #[serde(default)]
+    pub num_literate: i64,
+
+    /// Number of people in the pop that are radicals
+    #[serde(default)]
+    pub radicals: i64,
+
+    /// Number of people in the pop that are loyalists
+    #[serde(default)]
+    pub loyalists: i64,
+}
+
+impl Vic3Pop {
+    pub fn size(&self) -> i64 {
+        self.workforce + self.dependents
+    }
+
+    /// The social strata of the pop type, if known
+    ///
+    /// ```
+    /// use vic3save::models::{Vic3Pop, Vic3Strata};
+    /// assert_eq!(Vic3Pop::strata_of("aristocrats"), Some(Vic3Strata::Upper));
+    /// assert_eq!(Vic3Pop::strata_of("clerks"), Some(Vic3Strata::Middle));
+    /// assert_eq!(Vic3Pop::strata_of("peasants"), Some(Vic3Strata::Lower));
+    /// ```
+    pub fn strata_of(kind: &str) -> Option<Vic3Strata> {
+        match kind {
+            "aristocrats" | "capitalists" => Some(Vic3Strata::Upper),
+            "academics" | "bureaucrats" | "clergymen" | "clerks" | "engineers" | "farmers"
+            | "officers" | "shopkeepers" => Some(Vic3Strata::Middle),
+            "laborers" | "machinists" | "peasants" | "slaves" | "soldiers" => {
+                Some(Vic3Strata::Lower)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn strata(&self) -> Option<Vic3Strata> {
+        Self::strata_of(&self.kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Vic3Strata {
+    Upper,
+    Middle,
+    Lower,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3Culture {
+    /// The culture key (eg: `british`)
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3Building {
+    /// Building type (eg: `building_textile_mills`)
+    pub building: String,
+
+    /// Id of the state the building is in
+    pub state: u32,
+
+    #[serde(default)]
+    pub level: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vic3Market {
+    /// Id of the country that leads the market
+    #[serde(default)]
+    pub owner: Option<u32>,
+}
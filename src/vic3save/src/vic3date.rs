@@ -91,6 +91,45 @@ impl Vic3Date {
     pub fn hour(&self) -> u8 {
         self.raw.hour()
     }
+
+    /// Returns a new date offset by the given number of days, keeping the
+    /// hour. The game's calendar has no leap years. Offsets that would leave
+    /// the years 1 through `i16::MAX` clamp to the first or last day of that
+    /// range.
+    ///
+    /// ```
+    /// use vic3save::{Vic3Date, PdsDate};
+    /// let date = Vic3Date::from_ymdh(1836, 12, 25, 6);
+    /// assert_eq!(date.add_days(7), Vic3Date::from_ymdh(1837, 1, 1, 6));
+    /// assert_eq!(date.add_days(-359), Vic3Date::from_ymdh(1836, 1, 1, 6));
+    /// assert_eq!(date.add_days(i32::MAX), Vic3Date::from_ymdh(i16::MAX, 12, 31, 6));
+    /// assert_eq!(date.add_days(i32::MIN), Vic3Date::from_ymdh(1, 1, 1, 6));
+    /// ```
+    pub fn add_days(&self, days: i32) -> Vic3Date {
+        let days_before_month = |month: u8| -> i32 {
+            DAYS_PER_MONTH[1..usize::from(month)]
+                .iter()
+                .map(|x| i32::from(*x))
+                .sum()
+        };
+
+        let day_of_year = days_before_month(self.month()) + i32::from(self.day()) - 1;
+        let first = 365;
+        let last = i32::from(i16::MAX) * 365 + 364;
+        let ordinal = (i32::from(self.year()) * 365 + day_of_year)
+            .saturating_add(days)
+            .clamp(first, last);
+
+        let year = i16::try_from(ordinal / 365).unwrap_or(i16::MAX);
+        let mut remaining = ordinal % 365;
+        let mut month = 1;
+        while remaining >= i32::from(DAYS_PER_MONTH[usize::from(month)]) {
+            remaining -= i32::from(DAYS_PER_MONTH[usize::from(month)]);
+            month += 1;
+        }
+
+        Vic3Date::from_ymdh(year, month, remaining as u8 + 1, self.hour())
+    }
 }
 
 impl PdsDate for Vic3Date {
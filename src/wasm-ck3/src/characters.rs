@@ -0,0 +1,237 @@
+use crate::{
+    models::{Ck3Character, Ck3TitleTier},
+    SaveFileImpl,
+};
+use jomini::common::Date;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3HouseRef {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3TitleRef {
+    pub id: u32,
+    pub key: String,
+    pub name: String,
+    pub tier: Ck3TitleTier,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3CharacterSummary {
+    pub id: u32,
+    pub name: String,
+    pub birth: Option<Date>,
+    pub death: Option<Date>,
+    pub age: Option<i32>,
+    pub female: bool,
+    pub house: Option<Ck3HouseRef>,
+
+    /// The first title of the character's domain, which the game treats as
+    /// the primary title
+    pub primary_title: Option<Ck3TitleRef>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3Holdings {
+    pub character: Ck3CharacterSummary,
+
+    /// Held titles ordered from highest to lowest tier
+    pub titles: Vec<Ck3TitleRef>,
+}
+
+/// The house to explore. Defaults to the player's house
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FamilyTreePayload {
+    #[serde(default)]
+    pub house: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3FamilyMember {
+    pub character: Ck3CharacterSummary,
+    pub father: Option<u32>,
+    pub mother: Option<u32>,
+    pub spouses: Vec<u32>,
+    pub children: Vec<u32>,
+    pub is_head: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3FamilyTree {
+    pub house: Ck3HouseRef,
+
+    /// Members of the house followed by their spouses from other houses,
+    /// ordered by birth
+    pub members: Vec<Ck3FamilyMember>,
+}
+
+impl SaveFileImpl {
+    pub(crate) fn player_id(&self) -> Option<u32> {
+        self.gamestate
+            .played_character
+            .as_ref()
+            .map(|x| x.character)
+    }
+
+    pub(crate) fn house_ref(&self, id: u32) -> Option<Ck3HouseRef> {
+        let house = self.gamestate.house(id)?;
+        let dynasty = house
+            .dynasty
+            .and_then(|x| self.gamestate.dynasties.dynasties.get(&x));
+        let name = house
+            .name
+            .as_ref()
+            .or(house.key.as_ref())
+            .or_else(|| dynasty.and_then(|x| x.name.as_ref().or(x.key.as_ref())))
+            .map(|x| {
+                let localized = self.game.as_ref().and_then(|g| g.localize_dynasty(x));
+                String::from(localized.unwrap_or(x))
+            })
+            .unwrap_or_else(|| id.to_string());
+        Some(Ck3HouseRef { id, name })
+    }
+
+    pub(crate) fn title_ref(&self, id: u32) -> Option<Ck3TitleRef> {
+        let title = self.gamestate.title(id)?;
+        Some(Ck3TitleRef {
+            id,
+            key: title.key.clone(),
+            name: title
+                .name
+                .clone()
+                .or_else(|| {
+                    let game = self.game.as_ref()?;
+                    game.localize_title(&title.key).map(String::from)
+                })
+                .unwrap_or_else(|| title.key.clone()),
+            tier: title.tier(),
+        })
+    }
+
+    fn held_titles(&self, character: &Ck3Character) -> Vec<Ck3TitleRef> {
+        let mut result: Vec<_> = character
+            .landed_data
+            .iter()
+            .flat_map(|x| x.domain.iter())
+            .filter_map(|x| self.title_ref(*x))
+            .collect();
+        result.sort_by(|a, b| b.tier.cmp(&a.tier).then_with(|| a.key.cmp(&b.key)));
+        result
+    }
+
+    fn character_summary(&self, id: u32) -> Option<Ck3CharacterSummary> {
+        let character = self.gamestate.character(id)?;
+        let death = character.dead_data.as_ref().map(|x| x.date);
+        let age = character
+            .birth
+            .map(|x| x.days_until(&death.unwrap_or(self.gamestate.date)) / 365);
+
+        Some(Ck3CharacterSummary {
+            id,
+            name: character.first_name.clone(),
+            birth: character.birth,
+            death,
+            age,
+            female: character.female,
+            house: character.dynasty_house.and_then(|x| self.house_ref(x)),
+            primary_title: character
+                .landed_data
+                .as_ref()
+                .and_then(|x| x.domain.first())
+                .and_then(|x| self.title_ref(*x)),
+        })
+    }
+
+    /// The character the player is currently playing
+    pub fn get_player_character(&self) -> Option<Ck3CharacterSummary> {
+        self.character_summary(self.player_id()?)
+    }
+
+    /// Titles held by the player's character
+    pub fn get_player_holdings(&self) -> Option<Ck3Holdings> {
+        let id = self.player_id()?;
+        let character = self.gamestate.character(id)?;
+        Some(Ck3Holdings {
+            character: self.character_summary(id)?,
+            titles: self.held_titles(character),
+        })
+    }
+
+    /// Members of a house along with their parents, spouses, and children
+    pub fn get_family_tree(&self, payload: FamilyTreePayload) -> Option<Ck3FamilyTree> {
+        let house_id = match payload.house {
+            Some(x) => x,
+            None => self.gamestate.character(self.player_id()?)?.dynasty_house?,
+        };
+
+        let house = self.house_ref(house_id)?;
+        let head = self.gamestate.house(house_id).and_then(|x| x.head_of_house);
+
+        let members: HashSet<u32> = self
+            .gamestate
+            .characters()
+            .filter(|(_, x)| x.dynasty_house == Some(house_id))
+            .map(|(id, _)| id)
+            .collect();
+
+        // Spouses from other houses are included so that the tree shows both
+        // parents of a house member's children
+        let spouses: HashSet<u32> = members
+            .iter()
+            .filter_map(|x| self.gamestate.character(*x))
+            .filter_map(|x| x.family_data.as_ref())
+            .flat_map(|x| x.spouse.iter().chain(x.primary_spouse.iter()))
+            .copied()
+            .filter(|x| !members.contains(x))
+            .collect();
+
+        let mut result: Vec<_> = members
+            .iter()
+            .chain(spouses.iter())
+            .filter_map(|id| {
+                let character = self.gamestate.character(*id)?;
+                let family = character.family_data.clone().unwrap_or_default();
+                let mut spouses: Vec<_> = family.primary_spouse.into_iter().collect();
+                for spouse in family.spouse {
+                    if !spouses.contains(&spouse) {
+                        spouses.push(spouse);
+                    }
+                }
+
+                Some(Ck3FamilyMember {
+                    character: self.character_summary(*id)?,
+                    father: family.real_father,
+                    mother: family.mother,
+                    spouses,
+                    children: family.child,
+                    is_head: head == Some(*id),
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            let a_member = members.contains(&a.character.id);
+            let b_member = members.contains(&b.character.id);
+            b_member
+                .cmp(&a_member)
+                .then_with(|| a.character.birth.cmp(&b.character.birth))
+                .then_with(|| a.character.id.cmp(&b.character.id))
+        });
+
+        Some(Ck3FamilyTree {
+            house,
+            members: result,
+        })
+    }
+}
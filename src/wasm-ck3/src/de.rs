@@ -0,0 +1,107 @@
+use jomini::common::Date;
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::fmt;
+
+/// A title history entry's value is either the id of the new holder or an
+/// object describing the change, where a missing holder means the title was
+/// destroyed
+struct HolderChange(Option<u32>);
+
+impl<'de> Deserialize<'de> for HolderChange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HolderChangeVisitor;
+
+        impl<'de> Visitor<'de> for HolderChangeVisitor {
+            type Value = HolderChange;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a character id or a history event")
+            }
+
+            fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(HolderChange(u32::try_from(v).ok()))
+            }
+
+            fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(HolderChange(Some(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(HolderChange(u32::try_from(v).ok()))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(HolderChange(u32::try_from(v).ok()))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut holder = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "holder" {
+                        holder = Some(map.next_value::<u32>()?);
+                    } else {
+                        map.next_value::<de::IgnoredAny>()?;
+                    }
+                }
+
+                Ok(HolderChange(holder))
+            }
+        }
+
+        deserializer.deserialize_any(HolderChangeVisitor)
+    }
+}
+
+/// Deserializes a title's history into holder changes in the order they
+/// appear in the save
+pub(crate) fn deserialize_holder_history<'de, D>(
+    deserializer: D,
+) -> Result<Vec<(Date, Option<u32>)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct HistoryVisitor;
+
+    impl<'de> Visitor<'de> for HistoryVisitor {
+        type Value = Vec<(Date, Option<u32>)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a title history")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut result = Vec::new();
+            while let Some((date, change)) = map.next_entry::<Date, HolderChange>()? {
+                result.push((date, change.0));
+            }
+
+            Ok(result)
+        }
+    }
+
+    deserializer.deserialize_map(HistoryVisitor)
+}
@@ -0,0 +1,378 @@
+use crate::{
+    characters::{Ck3HouseRef, Ck3TitleRef},
+    models::{Ck3TitleTier, Ck3WarSide},
+    titles::Ck3TitleHolder,
+    SaveFileImpl,
+};
+use jomini::common::{Date, PdsDate};
+use ledger::{LocalizedLedger, LocalizedTag, OptionalLedgerPoint};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleHistoryPayload {
+    /// Tiers of titles to include. Defaults to duchies, kingdoms, and empires
+    #[serde(default)]
+    pub tiers: Vec<Ck3TitleTier>,
+}
+
+/// The character whose dynasty is charted. Defaults to the player
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealmSizePayload {
+    #[serde(default)]
+    pub character: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3TitleReign {
+    pub holder: Option<Ck3TitleHolder>,
+    pub house: Option<Ck3HouseRef>,
+    pub start: Date,
+
+    /// When the holder lost the title. `None` if still held
+    pub end: Option<Date>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3TitleHistory {
+    pub title: Ck3TitleRef,
+    pub reigns: Vec<Ck3TitleReign>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WarSide {
+    Attacker,
+    Defender,
+}
+
+/// A limitation of the save that the frontend should label the data with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Ck3Caveat {
+    /// The save only records the realm structure as it stands now, so past
+    /// years pair today's de facto lieges with the holders of that year
+    PresentDayLieges,
+
+    /// Some holders have been pruned from the save and can't be attributed
+    /// to a house
+    PrunedHolders,
+
+    /// The gamestate drops wars once they conclude, so neither past wars nor
+    /// their outcomes are available
+    ConcludedWarsDropped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3RealmSize {
+    #[serde(flatten)]
+    pub ledger: LocalizedLedger<String>,
+    pub caveats: Vec<Ck3Caveat>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3PlayerWar {
+    pub id: u32,
+    pub name: Option<String>,
+    pub start: Option<Date>,
+    pub side: WarSide,
+
+    /// If the player is the primary participant of their side
+    pub is_leader: bool,
+    pub casus_belli: Option<String>,
+    pub targeted_titles: Vec<Ck3TitleRef>,
+    pub attackers: Vec<Ck3TitleHolder>,
+    pub defenders: Vec<Ck3TitleHolder>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3PlayerWars {
+    pub active: Vec<Ck3PlayerWar>,
+    pub caveats: Vec<Ck3Caveat>,
+}
+
+impl SaveFileImpl {
+    /// Who held each title of the requested tiers and when, ordered by tier
+    /// and then title key
+    pub fn get_title_history(&self, payload: TitleHistoryPayload) -> Vec<Ck3TitleHistory> {
+        let tiers = if payload.tiers.is_empty() {
+            vec![
+                Ck3TitleTier::Duchy,
+                Ck3TitleTier::Kingdom,
+                Ck3TitleTier::Empire,
+            ]
+        } else {
+            payload.tiers
+        };
+
+        let mut result: Vec<_> = self
+            .gamestate
+            .landed_titles
+            .landed_titles
+            .iter()
+            .filter(|(_, title)| tiers.contains(&title.tier()))
+            .filter(|(_, title)| !title.history.is_empty())
+            .filter_map(|(id, title)| {
+                let mut reigns: Vec<Ck3TitleReign> = Vec::new();
+                for (date, holder) in &title.history {
+                    if let Some(last) = reigns.last_mut().filter(|x| x.end.is_none()) {
+                        last.end = Some(*date);
+                    }
+
+                    if let Some(holder) = holder {
+                        let house = self
+                            .gamestate
+                            .character(*holder)
+                            .and_then(|x| x.dynasty_house)
+                            .and_then(|x| self.house_ref(x));
+
+                        reigns.push(Ck3TitleReign {
+                            holder: self.title_holder(*holder),
+                            house,
+                            start: *date,
+                            end: None,
+                        });
+                    }
+                }
+
+                Some(Ck3TitleHistory {
+                    title: self.title_ref(*id)?,
+                    reigns,
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.title
+                .tier
+                .cmp(&a.title.tier)
+                .then_with(|| a.title.key.cmp(&b.title.key))
+        });
+        result
+    }
+
+    /// Counties within the realm of each house of a character's dynasty at
+    /// the start of every year. A county is attributed to the house holding
+    /// the highest title above it, following de facto lieges up to the top of
+    /// the realm. The approximations this entails are listed in the
+    /// response's caveats.
+    pub fn get_realm_size(&self, payload: RealmSizePayload) -> Ck3RealmSize {
+        let empty = Ck3RealmSize {
+            ledger: LocalizedLedger {
+                points: Vec::new(),
+                localization: Vec::new(),
+                dropped: Vec::new(),
+            },
+            caveats: Vec::new(),
+        };
+
+        let Some(dynasty) = payload
+            .character
+            .or_else(|| self.player_id())
+            .and_then(|x| self.gamestate.character(x))
+            .and_then(|x| x.dynasty_house)
+            .and_then(|x| self.gamestate.house(x))
+            .and_then(|x| x.dynasty)
+        else {
+            return empty;
+        };
+
+        let houses: HashSet<u32> = self
+            .gamestate
+            .dynasties
+            .dynasty_house
+            .iter()
+            .filter(|(_, house)| house.dynasty == Some(dynasty))
+            .map(|(id, _)| *id)
+            .collect();
+
+        let counties: Vec<_> = self
+            .gamestate
+            .landed_titles
+            .landed_titles
+            .iter()
+            .filter(|(_, x)| x.tier() == Ck3TitleTier::County)
+            .filter(|(_, x)| !x.history.is_empty())
+            .collect();
+
+        let Some(first_year) = counties
+            .iter()
+            .filter_map(|(_, x)| x.history.first())
+            .map(|(date, _)| date.year())
+            .min()
+        else {
+            return empty;
+        };
+
+        let last_year = self.gamestate.date.year();
+        let starts: Vec<_> = (first_year..=last_year)
+            .filter_map(|x| Date::from_ymd_opt(x, 1, 1))
+            .collect();
+
+        // The dynasty house holding each title at the start of every year
+        let mut character_houses: HashMap<u32, Option<u32>> = HashMap::new();
+        let mut title_houses: HashMap<u32, Vec<Option<u32>>> = HashMap::new();
+        let mut counts: HashMap<(u32, i16), i32> = HashMap::new();
+        let mut pruned = false;
+        for (id, _) in counties {
+            let chain = self.liege_chain(*id);
+            for title in &chain {
+                if title_houses.contains_key(title) {
+                    continue;
+                }
+
+                let history = self
+                    .gamestate
+                    .title(*title)
+                    .map_or(&[][..], |x| x.history.as_slice());
+                let mut changes = history.iter().peekable();
+                let mut holder = None;
+                let yearly = starts
+                    .iter()
+                    .map(|start| {
+                        while let Some((_, x)) = changes.next_if(|(date, _)| date <= start) {
+                            holder = *x;
+                        }
+
+                        let house = holder.and_then(|x| {
+                            *character_houses.entry(x).or_insert_with(|| {
+                                let character = self.gamestate.character(x);
+                                pruned |= character.is_none();
+                                character.and_then(|c| c.dynasty_house)
+                            })
+                        });
+                        house.filter(|x| houses.contains(x))
+                    })
+                    .collect();
+                title_houses.insert(*title, yearly);
+            }
+
+            for (i, start) in starts.iter().enumerate() {
+                let house = chain.iter().rev().find_map(|x| title_houses[x][i]);
+                if let Some(house) = house {
+                    *counts.entry((house, start.year())).or_default() += 1;
+                }
+            }
+        }
+
+        let charted: HashSet<u32> = counts.keys().map(|(house, _)| *house).collect();
+        let mut points = Vec::new();
+        for house in &charted {
+            for start in &starts {
+                let year = start.year();
+                let value = counts.get(&(*house, year)).copied();
+                points.push(OptionalLedgerPoint {
+                    tag: house.to_string(),
+                    year: year as u16,
+                    value,
+                });
+            }
+        }
+
+        points.sort_unstable_by(|a, b| a.year.cmp(&b.year).then_with(|| a.tag.cmp(&b.tag)));
+
+        let mut localization: Vec<_> = charted
+            .iter()
+            .filter_map(|x| self.house_ref(*x))
+            .map(|x| LocalizedTag {
+                tag: x.id.to_string(),
+                name: x.name,
+            })
+            .collect();
+        localization.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+
+        let mut caveats = vec![Ck3Caveat::PresentDayLieges];
+        if pruned {
+            caveats.push(Ck3Caveat::PrunedHolders);
+        }
+
+        Ck3RealmSize {
+            ledger: LocalizedLedger {
+                points,
+                localization,
+                dropped: Vec::new(),
+            },
+            caveats,
+        }
+    }
+
+    /// Ongoing wars the player's character is participating in. Concluded
+    /// wars are declined with a caveat as the save doesn't retain them.
+    pub fn get_player_active_wars(&self) -> Ck3PlayerWars {
+        let caveats = vec![Ck3Caveat::ConcludedWarsDropped];
+        let Some(player) = self.player_id() else {
+            return Ck3PlayerWars {
+                active: Vec::new(),
+                caveats,
+            };
+        };
+
+        let participants = |side: &Ck3WarSide| -> Vec<Ck3TitleHolder> {
+            side.participants
+                .iter()
+                .filter_map(|x| self.title_holder(x.character))
+                .collect()
+        };
+
+        let mut result: Vec<_> = self
+            .gamestate
+            .wars
+            .active_wars
+            .iter()
+            .filter_map(|(id, war)| {
+                let (side, participating) = if war
+                    .attacker
+                    .participants
+                    .iter()
+                    .any(|x| x.character == player)
+                {
+                    (WarSide::Attacker, &war.attacker)
+                } else if war
+                    .defender
+                    .participants
+                    .iter()
+                    .any(|x| x.character == player)
+                {
+                    (WarSide::Defender, &war.defender)
+                } else {
+                    return None;
+                };
+
+                let is_leader = participating
+                    .participants
+                    .first()
+                    .map_or(false, |x| x.character == player);
+
+                Some(Ck3PlayerWar {
+                    id: *id,
+                    name: war.name.clone(),
+                    start: war.start_date,
+                    side,
+                    is_leader,
+                    casus_belli: war.casus_belli.as_ref().map(|x| x.kind.clone()),
+                    targeted_titles: war
+                        .casus_belli
+                        .iter()
+                        .flat_map(|x| x.targeted_titles.iter())
+                        .filter_map(|x| self.title_ref(*x))
+                        .collect(),
+                    attackers: participants(&war.attacker),
+                    defenders: participants(&war.defender),
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.id.cmp(&b.id)));
+        Ck3PlayerWars {
+            active: result,
+            caveats,
+        }
+    }
+}
@@ -1,8 +1,18 @@
+use ck3game::Game;
 use ck3save::{models::HeaderOwned, Ck3Error, Ck3File, Encoding, FailedResolveStrategy};
+use models::Ck3Gamestate;
+use pdxgame::GameData;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+mod characters;
+mod de;
+mod history;
+mod map;
+mod models;
+mod titles;
 mod tokens;
+pub use pdxmap::map_fill_borders;
 pub use tokens::*;
 
 #[derive(Debug, Serialize)]
@@ -10,11 +20,22 @@ pub use tokens::*;
 pub struct Ck3Metadata {
     version: String,
     is_meltable: bool,
+
+    /// Set when the gamestate could not be deserialized, in which case only
+    /// the header is available and the analytics are empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gamestate_error: Option<String>,
 }
 
 pub struct SaveFileImpl {
     header: HeaderOwned,
+    gamestate: Ck3Gamestate,
+    gamestate_error: Option<String>,
     encoding: Encoding,
+
+    /// Localization from the game data, when the caller has supplied it.
+    /// Without it, names fall back to the keys found in the save.
+    game: Option<GameData<Game<'static>>>,
 }
 
 pub fn to_json_value<T: serde::ser::Serialize + ?Sized>(value: &T) -> JsValue {
@@ -30,6 +51,51 @@ impl SaveFile {
     pub fn metadata(&self) -> JsValue {
         to_json_value(&self.0.metadata())
     }
+
+    pub fn set_game_data(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.0.set_game_data(data).map_err(js_err)
+    }
+
+    pub fn get_player_character(&self) -> JsValue {
+        to_json_value(&self.0.get_player_character())
+    }
+
+    pub fn get_player_holdings(&self) -> JsValue {
+        to_json_value(&self.0.get_player_holdings())
+    }
+
+    pub fn get_title_tree(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_title_tree(payload)))
+    }
+
+    pub fn get_family_tree(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_family_tree(payload)))
+    }
+
+    pub fn get_title_history(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_title_history(payload)))
+    }
+
+    pub fn get_realm_size(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_realm_size(payload)))
+    }
+
+    pub fn get_player_active_wars(&self) -> JsValue {
+        to_json_value(&self.0.get_player_active_wars())
+    }
+
+    pub fn map_colors(&self, payload: JsValue) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(self.0.map_colors(payload))
+    }
+}
+
+fn js_err(err: impl std::error::Error) -> JsValue {
+    JsValue::from(err.to_string())
 }
 
 impl SaveFileImpl {
@@ -37,12 +103,19 @@ impl SaveFileImpl {
         Ck3Metadata {
             version: self.header.meta_data.version.clone(),
             is_meltable: self.is_meltable(),
+            gamestate_error: self.gamestate_error.clone(),
         }
     }
 
     fn is_meltable(&self) -> bool {
         matches!(self.encoding, Encoding::Binary | Encoding::BinaryZip)
     }
+
+    /// Loads the zstd compressed game data written by compile_assets
+    pub fn set_game_data(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.game = Some(GameData::from_zstd(data, Game::from_flatbuffer)?);
+        Ok(())
+    }
 }
 
 fn _parse_save(data: &[u8]) -> Result<SaveFile, Ck3Error> {
@@ -50,9 +123,17 @@ fn _parse_save(data: &[u8]) -> Result<SaveFile, Ck3Error> {
     let mut zip_sink = Vec::new();
     let meta = file.parse(&mut zip_sink)?;
     let header = meta.deserializer(tokens::get_tokens()).deserialize()?;
+    let gamestate = meta.deserializer(tokens::get_tokens()).deserialize();
+    let (gamestate, gamestate_error) = match gamestate {
+        Ok(gamestate) => (gamestate, None),
+        Err(e) => (Ck3Gamestate::default(), Some(e.to_string())),
+    };
     Ok(SaveFile(SaveFileImpl {
         header,
+        gamestate,
+        gamestate_error,
         encoding: file.encoding(),
+        game: None,
     }))
 }
 
@@ -0,0 +1,46 @@
+use crate::{models::Ck3TitleTier, SaveFileImpl};
+use pdxmap::{tag_color, ProvinceColors, UNOWNED_COLOR};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapPayload {
+    /// The number of province colors to allocate, one more than the largest
+    /// province id in the raster
+    pub provinces: usize,
+
+    /// Province ids keyed by barony title key (the `baronies.json` written
+    /// by compile_assets)
+    pub baronies: HashMap<String, u16>,
+}
+
+impl SaveFileImpl {
+    /// The independent title at the top of the title's realm
+    fn top_liege(&self, id: u32) -> u32 {
+        self.liege_chain(id).last().copied().unwrap_or(id)
+    }
+
+    /// Political map colors where each held barony is painted by the color
+    /// of the top title of the realm it belongs to
+    pub fn map_colors(&self, payload: MapPayload) -> Vec<u8> {
+        let mut colors = ProvinceColors::new(payload.provinces, UNOWNED_COLOR);
+        for (id, title) in &self.gamestate.landed_titles.landed_titles {
+            if title.tier() != Ck3TitleTier::Barony || title.holder.is_none() {
+                continue;
+            }
+
+            let Some(province) = payload.baronies.get(&title.key) else {
+                continue;
+            };
+
+            let realm = self.gamestate.title(self.top_liege(*id)).unwrap_or(title);
+            let color = realm
+                .color
+                .map_or_else(|| tag_color(&realm.key), |[r, g, b]| [r, g, b, 255]);
+            colors.set(usize::from(*province), color);
+        }
+
+        colors.into_data()
+    }
+}
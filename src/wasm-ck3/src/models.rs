@@ -0,0 +1,267 @@
+use crate::de::deserialize_holder_history;
+use jomini::common::Date;
+use pdxde::{deserialize_database, deserialize_rgb};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The subset of the gamestate that is modeled
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3Gamestate {
+    pub date: Date,
+
+    #[serde(default)]
+    pub played_character: Option<Ck3PlayedCharacter>,
+
+    /// Characters that are alive
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub living: HashMap<u32, Ck3Character>,
+
+    /// Dead characters the game keeps around (eg: for family trees)
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub dead_unprunable: HashMap<u32, Ck3Character>,
+
+    #[serde(default)]
+    pub dynasties: Ck3Dynasties,
+
+    #[serde(default)]
+    pub landed_titles: Ck3LandedTitles,
+
+    #[serde(default)]
+    pub wars: Ck3Wars,
+}
+
+impl Default for Ck3Gamestate {
+    fn default() -> Self {
+        Self {
+            date: Date::from_ymd(1, 1, 1),
+            played_character: None,
+            living: HashMap::new(),
+            dead_unprunable: HashMap::new(),
+            dynasties: Ck3Dynasties::default(),
+            landed_titles: Ck3LandedTitles::default(),
+            wars: Ck3Wars::default(),
+        }
+    }
+}
+
+impl Ck3Gamestate {
+    pub fn character(&self, id: u32) -> Option<&Ck3Character> {
+        self.living
+            .get(&id)
+            .or_else(|| self.dead_unprunable.get(&id))
+    }
+
+    pub fn characters(&self) -> impl Iterator<Item = (u32, &Ck3Character)> {
+        self.living
+            .iter()
+            .chain(self.dead_unprunable.iter())
+            .map(|(id, x)| (*id, x))
+    }
+
+    pub fn title(&self, id: u32) -> Option<&Ck3Title> {
+        self.landed_titles.landed_titles.get(&id)
+    }
+
+    pub fn house(&self, id: u32) -> Option<&Ck3House> {
+        self.dynasties.dynasty_house.get(&id)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3PlayedCharacter {
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3Character {
+    #[serde(default)]
+    pub first_name: String,
+
+    #[serde(default)]
+    pub birth: Option<Date>,
+
+    #[serde(default)]
+    pub female: bool,
+
+    #[serde(default)]
+    pub dynasty_house: Option<u32>,
+
+    #[serde(default)]
+    pub family_data: Option<Ck3FamilyData>,
+
+    #[serde(default)]
+    pub landed_data: Option<Ck3LandedData>,
+
+    #[serde(default)]
+    pub dead_data: Option<Ck3DeadData>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3FamilyData {
+    #[serde(default)]
+    pub real_father: Option<u32>,
+
+    #[serde(default)]
+    pub mother: Option<u32>,
+
+    #[serde(default)]
+    pub primary_spouse: Option<u32>,
+
+    #[serde(default)]
+    pub spouse: Vec<u32>,
+
+    #[serde(default)]
+    pub child: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3LandedData {
+    /// Titles held by the character, with the primary title first
+    #[serde(default)]
+    pub domain: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3DeadData {
+    pub date: Date,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3Dynasties {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub dynasty_house: HashMap<u32, Ck3House>,
+
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub dynasties: HashMap<u32, Ck3Dynasty>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3House {
+    /// Localization key of the house name
+    #[serde(default)]
+    pub key: Option<String>,
+
+    /// Custom name given to the house
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub dynasty: Option<u32>,
+
+    #[serde(default)]
+    pub head_of_house: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3Dynasty {
+    #[serde(default)]
+    pub key: Option<String>,
+
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3LandedTitles {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub landed_titles: HashMap<u32, Ck3Title>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3Title {
+    /// The title key (eg: `k_france`)
+    #[serde(default)]
+    pub key: String,
+
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Id of the character holding the title
+    #[serde(default)]
+    pub holder: Option<u32>,
+
+    #[serde(default)]
+    pub de_facto_liege: Option<u32>,
+
+    #[serde(default)]
+    pub de_jure_liege: Option<u32>,
+
+    /// The map color of the title, when the save records one
+    #[serde(default, deserialize_with = "deserialize_rgb")]
+    pub color: Option<[u8; 3]>,
+
+    /// Holder changes in chronological order. A holder of `None` means the
+    /// title was destroyed
+    #[serde(default, deserialize_with = "deserialize_holder_history")]
+    pub history: Vec<(Date, Option<u32>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Ck3TitleTier {
+    Barony,
+    County,
+    Duchy,
+    Kingdom,
+    Empire,
+    Other,
+}
+
+impl Ck3Title {
+    pub fn tier(&self) -> Ck3TitleTier {
+        match self.key.split_once('_').map(|(prefix, _)| prefix) {
+            Some("b") => Ck3TitleTier::Barony,
+            Some("c") => Ck3TitleTier::County,
+            Some("d") => Ck3TitleTier::Duchy,
+            Some("k") => Ck3TitleTier::Kingdom,
+            Some("e") => Ck3TitleTier::Empire,
+            _ => Ck3TitleTier::Other,
+        }
+    }
+}
+
+/// The gamestate only retains wars that are in progress
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3Wars {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub active_wars: HashMap<u32, Ck3War>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3War {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub start_date: Option<Date>,
+
+    #[serde(default)]
+    pub attacker: Ck3WarSide,
+
+    #[serde(default)]
+    pub defender: Ck3WarSide,
+
+    #[serde(default)]
+    pub casus_belli: Option<Ck3CasusBelli>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ck3WarSide {
+    #[serde(default)]
+    pub participants: Vec<Ck3WarParticipant>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3WarParticipant {
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ck3CasusBelli {
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Titles being fought over
+    #[serde(default)]
+    pub targeted_titles: Vec<u32>,
+}
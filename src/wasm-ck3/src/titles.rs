@@ -0,0 +1,159 @@
+use crate::{characters::Ck3TitleRef, models::Ck3Title, SaveFileImpl};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TitleHierarchy {
+    DeJure,
+    DeFacto,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleTreePayload {
+    pub hierarchy: TitleHierarchy,
+
+    /// Key of the title at the root of the tree. Defaults to the player's
+    /// primary title, and when there is no player, every top level title.
+    #[serde(default)]
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3TitleHolder {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ck3TitleNode {
+    pub title: Ck3TitleRef,
+    pub holder: Option<Ck3TitleHolder>,
+    pub held_by_player: bool,
+
+    /// If the title's de facto liege differs from its de jure liege
+    pub diverges: bool,
+    pub children: Vec<Ck3TitleNode>,
+}
+
+impl TitleHierarchy {
+    fn liege(self, title: &Ck3Title) -> Option<u32> {
+        match self {
+            TitleHierarchy::DeJure => title.de_jure_liege,
+            TitleHierarchy::DeFacto => title.de_facto_liege,
+        }
+    }
+}
+
+impl SaveFileImpl {
+    pub(crate) fn title_holder(&self, id: u32) -> Option<Ck3TitleHolder> {
+        let character = self.gamestate.character(id)?;
+        Some(Ck3TitleHolder {
+            id,
+            name: character.first_name.clone(),
+        })
+    }
+
+    /// The title followed by its de facto lieges up to the independent title
+    /// at the top of the realm. Baronies without a de facto liege continue
+    /// from their de jure county.
+    pub(crate) fn liege_chain(&self, id: u32) -> Vec<u32> {
+        let mut chain = vec![id];
+        let mut liege = self
+            .gamestate
+            .title(id)
+            .and_then(|x| x.de_facto_liege.or(x.de_jure_liege));
+
+        // Titles can't be nested deeper than the five tiers, so this also
+        // guards against a malformed cycle
+        for _ in 0..5 {
+            let Some(next) = liege else {
+                break;
+            };
+
+            chain.push(next);
+            liege = self.gamestate.title(next).and_then(|x| x.de_facto_liege);
+        }
+
+        chain
+    }
+
+    /// The titles beneath a root title, following either de jure or de facto
+    /// liege relationships
+    pub fn get_title_tree(&self, payload: TitleTreePayload) -> Vec<Ck3TitleNode> {
+        let titles = &self.gamestate.landed_titles.landed_titles;
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (id, title) in titles {
+            if let Some(liege) = payload.hierarchy.liege(title) {
+                children.entry(liege).or_default().push(*id);
+            }
+        }
+
+        let roots: Vec<u32> = if let Some(key) = payload.root.as_ref() {
+            titles
+                .iter()
+                .find(|(_, title)| &title.key == key)
+                .map(|(id, _)| *id)
+                .into_iter()
+                .collect()
+        } else if let Some(primary) = self.get_player_character().and_then(|x| x.primary_title) {
+            vec![primary.id]
+        } else {
+            titles
+                .iter()
+                .filter(|(_, title)| payload.hierarchy.liege(title).is_none())
+                .filter(|(id, _)| children.contains_key(id))
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        let player = self.player_id();
+        let mut visited = HashSet::new();
+        let mut result: Vec<_> = roots
+            .into_iter()
+            .filter_map(|x| self.title_node(x, &children, player, &mut visited))
+            .collect();
+        result.sort_by(|a, b| a.title.key.cmp(&b.title.key));
+        result
+    }
+
+    fn title_node(
+        &self,
+        id: u32,
+        children: &HashMap<u32, Vec<u32>>,
+        player: Option<u32>,
+        visited: &mut HashSet<u32>,
+    ) -> Option<Ck3TitleNode> {
+        // Guard against a malformed save where lieges form a cycle
+        if !visited.insert(id) {
+            return None;
+        }
+
+        let title = self.gamestate.title(id)?;
+        let holder = title.holder.and_then(|x| self.title_holder(x));
+
+        let mut nodes: Vec<_> = children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|x| self.title_node(*x, children, player, visited))
+            .collect();
+        nodes.sort_by(|a, b| {
+            b.title
+                .tier
+                .cmp(&a.title.tier)
+                .then_with(|| a.title.key.cmp(&b.title.key))
+        });
+
+        Some(Ck3TitleNode {
+            title: self.title_ref(id)?,
+            holder,
+            held_by_player: player.is_some() && title.holder == player,
+            diverges: title.de_facto_liege != title.de_jure_liege,
+            children: nodes,
+        })
+    }
+}
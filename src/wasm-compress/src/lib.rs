@@ -1,3 +1,4 @@
+use serde::Serialize;
 use std::io::{Cursor, Read, Write};
 use wasm_bindgen::prelude::*;
 use zip_next as zip;
@@ -121,3 +122,19 @@ pub fn recompressed_meta(data: &[u8]) -> String {
         String::from(r#"{"content_type":"application/zstd"}"#)
     }
 }
+
+/// Detects the save from its start and end, see [savedetect::SaveParts]
+#[wasm_bindgen]
+pub fn detect_save(head: &[u8], tail: &[u8], len: usize) -> Result<JsValue, JsValue> {
+    let parts = savedetect::SaveParts {
+        head,
+        tail,
+        len: len as u64,
+    };
+    let detected =
+        savedetect::detect_parts(parts).map_err(|e| JsValue::from_str(e.to_string().as_str()))?;
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    detected
+        .serialize(&serializer)
+        .map_err(|e| JsValue::from_str(e.to_string().as_str()))
+}
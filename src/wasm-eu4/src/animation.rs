@@ -0,0 +1,177 @@
+use crate::{
+    render::{crop_and_scale, scaled_height, BorderFill, MapCrop, MapRaster, RenderError},
+    SaveFileImpl, TimelapseIter,
+};
+use eu4save::{Eu4Date, PdsDate};
+use serde::{Deserialize, Serialize};
+
+// Overlay colors for the date stamp
+const OVERLAY_BACKGROUND: [u8; 4] = [15, 23, 42, 255];
+const OVERLAY_TEXT: [u8; 4] = [255, 255, 255, 255];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationOptions {
+    pub format: AnimationFormat,
+    pub fill: BorderFill,
+    pub crop: MapCrop,
+
+    /// Width of each frame. The height is derived from the crop region's
+    /// aspect ratio. Defaults to the crop region's width.
+    pub width: Option<u32>,
+
+    /// Frames per second. Gifs have a delay resolution of hundredths of a
+    /// second and most viewers ignore delays shorter than two hundredths, so
+    /// gifs are capped at 50 fps.
+    pub frame_rate: u16,
+
+    /// Stamp the date of each frame in the bottom left corner
+    pub date_overlay: bool,
+}
+
+impl SaveFileImpl {
+    /// Consumes the timelapse and encodes each interval as a frame of an
+    /// animated image
+    pub fn render_timelapse(
+        &self,
+        mut cursor: TimelapseIter,
+        raster: &MapRaster,
+        options: &AnimationOptions,
+    ) -> Result<Vec<u8>, RenderError> {
+        let crop = self.crop_region(&options.crop)?;
+        let width = options.width.unwrap_or(crop.width);
+        let height = scaled_height(crop, width)?;
+        let frames = cursor.remaining() as u32;
+        if frames == 0 {
+            return Err(RenderError::EmptyTimelapse);
+        }
+
+        let result_len = self.province_id_to_color_index.len() * 4;
+        let mut next_frame = || -> Result<Option<Vec<u8>>, RenderError> {
+            let Some(item) = cursor.next() else {
+                return Ok(None);
+            };
+            let date = item.map_date().date;
+            let data = item.data();
+            let (primary, rest) = data.split_at(result_len);
+            let secondary = &rest[..result_len];
+            let canvas = raster.paint(primary, secondary, options.fill);
+            let mut frame = crop_and_scale(&canvas, crop, width)?;
+            if options.date_overlay {
+                stamp_date(&mut frame, width, date);
+            }
+            Ok(Some(frame))
+        };
+
+        let frame_rate = options.frame_rate.max(1);
+        let mut out = Vec::new();
+        match options.format {
+            AnimationFormat::Gif => {
+                let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height))
+                else {
+                    return Err(RenderError::GifTooLarge { width, height });
+                };
+
+                let delay = (100 / frame_rate).max(2);
+                let mut encoder = gif::Encoder::new(&mut out, gif_width, gif_height, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                while let Some(mut frame) = next_frame()? {
+                    let mut frame =
+                        gif::Frame::from_rgba_speed(gif_width, gif_height, &mut frame, 10);
+                    frame.delay = delay;
+                    encoder.write_frame(&frame)?;
+                }
+            }
+            AnimationFormat::Apng => {
+                let mut encoder = png::Encoder::new(&mut out, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames, 0)?;
+                encoder.set_frame_delay(1, frame_rate)?;
+                let mut writer = encoder.write_header()?;
+                while let Some(frame) = next_frame()? {
+                    writer.write_image_data(&frame)?;
+                }
+                writer.finish()?;
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// 3x5 bitmap glyphs for the characters in an ISO 8601 date. Each row is
+/// three bits wide with the most significant bit on the left.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    let rows = match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Draws the date onto the bottom left of an RGBA frame that is `width`
+/// pixels wide. The text is scaled with the frame so that it remains
+/// legible on large exports.
+fn stamp_date(frame: &mut [u8], width: u32, date: Eu4Date) {
+    let width = width as usize;
+    let height = frame.len() / 4 / width;
+    let text = date.iso_8601().to_string();
+    let scale = (width / 320).max(2);
+    let padding = scale * 2;
+    let text_width = text.len() * 4 * scale - scale;
+    let text_height = 5 * scale;
+    let box_width = text_width + padding * 2;
+    let box_height = text_height + padding * 2;
+    if box_width > width || box_height > height {
+        return;
+    }
+
+    let mut fill = |x: usize, y: usize, color: [u8; 4]| {
+        let offset = (y * width + x) * 4;
+        frame[offset..offset + 4].copy_from_slice(&color);
+    };
+
+    let top = height - box_height;
+    for y in top..height {
+        for x in 0..box_width {
+            fill(x, y, OVERLAY_BACKGROUND);
+        }
+    }
+
+    for (i, rows) in text.chars().filter_map(glyph).enumerate() {
+        let left = padding + i * 4 * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+
+                let x = left + col * scale;
+                let y = top + padding + row * scale;
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        fill(x + dx, y + dy, OVERLAY_TEXT);
+                    }
+                }
+            }
+        }
+    }
+}
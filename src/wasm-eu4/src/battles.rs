@@ -0,0 +1,326 @@
+use crate::{LocalizedTag, SaveFileImpl};
+use eu4game::game::LandUnitKind;
+use eu4save::{
+    models::{Battle, BattleSide, WarEvent},
+    CountryTag, Eu4Date,
+};
+use schemas::eu4::Terrain;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// A side whose regiments are at least this share of cavalry or artillery is
+// classified by that arm
+const COMPOSITION_THRESHOLD: f64 = 0.4;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommanderRecord {
+    pub name: String,
+    pub country: LocalizedTag,
+
+    /// Fire / shock / maneuver / siege as of the commander's last battle
+    pub stats: String,
+    pub battles: u32,
+    pub wins: u32,
+    pub losses: i64,
+    pub enemy_losses: i64,
+
+    /// Enemy losses inflicted per loss suffered. Absent for commanders that
+    /// haven't lost a man.
+    pub casualty_ratio: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommanderRankings {
+    pub generals: Vec<CommanderRecord>,
+    pub admirals: Vec<CommanderRecord>,
+}
+
+/// How the participants of land battles fared when grouped by a trait of
+/// the battle. Outcomes aren't adjusted for combat width as saves record
+/// neither the width of a battle nor the technology of its participants at
+/// the time.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleOutcome {
+    pub key: &'static str,
+    pub battles: u32,
+    pub wins: u32,
+    pub losses: i64,
+    pub enemy_losses: i64,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleOutcomes {
+    /// Each side of a battle counted by the dominant arm of its regiments
+    pub compositions: Vec<BattleOutcome>,
+
+    /// Counted from the defender's perspective as terrain favors defenders
+    pub terrains: Vec<BattleOutcome>,
+}
+
+impl SaveFileImpl {
+    fn all_battles(&self) -> impl Iterator<Item = (Eu4Date, &Battle)> + '_ {
+        let game = &self.query.save().game;
+        let previous = game.previous_wars.iter().map(|x| &x.history);
+        let active = game.active_wars.iter().map(|x| &x.history);
+        previous
+            .chain(active)
+            .flat_map(|x| x.events.iter())
+            .filter_map(|(date, event)| match event {
+                WarEvent::Battle(b) => Some((*date, b)),
+                _ => None,
+            })
+    }
+
+    /// Generals and admirals of every war in the save ranked by battles won,
+    /// then casualty ratio, then enemy losses inflicted
+    pub fn get_commander_rankings(&self) -> CommanderRankings {
+        #[derive(Default)]
+        struct Tally {
+            last_battle: Option<Eu4Date>,
+            battles: u32,
+            wins: u32,
+            losses: i64,
+            enemy_losses: i64,
+        }
+
+        let mut generals: HashMap<(&str, CountryTag), Tally> = HashMap::new();
+        let mut admirals: HashMap<(&str, CountryTag), Tally> = HashMap::new();
+        for (date, battle) in self.all_battles() {
+            let sides = [
+                (&battle.attacker, &battle.defender, battle.attacker_won),
+                (&battle.defender, &battle.attacker, !battle.attacker_won),
+            ];
+
+            for (side, enemy, won) in sides {
+                let Some(commander) = side.commander.as_deref() else {
+                    continue;
+                };
+
+                let tallies = if is_naval(battle) {
+                    &mut admirals
+                } else {
+                    &mut generals
+                };
+
+                let tally = tallies.entry((commander, side.country)).or_default();
+                tally.last_battle = tally.last_battle.max(Some(date));
+                tally.battles += 1;
+                tally.wins += u32::from(won);
+                tally.losses += i64::from(side.losses);
+                tally.enemy_losses += i64::from(enemy.losses);
+            }
+        }
+
+        let rank = |tallies: HashMap<(&str, CountryTag), Tally>| {
+            let mut result: Vec<_> = tallies
+                .into_iter()
+                .map(|((name, tag), tally)| CommanderRecord {
+                    name: String::from(name),
+                    country: self.localize_tag(tag),
+                    stats: self.get_commander_stats(
+                        tally.last_battle.unwrap_or(self.query.save().meta.date),
+                        std::iter::once(&tag),
+                        name,
+                    ),
+                    battles: tally.battles,
+                    wins: tally.wins,
+                    losses: tally.losses,
+                    enemy_losses: tally.enemy_losses,
+                    casualty_ratio: casualty_ratio(tally.losses, tally.enemy_losses),
+                })
+                .collect();
+
+            rank_commanders(&mut result);
+            result
+        };
+
+        CommanderRankings {
+            generals: rank(generals),
+            admirals: rank(admirals),
+        }
+    }
+
+    /// Land battle outcomes across every war in the save broken down by
+    /// army composition and the terrain of the battle
+    pub fn get_battle_outcomes(&self) -> BattleOutcomes {
+        let mut compositions: HashMap<&'static str, BattleOutcome> = HashMap::new();
+        let mut terrains: HashMap<&'static str, BattleOutcome> = HashMap::new();
+
+        for (_, battle) in self.all_battles().filter(|(_, b)| !is_naval(b)) {
+            let (attacker, defender) = (&battle.attacker, &battle.defender);
+            let attacker_won = battle.attacker_won;
+            for (side, enemy, won) in [
+                (attacker, defender, attacker_won),
+                (defender, attacker, !attacker_won),
+            ] {
+                let key = composition(side).map_or("unknown", |x| match x {
+                    LandUnitKind::Infantry => "infantry",
+                    LandUnitKind::Cavalry => "cavalry",
+                    LandUnitKind::Artillery => "artillery",
+                });
+                record_outcome(&mut compositions, key, won, side, enemy);
+            }
+
+            let terrain = self
+                .game
+                .get_province(&battle.location)
+                .map_or("unknown", |x| terrain_key(x.terrain));
+            record_outcome(&mut terrains, terrain, !attacker_won, defender, attacker);
+        }
+
+        let sorted = |outcomes: HashMap<&'static str, BattleOutcome>| {
+            let mut result: Vec<_> = outcomes.into_values().collect();
+            result.sort_unstable_by(|a, b| b.battles.cmp(&a.battles).then(a.key.cmp(b.key)));
+            result
+        };
+
+        BattleOutcomes {
+            compositions: sorted(compositions),
+            terrains: sorted(terrains),
+        }
+    }
+}
+
+/// Enemy losses inflicted per loss suffered, absent without losses
+fn casualty_ratio(losses: i64, enemy_losses: i64) -> Option<f64> {
+    (losses > 0).then(|| enemy_losses as f64 / losses as f64)
+}
+
+fn rank_commanders(records: &mut [CommanderRecord]) {
+    // Commanders that haven't lost a man outrank any ratio
+    let ratio = |x: &CommanderRecord| x.casualty_ratio.unwrap_or(f64::INFINITY);
+    records.sort_unstable_by(|a, b| {
+        b.wins
+            .cmp(&a.wins)
+            .then_with(|| ratio(b).total_cmp(&ratio(a)))
+            .then_with(|| b.enemy_losses.cmp(&a.enemy_losses))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.country.tag.cmp(&b.country.tag))
+    });
+}
+
+fn is_naval(battle: &Battle) -> bool {
+    let ships = |x: &BattleSide| x.heavy_ship + x.light_ship + x.galley + x.transport;
+    ships(&battle.attacker) + ships(&battle.defender) > 0
+}
+
+fn record_outcome(
+    outcomes: &mut HashMap<&'static str, BattleOutcome>,
+    key: &'static str,
+    won: bool,
+    side: &BattleSide,
+    enemy: &BattleSide,
+) {
+    let outcome = outcomes.entry(key).or_insert_with(|| BattleOutcome {
+        key,
+        ..Default::default()
+    });
+    outcome.battles += 1;
+    outcome.wins += u32::from(won);
+    outcome.losses += i64::from(side.losses);
+    outcome.enemy_losses += i64::from(enemy.losses);
+}
+
+/// Classifies a side of a land battle by the arm that makes up a notable
+/// share of its regiments, otherwise infantry
+fn composition(side: &BattleSide) -> Option<LandUnitKind> {
+    let total = side.infantry + side.cavalry + side.artillery;
+    if total == 0 {
+        return None;
+    }
+
+    let share = |x: u32| f64::from(x) / f64::from(total);
+    if share(side.cavalry) >= COMPOSITION_THRESHOLD {
+        Some(LandUnitKind::Cavalry)
+    } else if share(side.artillery) >= COMPOSITION_THRESHOLD {
+        Some(LandUnitKind::Artillery)
+    } else {
+        Some(LandUnitKind::Infantry)
+    }
+}
+
+fn terrain_key(terrain: Terrain) -> &'static str {
+    match terrain {
+        Terrain::Grasslands => "grasslands",
+        Terrain::Hills => "hills",
+        Terrain::Mountains => "mountains",
+        Terrain::Desert => "desert",
+        Terrain::Farmlands => "farmlands",
+        Terrain::Forest => "forest",
+        Terrain::Ocean => "ocean",
+        Terrain::InlandOcean => "inlandOcean",
+        Terrain::CoastalDesert => "coastalDesert",
+        Terrain::Savannah => "savannah",
+        Terrain::Drylands => "drylands",
+        Terrain::Highlands => "highlands",
+        Terrain::Coastline => "coastline",
+        Terrain::Glacier => "glacier",
+        Terrain::ImpassableMountain => "impassableMountain",
+        Terrain::Marsh => "marsh",
+        Terrain::Steppe => "steppe",
+        Terrain::Wasteland => "wasteland",
+        Terrain::Jungle => "jungle",
+        Terrain::Woods => "woods",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, tag: &str, wins: u32, losses: i64, enemy_losses: i64) -> CommanderRecord {
+        CommanderRecord {
+            name: String::from(name),
+            country: LocalizedTag {
+                tag: tag.parse().unwrap(),
+                name: String::from(tag),
+            },
+            stats: String::new(),
+            battles: wins,
+            wins,
+            losses,
+            enemy_losses,
+            casualty_ratio: casualty_ratio(losses, enemy_losses),
+        }
+    }
+
+    #[test]
+    fn test_casualty_ratio() {
+        assert_eq!(casualty_ratio(0, 5000), None);
+        assert_eq!(casualty_ratio(0, 0), None);
+        assert_eq!(casualty_ratio(2000, 5000), Some(2.5));
+        assert_eq!(casualty_ratio(1000, 0), Some(0.0));
+    }
+
+    #[test]
+    fn test_rank_commanders() {
+        let mut records = vec![
+            record("Turenne", "FRA", 3, 1000, 3000),
+            record("Conde", "FRA", 5, 4000, 4000),
+            record("Vauban", "FRA", 3, 0, 2000),
+            record("Marlborough", "ENG", 3, 1000, 6000),
+            record("Eugene", "HAB", 3, 2000, 6000),
+            record("Eugene", "SAV", 3, 2000, 6000),
+        ];
+
+        rank_commanders(&mut records);
+        let ranked: Vec<_> = records
+            .iter()
+            .map(|x| (x.name.as_str(), x.country.name.as_str()))
+            .collect();
+        let expected = vec![
+            ("Conde", "FRA"),
+            ("Vauban", "FRA"),
+            ("Marlborough", "ENG"),
+            ("Eugene", "HAB"),
+            ("Eugene", "SAV"),
+            ("Turenne", "FRA"),
+        ];
+        assert_eq!(ranked, expected);
+    }
+}
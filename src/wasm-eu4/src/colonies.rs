@@ -0,0 +1,273 @@
+use crate::{
+    history::months_between,
+    tag_filter::{TagFilterPayload, TagFilterPayloadRaw},
+    LocalizedObj, LocalizedTag, SaveFileImpl,
+};
+use eu4save::{CountryTag, Eu4Date, PdsDate, ProvinceId};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Settlers at which a colony becomes a city
+const COMPLETED_COLONY_SIZE: f32 = 1000.0;
+
+const COLONIAL_SUBJECT_TYPES: &[&str] = &[
+    "colony",
+    "crown_colony",
+    "private_enterprise",
+    "self_governing_colony",
+];
+
+/// A province that was settled by a country after the start of the campaign
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Colonization {
+    pub date: Eu4Date,
+    pub province: ProvinceId,
+    pub colonizer: CountryTag,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveColony {
+    pub province: ProvinceId,
+    pub name: String,
+    pub owner: LocalizedTag,
+    pub founded: Option<Eu4Date>,
+    pub settlers: f32,
+
+    /// Share of the settlers needed for the colony to become a city
+    pub progress: f32,
+
+    /// Settlers gained per year since the colony was founded
+    pub yearly_growth: Option<f32>,
+
+    /// Completion date if the colony continues to grow at its average rate
+    pub estimated_completion: Option<Eu4Date>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColonialNation {
+    pub country: LocalizedTag,
+    pub overlord: LocalizedTag,
+    pub subject_type: String,
+    pub since: Option<Eu4Date>,
+
+    /// The region of the colonial nation's capital
+    pub region: Option<LocalizedObj>,
+    pub provinces: usize,
+    pub development: f32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColonizedProvince {
+    pub date: Eu4Date,
+    pub province: ProvinceId,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryColonization {
+    pub country: LocalizedTag,
+    pub provinces: Vec<ColonizedProvince>,
+}
+
+impl SaveFileImpl {
+    /// Provinces that were unowned at the start of the campaign and the date
+    /// and country that first settled them, ordered by date. Colonizers are
+    /// resolved to their current tag.
+    pub(crate) fn colonizations(&self) -> Vec<Colonization> {
+        let mut result: Vec<_> = self
+            .owner_history
+            .changed_provinces()
+            .filter_map(|x| self.province_colonization(x))
+            .collect();
+        result.sort_by_key(|x| (x.date, x.province.as_u16()));
+        result
+    }
+
+    /// The colonization of a single province (see `colonizations`)
+    pub(crate) fn province_colonization(&self, province: ProvinceId) -> Option<Colonization> {
+        if !self.initially_unowned(province) {
+            return None;
+        }
+
+        let (date, tag) = self.owner_history.changes(province).first()?;
+        Some(self.colonization(province, *tag, *date))
+    }
+
+    fn initially_unowned(&self, province: ProvinceId) -> bool {
+        let index = usize::from(province.as_u16());
+        matches!(self.province_owners.initial.get(index), Some(None))
+    }
+
+    fn colonization(&self, province: ProvinceId, tag: CountryTag, date: Eu4Date) -> Colonization {
+        let colonizer = self
+            .tag_resolver
+            .resolve(tag, date)
+            .map(|x| x.current)
+            .unwrap_or(tag);
+
+        Colonization {
+            date,
+            province,
+            colonizer,
+        }
+    }
+
+    /// Colonies owned by the countries matching the filter that have yet to
+    /// become cities, ordered by closest to completion
+    pub fn get_active_colonies(&self, payload: TagFilterPayloadRaw) -> Vec<ActiveColony> {
+        let payload = TagFilterPayload::from(payload);
+        let tags = self.matching_tags(&payload);
+        let save_date = self.query.save().meta.date;
+        let founded: HashMap<_, _> = self
+            .colonizations()
+            .into_iter()
+            .map(|x| (x.province, x.date))
+            .collect();
+
+        let mut result: Vec<_> = self
+            .query
+            .save()
+            .game
+            .provinces
+            .iter()
+            .filter_map(|(id, prov)| {
+                let settlers = prov.colony_size?;
+                let owner = prov.owner.filter(|x| tags.contains(x))?;
+                let founded = founded.get(id).copied();
+
+                let months = founded.map(|x| months_between(x, save_date));
+                let yearly_growth = months
+                    .filter(|x| *x > 0)
+                    .map(|x| settlers / x as f32 * 12.0);
+
+                let estimated_completion = yearly_growth.filter(|x| *x > 0.0).map(|growth| {
+                    let remaining = (COMPLETED_COLONY_SIZE - settlers).max(0.0);
+                    save_date.add_days((remaining / growth * 365.0).ceil() as i32)
+                });
+
+                Some(ActiveColony {
+                    province: *id,
+                    name: prov.name.clone(),
+                    owner: self.localize_tag(owner),
+                    founded,
+                    settlers,
+                    progress: (settlers / COMPLETED_COLONY_SIZE).min(1.0),
+                    yearly_growth,
+                    estimated_completion,
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.progress
+                .total_cmp(&a.progress)
+                .then_with(|| a.province.as_u16().cmp(&b.province.as_u16()))
+        });
+        result
+    }
+
+    /// Colonial nations in the save with their overlord, ordered by
+    /// development
+    pub fn get_colonial_nations(&self) -> Vec<ColonialNation> {
+        let save = self.query.save();
+        let mut result: Vec<_> = save
+            .game
+            .diplomacy
+            .dependencies
+            .iter()
+            .filter(|x| COLONIAL_SUBJECT_TYPES.contains(&x.subject_type.as_str()))
+            .filter_map(|dependency| {
+                let country = self.query.country(&dependency.second)?;
+                let (provinces, development) = save
+                    .game
+                    .provinces
+                    .values()
+                    .filter(|x| x.owner == Some(dependency.second))
+                    .fold((0, 0.0), |(count, dev), x| {
+                        let prov_dev = x.base_tax + x.base_production + x.base_manpower;
+                        (count + 1, dev + prov_dev)
+                    });
+
+                Some(ColonialNation {
+                    country: self.localize_tag(dependency.second),
+                    overlord: self.localize_tag(dependency.first),
+                    subject_type: dependency.subject_type.clone(),
+                    since: dependency.start_date,
+                    region: self.province_region(&country.capital),
+                    provinces,
+                    development,
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.development
+                .total_cmp(&a.development)
+                .then_with(|| a.country.tag.cmp(&b.country.tag))
+        });
+        result
+    }
+
+    /// Provinces colonized by each country matching the filter, ordered by
+    /// the number of provinces colonized
+    pub fn get_colonization_timeline(
+        &self,
+        payload: TagFilterPayloadRaw,
+    ) -> Vec<CountryColonization> {
+        let payload = TagFilterPayload::from(payload);
+        let tags = self.matching_tags(&payload);
+
+        let mut countries: HashMap<CountryTag, Vec<ColonizedProvince>> = HashMap::new();
+        for colonization in self.colonizations() {
+            if !tags.contains(&colonization.colonizer) {
+                continue;
+            }
+
+            countries
+                .entry(colonization.colonizer)
+                .or_default()
+                .push(ColonizedProvince {
+                    date: colonization.date,
+                    province: colonization.province,
+                    name: self.province_name(&colonization.province),
+                });
+        }
+
+        let mut result: Vec<_> = countries
+            .into_iter()
+            .map(|(tag, provinces)| CountryColonization {
+                country: self.localize_tag(tag),
+                provinces,
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.provinces
+                .len()
+                .cmp(&a.provinces.len())
+                .then_with(|| a.country.tag.cmp(&b.country.tag))
+        });
+        result
+    }
+
+    fn province_region(&self, id: &ProvinceId) -> Option<LocalizedObj> {
+        let area = self.game.province_area(id)?;
+        let region = self
+            .game
+            .regions()
+            .find_map(|(region, mut areas)| areas.any(|x| x == area).then_some(region))?;
+
+        Some(LocalizedObj {
+            id: String::from(region),
+            name: self
+                .game
+                .localize(region)
+                .map(String::from)
+                .unwrap_or_else(|| String::from(region)),
+        })
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{
+    hex_color,
+    history::{value_at, History},
+    tag_filter::{TagFilterPayload, TagFilterPayloadRaw},
+    LocalizedObj, LocalizedTag, SaveFileImpl,
+};
+use eu4save::{
+    models::{CountryEvent, ProvinceEvent},
+    CountryTag, Eu4Date, PdsDate, ProvinceId,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const CATHOLIC: &str = "catholic";
+const REFORMED_RELIGIONS: [&str; 2] = ["protestant", "reformed"];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConversionMethod {
+    /// Converted to the owner's state religion
+    Missionary,
+
+    /// Converted on the same day as the province changed culture
+    CultureShift,
+
+    /// Converted to a religion other than the owner's (eg: reformation
+    /// spread, events)
+    Event,
+
+    /// The owner's state religion at the time isn't recorded in its history
+    Unknown,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvinceConversion {
+    pub date: Eu4Date,
+    pub province: ProvinceId,
+    pub name: String,
+    pub from: Option<String>,
+    pub to: String,
+    pub owner: Option<LocalizedTag>,
+    pub method: ConversionMethod,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReformationKind {
+    Protestant,
+    Reformed,
+
+    /// Catholic reconversions of Protestant and Reformed provinces
+    CounterReformation,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReformationCenter {
+    pub province: ProvinceId,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReformationSpread {
+    pub kind: ReformationKind,
+    pub first_date: Option<Eu4Date>,
+
+    /// Provinces converted on the day the movement first appeared. Saves
+    /// don't record centers of reformation, so these approximate them.
+    pub centers: Vec<ReformationCenter>,
+
+    /// Provinces that adhere to the religion (or were reconverted by the
+    /// counter-reformation) as of the start of each year
+    pub provinces: Vec<(i16, usize)>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReligiousUnityPoint {
+    pub year: i16,
+    pub unity: f32,
+}
+
+/// Share of a country's development that follows the state religion at the
+/// start of each year. Development is taken from the save as history doesn't
+/// record development changes.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryReligiousUnity {
+    pub country: LocalizedTag,
+    pub points: Vec<ReligiousUnityPoint>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReligionDevelopmentPoint {
+    pub year: i16,
+    pub religion: String,
+    pub development: f32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReligionLegend {
+    #[serde(flatten)]
+    pub religion: LocalizedObj,
+    pub color: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReligionAnalytics {
+    pub conversions: Vec<ProvinceConversion>,
+    pub reformations: Vec<ReformationSpread>,
+    pub unity: Vec<CountryReligiousUnity>,
+    pub development: Vec<ReligionDevelopmentPoint>,
+    pub religions: Vec<ReligionLegend>,
+}
+
+/// Classifies a conversion given the state religion of the province's
+/// owner at the time, which is absent for unowned provinces and `None` when
+/// the owner's history doesn't record it
+fn conversion_method(
+    culture_shift: bool,
+    owner_religion: Option<Option<&str>>,
+    religion: &str,
+) -> ConversionMethod {
+    if culture_shift {
+        return ConversionMethod::CultureShift;
+    }
+
+    match owner_religion {
+        Some(None) => ConversionMethod::Unknown,
+        Some(Some(x)) if x == religion => ConversionMethod::Missionary,
+        _ => ConversionMethod::Event,
+    }
+}
+
+impl SaveFileImpl {
+    /// Religious history since the start of the campaign: how provinces
+    /// converted, the spread of the reformation, religious unity of the
+    /// countries matching the filter, and world development by religion.
+    pub fn get_religion_analytics(&self, payload: TagFilterPayloadRaw) -> ReligionAnalytics {
+        let payload = TagFilterPayload::from(payload);
+        let tags = self.matching_tags(&payload);
+        let save = self.query.save();
+        let start_date = save.game.start_date;
+        let save_date = save.meta.date;
+        let epoch = Eu4Date::from_ymd(1, 1, 1);
+
+        // The current state religion isn't used as a fallback when history
+        // lacks the initial religion as it may have changed since
+        let state_religions: HashMap<CountryTag, History<&str>> = save
+            .game
+            .countries
+            .iter()
+            .map(|(tag, country)| {
+                let initial = country.history.religion.as_deref();
+                let changes =
+                    country
+                        .history
+                        .events
+                        .iter()
+                        .filter_map(|(date, event)| match event {
+                            CountryEvent::Religion(x) => Some((*date, x.as_str())),
+                            _ => None,
+                        });
+                let history = initial.map(|x| (epoch, x)).into_iter().chain(changes);
+                (*tag, history.collect())
+            })
+            .collect();
+
+        let owner_at = |id: &ProvinceId, date: Eu4Date| self.owner_history.owner_at(*id, date);
+
+        let state_religion_at = |owner: CountryTag, date: Eu4Date| {
+            let stored = self
+                .tag_resolver
+                .resolve(owner, date)
+                .map(|x| x.stored)
+                .unwrap_or(owner);
+            state_religions.get(&stored).and_then(|x| value_at(x, date))
+        };
+
+        let current_tag = |owner: CountryTag, date: Eu4Date| {
+            self.tag_resolver
+                .resolve(owner, date)
+                .map(|x| x.current)
+                .unwrap_or(owner)
+        };
+
+        let mut religions: HashMap<ProvinceId, History<&str>> = HashMap::new();
+        let mut conversions = Vec::new();
+        for (id, prov) in &save.game.provinces {
+            let culture_changes: HashSet<_> = prov
+                .history
+                .events
+                .iter()
+                .filter_map(|(date, event)| match event {
+                    ProvinceEvent::KV((key, _)) if key == "culture" => Some(*date),
+                    _ => None,
+                })
+                .collect();
+
+            let mut history: History<&str> = prov
+                .history
+                .religion
+                .as_deref()
+                .map(|x| (epoch, x))
+                .into_iter()
+                .collect();
+
+            for (date, event) in &prov.history.events {
+                let ProvinceEvent::Religion(religion) = event else {
+                    continue;
+                };
+
+                let from = history.last().map(|(_, x)| *x);
+                history.push((*date, religion.as_str()));
+                if *date <= start_date || from == Some(religion.as_str()) {
+                    continue;
+                }
+
+                let owner = owner_at(id, *date);
+                let method = conversion_method(
+                    culture_changes.contains(date),
+                    owner.map(|x| state_religion_at(x, *date)),
+                    religion,
+                );
+
+                conversions.push(ProvinceConversion {
+                    date: *date,
+                    province: *id,
+                    name: prov.name.clone(),
+                    from: from.map(String::from),
+                    to: religion.clone(),
+                    owner: owner.map(|x| self.localize_tag(current_tag(x, *date))),
+                    method,
+                });
+            }
+
+            religions.insert(*id, history);
+        }
+        conversions.sort_by_key(|x| (x.date, x.province.as_u16()));
+
+        let mut dates: Vec<_> = (start_date.year() + 1..=save_date.year())
+            .map(|year| Eu4Date::from_ymd(year, 1, 1))
+            .collect();
+        dates.insert(0, start_date);
+        let years: Vec<_> = dates.iter().map(|x| x.year()).collect();
+
+        let counter_reformations: Vec<_> = conversions
+            .iter()
+            .filter(|x| x.to == CATHOLIC)
+            .filter(
+                |x| matches!(&x.from, Some(from) if REFORMED_RELIGIONS.contains(&from.as_str())),
+            )
+            .collect();
+
+        let mut development = Vec::new();
+        let mut adherents: HashMap<&str, Vec<(i16, usize)>> = HashMap::new();
+        let mut unity: HashMap<CountryTag, Vec<ReligiousUnityPoint>> = HashMap::new();
+        for date in dates {
+            let mut religion_dev: BTreeMap<&str, f32> = BTreeMap::new();
+            let mut religion_provinces: HashMap<&str, usize> = HashMap::new();
+            let mut country_dev: HashMap<CountryTag, (f32, f32)> = HashMap::new();
+
+            for (id, prov) in &save.game.provinces {
+                let Some(religion) = religions.get(id).and_then(|x| value_at(x, date)) else {
+                    continue;
+                };
+
+                let dev = prov.base_tax + prov.base_production + prov.base_manpower;
+                *religion_dev.entry(religion).or_default() += dev;
+                *religion_provinces.entry(religion).or_default() += 1;
+
+                let Some(owner) = owner_at(id, date) else {
+                    continue;
+                };
+
+                let tag = current_tag(owner, date);
+                if !tags.contains(&tag) {
+                    continue;
+                }
+
+                // Unity is measured against the provinces of owners whose
+                // state religion at the time is known
+                let Some(state_religion) = state_religion_at(owner, date) else {
+                    continue;
+                };
+
+                let entry = country_dev.entry(tag).or_default();
+                entry.1 += dev;
+                if state_religion == religion {
+                    entry.0 += dev;
+                }
+            }
+
+            for religion in REFORMED_RELIGIONS {
+                let count = religion_provinces
+                    .get(religion)
+                    .copied()
+                    .unwrap_or_default();
+                adherents
+                    .entry(religion)
+                    .or_default()
+                    .push((date.year(), count));
+            }
+
+            development.extend(religion_dev.into_iter().map(|(religion, development)| {
+                ReligionDevelopmentPoint {
+                    year: date.year(),
+                    religion: String::from(religion),
+                    development,
+                }
+            }));
+
+            for (tag, (matching, total)) in country_dev {
+                if total > 0.0 {
+                    unity.entry(tag).or_default().push(ReligiousUnityPoint {
+                        year: date.year(),
+                        unity: matching / total,
+                    });
+                }
+            }
+        }
+
+        let spread = |kind: ReformationKind, converted: &[&ProvinceConversion], provinces| {
+            let first_date = converted.first().map(|x| x.date);
+            let centers = converted
+                .iter()
+                .take_while(|x| Some(x.date) == first_date)
+                .map(|x| ReformationCenter {
+                    province: x.province,
+                    name: x.name.clone(),
+                })
+                .collect();
+
+            ReformationSpread {
+                kind,
+                first_date,
+                centers,
+                provinces,
+            }
+        };
+
+        let mut reformations = Vec::new();
+        for (religion, kind) in REFORMED_RELIGIONS
+            .into_iter()
+            .zip([ReformationKind::Protestant, ReformationKind::Reformed])
+        {
+            let converted: Vec<_> = conversions.iter().filter(|x| x.to == religion).collect();
+            let provinces = adherents.remove(religion).unwrap_or_default();
+            reformations.push(spread(kind, &converted, provinces));
+        }
+
+        let reconverted = years
+            .into_iter()
+            .map(|year| {
+                let count = counter_reformations
+                    .iter()
+                    .filter(|x| x.date.year() <= year)
+                    .count();
+                (year, count)
+            })
+            .collect();
+        reformations.push(spread(
+            ReformationKind::CounterReformation,
+            &counter_reformations,
+            reconverted,
+        ));
+
+        let mut unity: Vec<_> = unity
+            .into_iter()
+            .map(|(tag, points)| CountryReligiousUnity {
+                country: self.localize_tag(tag),
+                points,
+            })
+            .collect();
+        unity.sort_by(|a, b| a.country.tag.cmp(&b.country.tag));
+
+        let mut religions: Vec<_> = development
+            .iter()
+            .map(|x| x.religion.as_str())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|id| {
+                let religion = self.game.religion(id);
+                ReligionLegend {
+                    religion: LocalizedObj {
+                        id: String::from(id),
+                        name: religion
+                            .as_ref()
+                            .map(|x| String::from(x.name))
+                            .unwrap_or_else(|| String::from(id)),
+                    },
+                    color: hex_color(religion.map(|x| x.color).unwrap_or_default()),
+                }
+            })
+            .collect();
+        religions.sort_by(|a, b| a.religion.name.cmp(&b.religion.name));
+
+        ReligionAnalytics {
+            conversions,
+            reformations,
+            unity,
+            development,
+            religions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_method() {
+        let method = conversion_method(true, Some(Some("catholic")), "protestant");
+        assert_eq!(method, ConversionMethod::CultureShift);
+
+        let method = conversion_method(false, Some(Some("catholic")), "catholic");
+        assert_eq!(method, ConversionMethod::Missionary);
+
+        let method = conversion_method(false, Some(Some("catholic")), "protestant");
+        assert_eq!(method, ConversionMethod::Event);
+
+        let method = conversion_method(false, None, "protestant");
+        assert_eq!(method, ConversionMethod::Event);
+
+        let method = conversion_method(false, Some(None), "catholic");
+        assert_eq!(method, ConversionMethod::Unknown);
+    }
+}
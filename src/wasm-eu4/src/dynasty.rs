@@ -0,0 +1,334 @@
+use crate::{history::months_between, LocalizedTag, SaveFileImpl};
+use eu4save::{
+    models::{Country, CountryEvent, Monarch},
+    CountryTag, Eu4Date,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyReign {
+    pub name: String,
+    pub country: LocalizedTag,
+    pub start: Eu4Date,
+
+    /// Absent for monarchs that still reign
+    pub end: Option<Eu4Date>,
+    pub months: i32,
+    pub adm: u16,
+    pub dip: u16,
+    pub mil: u16,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DynastyRole {
+    Heir,
+    Consort,
+}
+
+/// Heirs that never took the throne and consorts of a dynasty
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyMember {
+    pub name: String,
+    pub country: LocalizedTag,
+    pub role: DynastyRole,
+    pub date: Eu4Date,
+}
+
+/// A throne that passed to the dynasty from another dynasty
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroneAccession {
+    pub date: Eu4Date,
+    pub country: LocalizedTag,
+    pub previous_dynasty: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyUnion {
+    pub senior: LocalizedTag,
+    pub junior: LocalizedTag,
+    pub start: Option<Eu4Date>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Dynasty {
+    pub name: String,
+    pub countries: Vec<LocalizedTag>,
+    pub reigns: Vec<DynastyReign>,
+    pub members: Vec<DynastyMember>,
+    pub accessions: Vec<ThroneAccession>,
+
+    /// Personal unions in the save whose senior partner is ruled by the
+    /// dynasty
+    pub unions: Vec<DynastyUnion>,
+    pub reign_years: f64,
+    pub avg_adm: f64,
+    pub avg_dip: f64,
+    pub avg_mil: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyDevelopment {
+    pub name: String,
+    pub development: f32,
+    pub countries: Vec<LocalizedTag>,
+}
+
+pub(crate) struct Reign<'a> {
+    pub tag: CountryTag,
+    pub monarch: &'a Monarch,
+    pub start: Eu4Date,
+    pub end: Option<Eu4Date>,
+}
+
+impl SaveFileImpl {
+    /// Monarchs that took the throne since the start of the campaign in
+    /// every country, in order of accession per country
+    pub(crate) fn all_reigns(&self) -> Vec<Reign> {
+        let start_date = self.query.save().game.start_date;
+        let mut result = Vec::new();
+        for (tag, country) in &self.query.save().game.countries {
+            let monarch_ids = monarch_ids(country);
+            let mut previous: Option<Reign> = None;
+            for (date, event) in &country.history.events {
+                let (CountryEvent::Monarch(x)
+                | CountryEvent::MonarchHeir(x)
+                | CountryEvent::MonarchConsort(x)) = event
+                else {
+                    continue;
+                };
+
+                if !monarch_ids.contains(&x.id.id) {
+                    continue;
+                }
+
+                if let Some(mut reign) = previous.take() {
+                    reign.end = Some(*date);
+                    if *date > start_date {
+                        result.push(reign);
+                    }
+                }
+
+                previous = Some(Reign {
+                    tag: *tag,
+                    monarch: x,
+                    start: (*date).max(start_date),
+                    end: None,
+                });
+            }
+
+            result.extend(previous);
+        }
+
+        result
+    }
+
+    /// Dynasties that reigned in any country since the start of the
+    /// campaign ordered by total years reigned
+    pub fn get_dynasties(&self) -> Vec<Dynasty> {
+        let save_date = self.query.save().meta.date;
+        let mut dynasties: HashMap<&str, Dynasty> = HashMap::new();
+        let mut last_dynasty: HashMap<CountryTag, &str> = HashMap::new();
+        let new_dynasty = |name: &str| Dynasty {
+            name: String::from(name),
+            countries: Vec::new(),
+            reigns: Vec::new(),
+            members: Vec::new(),
+            accessions: Vec::new(),
+            unions: Vec::new(),
+            reign_years: 0.0,
+            avg_adm: 0.0,
+            avg_dip: 0.0,
+            avg_mil: 0.0,
+        };
+
+        for reign in self.all_reigns() {
+            let Some(name) = reign.monarch.dynasty.as_deref() else {
+                continue;
+            };
+
+            let dynasty = dynasties.entry(name).or_insert_with(|| new_dynasty(name));
+            if let Some(previous) = last_dynasty.insert(reign.tag, name) {
+                if previous != name {
+                    dynasty.accessions.push(ThroneAccession {
+                        date: reign.start,
+                        country: self.localize_tag(reign.tag),
+                        previous_dynasty: String::from(previous),
+                    });
+                }
+            }
+
+            let end = reign.end.unwrap_or(save_date);
+            dynasty.reigns.push(DynastyReign {
+                name: reign.monarch.name.clone(),
+                country: self.localize_tag(reign.tag),
+                start: reign.start,
+                end: reign.end,
+                months: months_between(reign.start, end),
+                adm: reign.monarch.adm as u16,
+                dip: reign.monarch.dip as u16,
+                mil: reign.monarch.mil as u16,
+            });
+        }
+
+        for (tag, country) in &self.query.save().game.countries {
+            let monarch_ids = monarch_ids(country);
+            for (date, event) in &country.history.events {
+                let (monarch, role) = match event {
+                    CountryEvent::Heir(x) => (x, DynastyRole::Heir),
+                    CountryEvent::Queen(x) => (x, DynastyRole::Consort),
+                    _ => continue,
+                };
+
+                let Some(dynasty) = monarch
+                    .dynasty
+                    .as_deref()
+                    .and_then(|x| dynasties.get_mut(x))
+                else {
+                    continue;
+                };
+
+                if role == DynastyRole::Heir && monarch_ids.contains(&monarch.id.id) {
+                    continue;
+                }
+
+                dynasty.members.push(DynastyMember {
+                    name: monarch.name.clone(),
+                    country: self.localize_tag(*tag),
+                    role,
+                    date: *date,
+                });
+            }
+        }
+
+        // The dynasty of each country's current monarch
+        let current: HashMap<CountryTag, &str> = self
+            .all_reigns()
+            .into_iter()
+            .filter(|x| x.end.is_none())
+            .filter_map(|x| Some((x.tag, x.monarch.dynasty.as_deref()?)))
+            .collect();
+
+        let unions = self
+            .query
+            .save()
+            .game
+            .diplomacy
+            .dependencies
+            .iter()
+            .filter(|x| x.subject_type == "personal_union");
+        for union in unions {
+            let Some(dynasty) = current.get(&union.first).and_then(|x| dynasties.get_mut(x)) else {
+                continue;
+            };
+
+            dynasty.unions.push(DynastyUnion {
+                senior: self.localize_tag(union.first),
+                junior: self.localize_tag(union.second),
+                start: union.start_date,
+            });
+        }
+
+        let mut result: Vec<_> = dynasties
+            .into_values()
+            .map(|mut dynasty| {
+                let reigns = dynasty.reigns.len().max(1) as f64;
+                let months: i32 = dynasty.reigns.iter().map(|x| x.months).sum();
+                dynasty.reign_years = f64::from(months) / 12.0;
+                dynasty.avg_adm =
+                    dynasty.reigns.iter().map(|x| f64::from(x.adm)).sum::<f64>() / reigns;
+                dynasty.avg_dip =
+                    dynasty.reigns.iter().map(|x| f64::from(x.dip)).sum::<f64>() / reigns;
+                dynasty.avg_mil =
+                    dynasty.reigns.iter().map(|x| f64::from(x.mil)).sum::<f64>() / reigns;
+
+                let mut countries: Vec<_> = dynasty.reigns.iter().map(|x| x.country.tag).collect();
+                countries.sort_unstable();
+                countries.dedup();
+                dynasty.countries = countries
+                    .into_iter()
+                    .map(|x| self.localize_tag(x))
+                    .collect();
+                dynasty
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.reign_years
+                .total_cmp(&a.reign_years)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        result
+    }
+
+    /// Development owned by countries ruled by each dynasty the given number
+    /// of days after the start (or at the save date). Province development is
+    /// taken from the save as history doesn't record development changes.
+    pub fn get_dynasty_development(&self, days: Option<i32>) -> Vec<DynastyDevelopment> {
+        let date = days.map_or(self.query.save().meta.date, |x| {
+            self.query.save().game.start_date.add_days(x)
+        });
+        let rulers: HashMap<CountryTag, &str> = self
+            .all_reigns()
+            .into_iter()
+            .filter(|x| x.start <= date && x.end.map_or(true, |end| date < end))
+            .filter_map(|x| Some((x.tag, x.monarch.dynasty.as_deref()?)))
+            .collect();
+
+        let mut development: HashMap<CountryTag, f32> = HashMap::new();
+        for (id, prov) in &self.query.save().game.provinces {
+            let Some(owner) = self.owner_history.owner_at(*id, date) else {
+                continue;
+            };
+
+            let dev = prov.base_tax + prov.base_production + prov.base_manpower;
+            *development.entry(owner).or_default() += dev;
+        }
+
+        let mut dynasties: HashMap<&str, (f32, Vec<CountryTag>)> = HashMap::new();
+        for (tag, dynasty) in rulers {
+            let entry = dynasties.entry(dynasty).or_default();
+            entry.0 += development.get(&tag).copied().unwrap_or_default();
+            entry.1.push(tag);
+        }
+
+        let mut result: Vec<_> = dynasties
+            .into_iter()
+            .filter(|(_, (development, _))| *development > 0.0)
+            .map(|(name, (development, mut countries))| {
+                countries.sort_unstable();
+                DynastyDevelopment {
+                    name: String::from(name),
+                    development,
+                    countries: countries
+                        .into_iter()
+                        .map(|x| self.localize_tag(x))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.development
+                .total_cmp(&a.development)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        result
+    }
+}
+
+fn monarch_ids(country: &Country) -> HashSet<i32> {
+    country
+        .previous_monarchs
+        .iter()
+        .map(|x| x.id)
+        .chain(country.monarch.as_ref().map(|x| x.id))
+        .collect()
+}
@@ -0,0 +1,178 @@
+use crate::{LocalizedTag, SaveFileImpl};
+use eu4save::{models::Estate, CountryTag, Eu4Date, PdsDate};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EstatePrivilege {
+    pub name: String,
+    pub granted: Eu4Date,
+    pub days_held: i32,
+}
+
+/// An influence modifier applied to the estate and the estate's influence
+/// from modifiers once it and all earlier modifiers were applied
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EstateInfluencePoint {
+    pub date: Eu4Date,
+    pub desc: String,
+    pub value: f32,
+    pub cumulative: f32,
+}
+
+/// The history of an estate as far as the save records it. Saves only
+/// record privileges that are still granted and the influence modifiers
+/// that haven't expired, so revoked privileges and past loyalty and
+/// territory values can't be reconstructed.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EstateHistory {
+    pub kind: String,
+    pub loyalty: f32,
+    pub territory: f32,
+    pub completed_agendas: i32,
+    pub privileges: Vec<EstatePrivilege>,
+    pub influence: Vec<EstateInfluencePoint>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryEstateHistory {
+    pub country: LocalizedTag,
+
+    /// Territory not held by any estate
+    pub crown_land: f32,
+    pub estates: Vec<EstateHistory>,
+}
+
+impl SaveFileImpl {
+    /// Estate histories of the countries so that they can be compared side
+    /// by side. Unknown tags are skipped.
+    pub fn get_estate_history(&self, tags: &[CountryTag]) -> Vec<CountryEstateHistory> {
+        tags.iter()
+            .filter_map(|tag| {
+                let country = self.query.country(tag)?;
+                let mut estates: Vec<_> = country
+                    .estates
+                    .iter()
+                    .map(|x| self.estate_history(x))
+                    .collect();
+                estates.sort_by(|a, b| a.kind.cmp(&b.kind));
+
+                Some(CountryEstateHistory {
+                    country: self.localize_tag(*tag),
+                    crown_land: crown_land(estates.iter().map(|x| x.territory)),
+                    estates,
+                })
+            })
+            .collect()
+    }
+
+    fn estate_history(&self, estate: &Estate) -> EstateHistory {
+        let save_date = self.query.save().meta.date;
+        let mut privileges: Vec<_> = estate
+            .granted_privileges
+            .iter()
+            .map(|(privilege, date)| EstatePrivilege {
+                name: privilege_name(privilege, &estate._type),
+                granted: *date,
+                days_held: date.days_until(&save_date),
+            })
+            .collect();
+        privileges.sort_by(|a, b| a.granted.cmp(&b.granted).then_with(|| a.name.cmp(&b.name)));
+
+        let modifiers = estate
+            .influence_modifiers
+            .iter()
+            .map(|x| (x.date, x.desc.as_str(), x.value));
+
+        EstateHistory {
+            kind: String::from(estate._type.trim_start_matches("estate_")),
+            loyalty: estate.loyalty,
+            territory: estate.territory,
+            completed_agendas: estate.num_of_estate_agendas_completed,
+            privileges,
+            influence: influence_timeline(modifiers),
+        }
+    }
+}
+
+/// Privileges are prefixed by the estate that they belong to
+fn privilege_name(privilege: &str, estate: &str) -> String {
+    privilege
+        .trim_start_matches(estate)
+        .trim_start_matches('_')
+        .replace('_', " ")
+}
+
+fn crown_land(territories: impl Iterator<Item = f32>) -> f32 {
+    (100.0 - territories.sum::<f32>()).max(0.0)
+}
+
+/// Orders the influence modifiers by date and accumulates their values
+fn influence_timeline<'a>(
+    modifiers: impl Iterator<Item = (Eu4Date, &'a str, f32)>,
+) -> Vec<EstateInfluencePoint> {
+    let mut modifiers: Vec<_> = modifiers.collect();
+    modifiers.sort_by_key(|(date, _, _)| *date);
+
+    let mut cumulative = 0.0;
+    modifiers
+        .into_iter()
+        .map(|(date, desc, value)| {
+            cumulative += value;
+            EstateInfluencePoint {
+                date,
+                desc: desc.trim_start_matches("EST_VAL_").replace('_', " "),
+                value,
+                cumulative,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i16, month: u8, day: u8) -> Eu4Date {
+        Eu4Date::from_ymd(year, month, day)
+    }
+
+    #[test]
+    fn test_privilege_name() {
+        let name = privilege_name("estate_nobles_land_rights", "estate_nobles");
+        assert_eq!(name, "land rights");
+        assert_eq!(privilege_name("free_city", "estate_burghers"), "free city");
+    }
+
+    #[test]
+    fn test_crown_land() {
+        assert_eq!(crown_land([20.0, 30.0].into_iter()), 50.0);
+        assert_eq!(crown_land([60.0, 50.0].into_iter()), 0.0);
+        assert_eq!(crown_land(std::iter::empty()), 100.0);
+    }
+
+    #[test]
+    fn test_influence_timeline() {
+        let modifiers = [
+            (date(1460, 1, 1), "EST_VAL_OTHER_ESTATE_IN_POWER", -5.0),
+            (date(1450, 6, 1), "EST_VAL_GRANTED_PRIVILEGE", 10.0),
+            (date(1455, 3, 1), "EST_VAL_SEIZED_LAND", 2.5),
+        ];
+
+        let timeline = influence_timeline(modifiers.into_iter());
+        let points: Vec<_> = timeline
+            .iter()
+            .map(|x| (x.date, x.desc.as_str(), x.cumulative))
+            .collect();
+        let expected = vec![
+            (date(1450, 6, 1), "GRANTED PRIVILEGE", 10.0),
+            (date(1455, 3, 1), "SEIZED LAND", 12.5),
+            (date(1460, 1, 1), "OTHER ESTATE IN POWER", 7.5),
+        ];
+        assert_eq!(points, expected);
+        assert!(influence_timeline(std::iter::empty()).is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use eu4save::{query::ProvinceOwners, CountryTag, Eu4Date, PdsDate, ProvinceId};
+use std::collections::HashMap;
+
+/// Dated values ordered by date where the first entry is the value prior to
+/// any recorded change
+pub(crate) type History<T> = Vec<(Eu4Date, T)>;
+
+/// The value as of the date, including changes made on the date
+pub(crate) fn value_at<T: Copy>(history: &[(Eu4Date, T)], date: Eu4Date) -> Option<T> {
+    let index = history.partition_point(|(d, _)| *d <= date);
+    index.checked_sub(1).map(|x| history[x].1)
+}
+
+/// Whole calendar months from the start date to the end date
+pub(crate) fn months_between(start: Eu4Date, end: Eu4Date) -> i32 {
+    (i32::from(end.year()) * 12 + i32::from(end.month()))
+        - (i32::from(start.year()) * 12 + i32::from(start.month()))
+}
+
+/// The owner of every province at any date of the campaign. Changes are
+/// ordered by date as the save doesn't record them in order, and changes
+/// on the same date keep the order of the save.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OwnerHistory {
+    initial: Vec<Option<CountryTag>>,
+    changes: HashMap<ProvinceId, History<CountryTag>>,
+}
+
+impl OwnerHistory {
+    pub(crate) fn new(
+        initial: Vec<Option<CountryTag>>,
+        changes: impl IntoIterator<Item = (ProvinceId, Eu4Date, CountryTag)>,
+    ) -> Self {
+        let mut by_province: HashMap<ProvinceId, History<CountryTag>> = HashMap::new();
+        for (province, date, tag) in changes {
+            by_province.entry(province).or_default().push((date, tag));
+        }
+
+        for history in by_province.values_mut() {
+            history.sort_by_key(|(date, _)| *date);
+        }
+
+        OwnerHistory {
+            initial,
+            changes: by_province,
+        }
+    }
+
+    pub(crate) fn from_owners(owners: &ProvinceOwners) -> Self {
+        let changes = owners.changes.iter().map(|x| (x.province, x.date, x.tag));
+        Self::new(owners.initial.clone(), changes)
+    }
+
+    /// The owner at the start of the campaign
+    pub(crate) fn initial_owner(&self, province: ProvinceId) -> Option<CountryTag> {
+        let index = usize::from(province.as_u16());
+        self.initial.get(index).copied().flatten()
+    }
+
+    /// Changes of owner of the province ordered by date
+    pub(crate) fn changes(&self, province: ProvinceId) -> &[(Eu4Date, CountryTag)] {
+        self.changes.get(&province).map_or(&[], |x| x.as_slice())
+    }
+
+    /// Provinces that changed owner at least once
+    pub(crate) fn changed_provinces(&self) -> impl Iterator<Item = ProvinceId> + '_ {
+        self.changes.keys().copied()
+    }
+
+    /// The owner as of the date, including changes made on the date
+    pub(crate) fn owner_at(&self, province: ProvinceId, date: Eu4Date) -> Option<CountryTag> {
+        value_at(self.changes(province), date).or_else(|| self.initial_owner(province))
+    }
+
+    /// Changes of owner made on the date along with the preceding owner,
+    /// ordered by province
+    pub(crate) fn transfers_on(
+        &self,
+        date: Eu4Date,
+    ) -> Vec<(ProvinceId, Option<CountryTag>, CountryTag)> {
+        let mut result = Vec::new();
+        for (province, history) in &self.changes {
+            let start = history.partition_point(|(d, _)| *d < date);
+            let mut previous = start
+                .checked_sub(1)
+                .map(|x| history[x].1)
+                .or_else(|| self.initial_owner(*province));
+
+            for (_, tag) in history[start..].iter().take_while(|(d, _)| *d == date) {
+                result.push((*province, previous, *tag));
+                previous = Some(*tag);
+            }
+        }
+
+        result.sort_by_key(|(province, _, _)| province.as_u16());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> CountryTag {
+        s.parse().unwrap()
+    }
+
+    fn date(year: i16) -> Eu4Date {
+        Eu4Date::from_ymd(year, 1, 1)
+    }
+
+    fn province(id: i32) -> ProvinceId {
+        ProvinceId::new(id)
+    }
+
+    #[test]
+    fn test_months_between() {
+        let start = Eu4Date::from_ymd(1444, 11, 11);
+        assert_eq!(months_between(start, Eu4Date::from_ymd(1445, 1, 1)), 2);
+        assert_eq!(months_between(start, start), 0);
+        assert_eq!(months_between(Eu4Date::from_ymd(1445, 1, 1), start), -2);
+    }
+
+    #[test]
+    fn test_value_at() {
+        let history = vec![(date(1444), "catholic"), (date(1520), "protestant")];
+        assert_eq!(value_at(&history, date(1400)), None);
+        assert_eq!(value_at(&history, date(1444)), Some("catholic"));
+        assert_eq!(value_at(&history, date(1519)), Some("catholic"));
+        assert_eq!(value_at(&history, date(1520)), Some("protestant"));
+    }
+
+    #[test]
+    fn test_owner_at_sorts_changes() {
+        let initial = vec![None, Some(tag("FRA")), None];
+        let changes = [
+            (province(1), date(1600), tag("ENG")),
+            (province(1), date(1500), tag("BUR")),
+            (province(2), date(1550), tag("CAS")),
+        ];
+        let owners = OwnerHistory::new(initial, changes);
+
+        assert_eq!(owners.owner_at(province(1), date(1444)), Some(tag("FRA")));
+        assert_eq!(owners.owner_at(province(1), date(1500)), Some(tag("BUR")));
+        assert_eq!(owners.owner_at(province(1), date(1599)), Some(tag("BUR")));
+        assert_eq!(owners.owner_at(province(1), date(1700)), Some(tag("ENG")));
+        assert_eq!(owners.owner_at(province(2), date(1549)), None);
+        assert_eq!(owners.owner_at(province(2), date(1550)), Some(tag("CAS")));
+        assert_eq!(owners.owner_at(province(5), date(1550)), None);
+
+        let dates: Vec<_> = owners.changes(province(1)).iter().map(|x| x.0).collect();
+        assert_eq!(dates, vec![date(1500), date(1600)]);
+    }
+
+    #[test]
+    fn test_transfers_on() {
+        let initial = vec![None, Some(tag("FRA")), Some(tag("ENG")), None];
+        let changes = [
+            (province(2), date(1500), tag("FRA")),
+            (province(1), date(1500), tag("BUR")),
+            (province(1), date(1500), tag("ENG")),
+            (province(3), date(1450), tag("CAS")),
+            (province(3), date(1500), tag("POR")),
+        ];
+        let owners = OwnerHistory::new(initial, changes);
+
+        let expected = vec![
+            (province(1), Some(tag("FRA")), tag("BUR")),
+            (province(1), Some(tag("BUR")), tag("ENG")),
+            (province(2), Some(tag("ENG")), tag("FRA")),
+            (province(3), Some(tag("CAS")), tag("POR")),
+        ];
+        assert_eq!(owners.transfers_on(date(1500)), expected);
+        assert!(owners.transfers_on(date(1501)).is_empty());
+    }
+}
@@ -24,20 +24,36 @@ use std::{
     collections::{HashMap, HashSet},
     io::Cursor,
 };
-use tag_filter::{AiTagsState, TagFilterPayload, TagFilterPayloadRaw};
+use tag_filter::{TagFilterPayload, TagFilterPayloadRaw, TagSelection};
 use tarsave::TarSave;
 use wasm_bindgen::prelude::*;
 
+pub mod animation;
+mod battles;
+mod colonies;
+mod conversions;
 mod country_details;
+mod dynasty;
+mod estates;
+mod history;
 mod log;
+mod losses;
+mod mana;
 mod map;
+mod palette;
+pub mod render;
+mod spatial;
 mod tag_filter;
 mod tokens;
 mod utils;
+mod war;
 
+pub use map::{Interval, MapCursorPayload, MapCursorPayloadKind, TimelapseIter};
 pub use tokens::*;
 
 use crate::utils::to_json_value;
+use losses::LossBreakdown;
+use palette::{Palette, PalettePayload};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LocalizedObj {
@@ -45,11 +61,7 @@ pub struct LocalizedObj {
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct LocalizedTag {
-    pub tag: CountryTag,
-    pub name: String,
-}
+pub type LocalizedTag = ledger::LocalizedTag<CountryTag>;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GfxObj {
@@ -205,7 +217,7 @@ pub struct FrontendWarSide {
     pub original: CountryTag,
     pub original_name: String,
     pub members: Vec<CountryTag>,
-    pub losses: [u32; 21],
+    pub losses: LossBreakdown,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -261,7 +273,7 @@ impl FrontendBattleSide {
 pub struct WarParticipant {
     pub tag: CountryTag,
     pub name: String,
-    pub losses: [u32; 21],
+    pub losses: LossBreakdown,
     pub participation: f32,
     pub participation_percent: f64,
     pub joined: Option<String>,
@@ -271,7 +283,7 @@ pub struct WarParticipant {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SingleCountryWarCasualties {
     pub war: String,
-    pub losses: [u32; 21],
+    pub losses: LossBreakdown,
     pub participation: f32,
     pub participation_percent: f64,
     pub start: Option<String>,
@@ -311,7 +323,7 @@ pub struct LocalizedCountryExpense {
 pub struct LocalizedCasualties {
     tag: CountryTag,
     name: String,
-    losses: [u32; 21],
+    losses: LossBreakdown,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -322,18 +334,8 @@ pub enum SaveMode {
     IronmanNo,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct OptionalLedgerPoint {
-    pub tag: CountryTag,
-    pub year: u16,
-    pub value: Option<i32>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct LocalizedLedger {
-    pub points: Vec<OptionalLedgerPoint>,
-    pub localization: Vec<LocalizedTag>,
-}
+pub type OptionalLedgerPoint = ledger::OptionalLedgerPoint<CountryTag>;
+pub type LocalizedLedger = ledger::LocalizedLedger<CountryTag>;
 
 #[wasm_bindgen]
 pub struct SaveFile(SaveFileImpl);
@@ -492,6 +494,14 @@ impl SaveFile {
         to_json_value(&self.0.get_country_rulers(tag))
     }
 
+    pub fn get_dynasties(&self) -> JsValue {
+        to_json_value(&self.0.get_dynasties())
+    }
+
+    pub fn get_dynasty_development(&self, days: Option<i32>) -> JsValue {
+        to_json_value(&self.0.get_dynasty_development(days))
+    }
+
     pub fn get_country_advisors(&self, tag: &str) -> JsValue {
         to_json_value(&self.0.get_country_advisors(tag))
     }
@@ -516,6 +526,35 @@ impl SaveFile {
         self.0.get_country_estates(tag)
     }
 
+    pub fn get_estate_history(&self, tags: JsValue) -> Result<JsValue, JsValue> {
+        let tags: Vec<CountryTag> = serde_wasm_bindgen::from_value(tags).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_estate_history(&tags)))
+    }
+
+    pub fn get_mana_efficiency(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_mana_efficiency(payload)))
+    }
+
+    pub fn get_active_colonies(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_active_colonies(payload)))
+    }
+
+    pub fn get_colonial_nations(&self) -> JsValue {
+        to_json_value(&self.0.get_colonial_nations())
+    }
+
+    pub fn get_colonization_timeline(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_colonization_timeline(payload)))
+    }
+
+    pub fn get_religion_analytics(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_religion_analytics(payload)))
+    }
+
     pub fn get_nation_idea_groups(&self, payload: JsValue) -> JsValue {
         let payload = serde_wasm_bindgen::from_value(payload).unwrap();
         to_json_value(&self.0.get_nation_idea_groups(payload))
@@ -526,11 +565,14 @@ impl SaveFile {
         only_players: bool,
         incl_subjects: bool,
         paint_subject_in_overlord_hue: bool,
+        palette: JsValue,
     ) -> Vec<u8> {
+        let palette: PalettePayload = serde_wasm_bindgen::from_value(palette).unwrap_or_default();
         self.0.province_nation_owner_color(
             only_players,
             incl_subjects,
             paint_subject_in_overlord_hue,
+            &Palette::from_payload(&palette),
         )
     }
 
@@ -539,27 +581,78 @@ impl SaveFile {
         only_players: bool,
         incl_subjects: bool,
         paint_subject_in_overlord_hue: bool,
+        palette: JsValue,
     ) -> Vec<u8> {
+        let palette: PalettePayload = serde_wasm_bindgen::from_value(palette).unwrap_or_default();
         self.0.province_nation_controller_color(
             only_players,
             incl_subjects,
             paint_subject_in_overlord_hue,
+            &Palette::from_payload(&palette),
         )
     }
 
-    pub fn map_colors(&self, payload: JsValue) -> Result<Vec<u8>, JsValue> {
+    pub fn map_colors(&self, payload: JsValue) -> Result<map::MapColors, JsValue> {
         let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
         Ok(self.0.map_colors(payload))
     }
 
     pub fn map_cursor(&self, payload: JsValue) -> Result<map::TimelapseIter, JsValue> {
         let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
-        Ok(self.0.map_cursor(payload))
+        self.0.map_cursor(payload).map_err(js_err)
+    }
+
+    pub fn render_map(
+        &self,
+        payload: JsValue,
+        color_indices: &[u16],
+        terrain: Option<Vec<u8>>,
+        options: JsValue,
+    ) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        let options = serde_wasm_bindgen::from_value(options).map_err(js_err)?;
+        let raster = self
+            .0
+            .map_raster(color_indices, terrain.as_deref())
+            .map_err(js_err)?;
+        self.0
+            .render_map(payload, &raster, &options)
+            .map_err(js_err)
     }
 
-    pub fn map_quick_tip(&self, province_id: i32, payload: JsValue, days: Option<i32>) -> JsValue {
+    pub fn render_timelapse(
+        &self,
+        payload: JsValue,
+        color_indices: &[u16],
+        terrain: Option<Vec<u8>>,
+        options: JsValue,
+    ) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        let options = serde_wasm_bindgen::from_value(options).map_err(js_err)?;
+        let raster = self
+            .0
+            .map_raster(color_indices, terrain.as_deref())
+            .map_err(js_err)?;
+        let cursor = self.0.map_cursor(payload).map_err(js_err)?;
+        self.0
+            .render_timelapse(cursor, &raster, &options)
+            .map_err(js_err)
+    }
+
+    pub fn map_quick_tip(
+        &self,
+        province_id: i32,
+        payload: JsValue,
+        days: Option<i32>,
+        paint_subject_in_overlord_hue: bool,
+    ) -> JsValue {
         let payload = serde_wasm_bindgen::from_value(payload).unwrap();
-        to_json_value(&self.0.map_quick_tip(province_id, payload, days))
+        to_json_value(&self.0.map_quick_tip(
+            province_id,
+            payload,
+            days,
+            paint_subject_in_overlord_hue,
+        ))
     }
 
     pub fn initial_map_position(&self) -> JsValue {
@@ -574,6 +667,40 @@ impl SaveFile {
         to_json_value(&result)
     }
 
+    pub fn search_provinces(&self, query: &str, limit: usize) -> JsValue {
+        to_json_value(&self.0.search_provinces(query, limit))
+    }
+
+    pub fn provinces_in_bounds(&self, bounds: JsValue) -> Result<JsValue, JsValue> {
+        let bounds = serde_wasm_bindgen::from_value(bounds).map_err(js_err)?;
+        Ok(to_json_value(&self.0.provinces_in_bounds(bounds)))
+    }
+
+    pub fn province_at(
+        &self,
+        color_indices: &[u16],
+        x: u32,
+        y: u32,
+    ) -> Result<Option<u16>, JsValue> {
+        let raster = self.0.map_raster(color_indices, None).map_err(js_err)?;
+        let province = self.0.province_at(&raster, x, y);
+        Ok(province.map(|x| x.as_u16()))
+    }
+
+    pub fn province_at_color_index(&self, color_index: u16) -> Option<u16> {
+        self.0
+            .province_at_color_index(color_index)
+            .map(|x| x.as_u16())
+    }
+
+    pub fn realm_bounds(&self, tag: &str) -> JsValue {
+        let bounds = tag
+            .parse::<CountryTag>()
+            .ok()
+            .and_then(|tag| self.0.realm_bounds(&tag));
+        to_json_value(&bounds)
+    }
+
     pub fn matching_countries(&self, payload: JsValue) -> JsValue {
         let payload = serde_wasm_bindgen::from_value(payload).unwrap();
         to_json_value(&self.0.matching_countries(payload))
@@ -598,6 +725,18 @@ impl SaveFile {
         let res = self.0.get_war(&name);
         to_json_value(&res)
     }
+
+    pub fn get_war_timeline(&self, war_name: &str) -> JsValue {
+        to_json_value(&self.0.get_war_timeline(war_name))
+    }
+
+    pub fn get_commander_rankings(&self) -> JsValue {
+        to_json_value(&self.0.get_commander_rankings())
+    }
+
+    pub fn get_battle_outcomes(&self) -> JsValue {
+        to_json_value(&self.0.get_battle_outcomes())
+    }
 }
 
 // Struct created to help compiler debugging as the wasm_bindgen macro can cause opaque errors.
@@ -613,8 +752,12 @@ pub struct SaveFileImpl {
     war_participants: Vec<eu4save::query::ResolvedWarParticipants>,
     player_histories: Vec<eu4save::query::PlayerHistory>,
     province_owners: eu4save::query::ProvinceOwners,
+    owner_history: history::OwnerHistory,
     religion_lookup: eu4save::query::ReligionLookup,
     province_id_to_color_index: Vec<u16>,
+
+    // Province id of each color index where zero is the void around the map
+    color_index_to_province_id: Vec<u16>,
 }
 
 impl SaveFileImpl {
@@ -627,6 +770,7 @@ impl SaveFileImpl {
 
         self.query = Query::from_save(save);
         self.province_owners = self.query.province_owners();
+        self.owner_history = history::OwnerHistory::from_owners(&self.province_owners);
         self.nation_events = self.query.nation_events(&self.province_owners);
         self.player_histories = self.query.player_histories(&self.nation_events);
         self.tag_resolver = self.query.tag_resolver(&self.nation_events);
@@ -653,39 +797,16 @@ impl SaveFileImpl {
         warnings
     }
 
-    fn filter_stored_tags(
-        &self,
-        payload: TagFilterPayloadRaw,
-        limit: usize,
-    ) -> HashSet<CountryTag> {
+    fn filter_stored_tags(&self, payload: TagFilterPayloadRaw, limit: usize) -> TagSelection {
         let payload = TagFilterPayload::from(payload);
-        let tags = self.matching_tags(&payload);
-        if tags.len() > limit {
-            let mut new_payload = payload;
-            if self.player_histories.len() == 1 {
-                new_payload.ai = AiTagsState::Great;
-            } else {
-                new_payload.ai = AiTagsState::None;
-            };
-
-            let ntags = self.matching_tags(&new_payload);
-            let inter: HashSet<CountryTag> = tags.intersection(&ntags).cloned().collect();
-
-            if inter.is_empty() {
-                tags.into_iter()
-                    .enumerate()
-                    .filter(|(i, _x)| *i < limit)
-                    .map(|(_i, x)| x)
-                    .collect()
-            } else {
-                inter
-            }
-        } else {
-            tags
-        }
+        self.select_tags(&payload, limit)
     }
 
-    fn localize_ledger_points(&self, iter: impl Iterator<Item = LedgerPoint>) -> LocalizedLedger {
+    fn localize_ledger_points(
+        &self,
+        iter: impl Iterator<Item = LedgerPoint>,
+        dropped: Vec<LocalizedTag>,
+    ) -> LocalizedLedger {
         let sgq = SaveGameQuery::new(&self.query, &self.game);
         let mut points: Vec<_> = iter
             .map(|x| OptionalLedgerPoint {
@@ -740,47 +861,52 @@ impl SaveFileImpl {
         LocalizedLedger {
             points: result,
             localization,
+            dropped,
         }
     }
 
     pub fn get_annual_income_ledger(&self, payload: TagFilterPayloadRaw) -> LocalizedLedger {
-        let tags = self.filter_stored_tags(payload, 30);
-        let ledger = tags
+        let selection = self.filter_stored_tags(payload, 30);
+        let ledger = selection
+            .tags
             .into_iter()
             .filter_map(|x| self.nation_events.iter().find(|events| events.stored == x))
             .flat_map(|x| self.query.income_statistics_ledger(x));
 
-        self.localize_ledger_points(ledger)
+        self.localize_ledger_points(ledger, selection.dropped)
     }
 
     pub fn get_annual_nation_size_ledger(&self, payload: TagFilterPayloadRaw) -> LocalizedLedger {
-        let tags = self.filter_stored_tags(payload, 30);
-        let ledger = tags
+        let selection = self.filter_stored_tags(payload, 30);
+        let ledger = selection
+            .tags
             .into_iter()
             .filter_map(|x| self.nation_events.iter().find(|events| events.stored == x))
             .flat_map(|x| self.query.nation_size_statistics_ledger(x));
 
-        self.localize_ledger_points(ledger)
+        self.localize_ledger_points(ledger, selection.dropped)
     }
 
     pub fn get_annual_score_ledger(&self, payload: TagFilterPayloadRaw) -> LocalizedLedger {
-        let tags = self.filter_stored_tags(payload, 30);
-        let ledger = tags
+        let selection = self.filter_stored_tags(payload, 30);
+        let ledger = selection
+            .tags
             .into_iter()
             .filter_map(|x| self.nation_events.iter().find(|events| events.stored == x))
             .flat_map(|x| self.query.score_statistics_ledger(x));
 
-        self.localize_ledger_points(ledger)
+        self.localize_ledger_points(ledger, selection.dropped)
     }
 
     pub fn get_annual_inflation_ledger(&self, payload: TagFilterPayloadRaw) -> LocalizedLedger {
-        let tags = self.filter_stored_tags(payload, 30);
-        let ledger = tags
+        let selection = self.filter_stored_tags(payload, 30);
+        let ledger = selection
+            .tags
             .into_iter()
             .filter_map(|x| self.nation_events.iter().find(|events| events.stored == x))
             .flat_map(|x| self.query.inflation_statistics_ledger(x));
 
-        self.localize_ledger_points(ledger)
+        self.localize_ledger_points(ledger, selection.dropped)
     }
 
     pub fn get_achievements(&self) -> Result<JsValue, JsValue> {
@@ -1386,7 +1512,8 @@ impl SaveFileImpl {
         }
 
         let sgq = SaveGameQuery::new(&self.query, &self.game);
-        let tags = self.filter_stored_tags(payload, 30);
+        let selection = self.filter_stored_tags(payload, 30);
+        let tags = &selection.tags;
         let countries: Vec<_> = self
             .query
             .save()
@@ -1716,9 +1843,13 @@ impl SaveFileImpl {
         #[derive(Serialize, Clone, Debug)]
         struct HealthData {
             data: Vec<CountryHealth>,
+            dropped: Vec<LocalizedTag>,
         }
 
-        to_json_value(&HealthData { data: health })
+        to_json_value(&HealthData {
+            data: health,
+            dropped: selection.dropped,
+        })
     }
 
     pub fn get_province_details(&self, province_id: u16) -> Option<ProvinceDetails> {
@@ -1949,7 +2080,7 @@ impl SaveFileImpl {
     }
 
     pub fn owned_development_states(&self, payload: TagFilterPayloadRaw) -> JsValue {
-        let filter = self.filter_stored_tags(payload, 12);
+        let filter = self.filter_stored_tags(payload, 12).tags;
         let mut devs: HashMap<CountryTag, CountryDevelopment> = HashMap::new();
         let prov_area = self.game.province_area_lookup();
         let provs = self
@@ -2241,6 +2372,7 @@ impl SaveFileImpl {
         only_players: bool,
         incl_subjects: bool,
         paint_subject_in_overlord_hue: bool,
+        palette: &Palette,
         f: F,
     ) -> Vec<u8> {
         let mut desired_countries: HashSet<CountryTag> = HashSet::new();
@@ -2304,7 +2436,8 @@ impl SaveFileImpl {
         for (id, prov) in &self.query.save().game.provinces {
             let offset = usize::from(id.as_u16() * 3);
             if let Some(owner) = prov.owner.as_ref() {
-                let mut color = [106, 108, 128];
+                let [r, g, b, _] = palette.excluded;
+                let mut color = [r, g, b];
                 if desired_countries.contains(owner) {
                     if let Some(x) = f(prov) {
                         if let Some(data) = country_colors.get(x) {
@@ -2346,11 +2479,13 @@ impl SaveFileImpl {
         only_players: bool,
         incl_subjects: bool,
         paint_subject_in_overlord_hue: bool,
+        palette: &Palette,
     ) -> Vec<u8> {
         self.province_nation_color(
             only_players,
             incl_subjects,
             paint_subject_in_overlord_hue,
+            palette,
             |x| x.owner.as_ref(),
         )
     }
@@ -2360,11 +2495,13 @@ impl SaveFileImpl {
         only_players: bool,
         incl_subjects: bool,
         paint_subject_in_overlord_hue: bool,
+        palette: &Palette,
     ) -> Vec<u8> {
         self.province_nation_color(
             only_players,
             incl_subjects,
             paint_subject_in_overlord_hue,
+            palette,
             |x| x.controller.as_ref(),
         )
     }
@@ -2383,25 +2520,11 @@ impl SaveFileImpl {
             .map(|(tag, c)| LocalizedCasualties {
                 tag: *tag,
                 name: save_game_query.localize_country(tag),
-                losses: SaveFileImpl::create_losses(&c.losses.members),
+                losses: LossBreakdown::from_members(&c.losses.members),
             })
             .collect()
     }
 
-    fn create_losses(data: &[i32]) -> [u32; 21] {
-        let mut values = [0u32; 21];
-        const LOSSES_MAX: i32 = i32::MAX / 1000;
-        const LOSSES_MIN: i32 = -LOSSES_MAX;
-        for (&x, y) in data.iter().zip(values.iter_mut()) {
-            *y += match x {
-                0.. => x as u32,
-                LOSSES_MIN..=-1 => (x + 2 * LOSSES_MAX) as u32,
-                _ => x.unsigned_abs(),
-            };
-        }
-        values
-    }
-
     fn active_wars(&self, wars: &mut Vec<FrontendWar>, tags: &HashSet<CountryTag>) {
         let mut attackers = HashSet::new();
         let mut defenders = HashSet::new();
@@ -2448,18 +2571,14 @@ impl SaveFileImpl {
                 continue;
             }
 
-            let mut attacker_losses = [0u32; 21];
-            let mut defender_losses = [0u32; 21];
+            let mut attacker_losses = LossBreakdown::default();
+            let mut defender_losses = LossBreakdown::default();
             for participant in &war.participants {
-                let losses = SaveFileImpl::create_losses(&participant.losses.members);
+                let losses = LossBreakdown::from_members(&participant.losses.members);
                 if attackers.contains(&participant.tag) {
-                    for (&x, y) in losses.iter().zip(attacker_losses.iter_mut()) {
-                        *y += x;
-                    }
+                    attacker_losses += &losses;
                 } else if defenders.contains(&participant.tag) {
-                    for (&x, y) in losses.iter().zip(defender_losses.iter_mut()) {
-                        *y += x;
-                    }
+                    defender_losses += &losses;
                 }
             }
 
@@ -2559,18 +2678,14 @@ impl SaveFileImpl {
                 continue;
             }
 
-            let mut attacker_losses = [0u32; 21];
-            let mut defender_losses = [0u32; 21];
+            let mut attacker_losses = LossBreakdown::default();
+            let mut defender_losses = LossBreakdown::default();
             for participant in &war.participants {
-                let losses = SaveFileImpl::create_losses(&participant.losses.members);
+                let losses = LossBreakdown::from_members(&participant.losses.members);
                 if attackers.contains(&participant.tag) {
-                    for (&x, y) in losses.iter().zip(attacker_losses.iter_mut()) {
-                        *y += x;
-                    }
+                    attacker_losses += &losses;
                 } else if defenders.contains(&participant.tag) {
-                    for (&x, y) in losses.iter().zip(defender_losses.iter_mut()) {
-                        *y += x;
-                    }
+                    defender_losses += &losses;
                 }
             }
 
@@ -2701,7 +2816,7 @@ impl SaveFileImpl {
         for (war, participant, participation_percent, start, end) in previous_wars {
             result.push(SingleCountryWarCasualties {
                 war: war.name.clone(),
-                losses: SaveFileImpl::create_losses(&participant.losses.members),
+                losses: LossBreakdown::from_members(&participant.losses.members),
                 participation: participant.value,
                 participation_percent,
                 start: start.map(|x| x.iso_8601().to_string()),
@@ -2901,7 +3016,7 @@ impl SaveFileImpl {
                         participation: participant.value,
                         participation_percent: f64::from(participant.value)
                             / total_attacker_participation,
-                        losses: SaveFileImpl::create_losses(&participant.losses.members),
+                        losses: LossBreakdown::from_members(&participant.losses.members),
                         joined: join,
                         exited: exit,
                     });
@@ -2912,7 +3027,7 @@ impl SaveFileImpl {
                         participation: participant.value,
                         participation_percent: f64::from(participant.value)
                             / total_defender_participation,
-                        losses: SaveFileImpl::create_losses(&participant.losses.members),
+                        losses: LossBreakdown::from_members(&participant.losses.members),
                         joined: join,
                         exited: exit,
                     });
@@ -2955,7 +3070,7 @@ impl SaveFileImpl {
                         participation: participant.value,
                         participation_percent: f64::from(participant.value)
                             / total_attacker_participation,
-                        losses: SaveFileImpl::create_losses(&participant.losses.members),
+                        losses: LossBreakdown::from_members(&participant.losses.members),
                         joined: join,
                         exited: exit,
                     });
@@ -2966,7 +3081,7 @@ impl SaveFileImpl {
                         participation: participant.value,
                         participation_percent: f64::from(participant.value)
                             / total_defender_participation,
-                        losses: SaveFileImpl::create_losses(&participant.losses.members),
+                        losses: LossBreakdown::from_members(&participant.losses.members),
                         joined: join,
                         exited: exit,
                     });
@@ -3000,11 +3115,6 @@ pub fn hex_color(colors: [u8; 3]) -> String {
     }
 }
 
-#[inline]
-fn memcmp_three(a: &[u8], b: &[u8]) -> bool {
-    a[0] == b[0] && a[1] == b[1] && a[2] == b[2]
-}
-
 #[wasm_bindgen]
 pub fn map_fill_borders(
     data: &mut [u8],
@@ -3013,158 +3123,18 @@ pub fn map_fill_borders(
     secondary: &[u8],
     fill: &str,
 ) {
-    let height: usize = 2048;
-    let width: usize = 5632;
-
-    if fill == "None" {
-        if secondary.len() == primary.len() {
-            for (row_idx, row) in data.chunks_exact_mut(width * 4).enumerate() {
-                for (pixel, x) in row.chunks_exact_mut(4).enumerate() {
-                    let i = row_idx * width + pixel;
-                    let province_id = usize::from(provinces[i]);
-                    let province_offset = province_id * 3;
-                    x[3] = 255;
-                    if (pixel + row_idx) % 6 < 3 {
-                        x[0..3].copy_from_slice(&secondary[province_offset..province_offset + 3]);
-                    } else {
-                        x[0..3].copy_from_slice(&primary[province_offset..province_offset + 3]);
-                    }
-                }
-            }
-        } else {
-            for (i, x) in data.chunks_exact_mut(4).enumerate() {
-                let province_id = usize::from(provinces[i]);
-                let province_offset = province_id * 3;
-                x[0..3].copy_from_slice(&primary[province_offset..province_offset + 3]);
-                x[3] = 255;
-            }
-        }
-    } else if fill == "Provinces" {
-        for y in 0..height - 1 {
-            for x in 0..width {
-                let pixel = y * width + x;
-                let data_offset = pixel * 4;
-
-                let prov_id = usize::from(provinces[pixel]);
-                let prov_down = usize::from(provinces[pixel + width]);
-                let prov_right = usize::from(provinces[pixel + 1]);
-                let mut is_edge = false;
-
-                if prov_id != prov_down {
-                    data[data_offset + 3 + width * 4] = 1;
-                    is_edge = true;
-                }
-
-                if prov_id != prov_right {
-                    data[data_offset + 3 + 4] = 1;
-                    is_edge = true;
-                }
-
-                if is_edge || data[data_offset + 3] == 1 {
-                    data[data_offset] = 30;
-                    data[data_offset + 1] = 30;
-                    data[data_offset + 2] = 30;
-                    data[data_offset + 3] = 255;
-                } else if secondary.len() == primary.len() && (y + x) % 6 < 3 {
-                    let province_offset = prov_id * 3;
-                    data[data_offset] = secondary[province_offset];
-                    data[data_offset + 1] = secondary[province_offset + 1];
-                    data[data_offset + 2] = secondary[province_offset + 2];
-                    data[data_offset + 3] = 255;
-                } else {
-                    let province_offset = prov_id * 3;
-                    data[data_offset] = primary[province_offset];
-                    data[data_offset + 1] = primary[province_offset + 1];
-                    data[data_offset + 2] = primary[province_offset + 2];
-                    data[data_offset + 3] = 255;
-                }
-            }
-        }
-
-        for x in 0..width {
-            let pixel = (height - 1) * width + x;
-            let prov_id = usize::from(provinces[pixel]);
-            let data_offset = pixel * 4;
-            if data[data_offset + 3] == 1 {
-                data[data_offset] = 30;
-                data[data_offset + 1] = 30;
-                data[data_offset + 2] = 30;
-                data[data_offset + 3] = 255;
-            } else {
-                let province_offset = prov_id * 3;
-                data[data_offset] = primary[province_offset];
-                data[data_offset + 1] = primary[province_offset + 1];
-                data[data_offset + 2] = primary[province_offset + 2];
-                data[data_offset + 3] = 255;
-            }
-        }
-    } else if fill == "Countries" {
-        for y in 0..height - 1 {
-            for x in 0..width {
-                let pixel = y * width + x;
-                let data_offset = pixel * 4;
-
-                let prov_id = usize::from(provinces[pixel]);
-                let prov_down = usize::from(provinces[pixel + width]);
-                let prov_right = usize::from(provinces[pixel + 1]);
-                let province_offset = prov_id * 3;
-                let mut is_edge = false;
-
-                if prov_id != prov_down {
-                    let prov_down_offset = prov_down * 3;
-                    if !memcmp_three(&primary[province_offset..], &primary[prov_down_offset..]) {
-                        data[data_offset + 3 + width * 4] = 1;
-                        is_edge = true;
-                    }
-                }
-
-                if prov_id != prov_right {
-                    let prov_right_offset = prov_right * 3;
-                    if !memcmp_three(&primary[province_offset..], &primary[prov_right_offset..]) {
-                        data[data_offset + 3 + 4] = 1;
-                        is_edge = true;
-                    }
-                }
-
-                if is_edge || data[data_offset + 3] == 1 {
-                    data[data_offset] = 30;
-                    data[data_offset + 1] = 30;
-                    data[data_offset + 2] = 30;
-                    data[data_offset + 3] = 255;
-                } else if secondary.len() == primary.len() && (y + x) % 6 < 3 {
-                    let province_offset = prov_id * 3;
-                    data[data_offset] = secondary[province_offset];
-                    data[data_offset + 1] = secondary[province_offset + 1];
-                    data[data_offset + 2] = secondary[province_offset + 2];
-                    data[data_offset + 3] = 255;
-                } else {
-                    let province_offset = prov_id * 3;
-                    data[data_offset] = primary[province_offset];
-                    data[data_offset + 1] = primary[province_offset + 1];
-                    data[data_offset + 2] = primary[province_offset + 2];
-                    data[data_offset + 3] = 255;
-                }
-            }
-        }
-
-        for x in 0..width {
-            let pixel = (height - 1) * width + x;
-            let prov_id = usize::from(provinces[pixel]);
-            let data_offset = pixel * 4;
-            if data[data_offset + 3] == 1 {
-                data[data_offset] = 30;
-                data[data_offset + 1] = 30;
-                data[data_offset + 2] = 30;
-                data[data_offset + 3] = 255;
-            } else {
-                let province_offset = prov_id * 3;
-                data[data_offset] = primary[province_offset];
-                data[data_offset + 1] = primary[province_offset + 1];
-                data[data_offset + 2] = primary[province_offset + 2];
-                data[data_offset + 3] = 255;
-            }
-        }
-    }
+    let Some(fill) = pdxmap::BorderFill::from_name(fill) else {
+        return;
+    };
+
+    pdxmap::fill_borders(
+        data,
+        provinces,
+        primary,
+        secondary,
+        fill,
+        render::MAP_WIDTH as usize,
+    );
 }
 
 fn js_err(err: impl std::error::Error) -> JsValue {
@@ -3284,6 +3254,8 @@ pub fn _initial_save(
         })
         .collect();
 
+    // The initial map is painted before the user's palette is known
+    let palette = Palette::default();
     let result_len: usize = province_id_to_color_index.len() * 4;
     let mut primary: Vec<u8> = vec![0; result_len];
 
@@ -3294,14 +3266,14 @@ pub fn _initial_save(
         };
 
         let primary_color = &mut primary[offset..offset + 4];
-        primary_color.copy_from_slice(&map::WASTELAND);
+        primary_color.copy_from_slice(&palette.wasteland);
         if let Some(owner_tag) = prov.owner.as_ref() {
             if let Some(known_color) = country_colors.get(owner_tag) {
                 primary_color.copy_from_slice(known_color);
             }
         } else if let Some(prov) = game.get_province(id) {
             if prov.is_habitable() {
-                primary_color.copy_from_slice(&[94, 94, 94, 128]);
+                primary_color.copy_from_slice(&palette.unowned);
             }
         }
     }
@@ -3321,30 +3293,63 @@ pub fn game_save(
     game_data: Vec<u8>,
     province_id_to_color_index: Vec<u16>,
 ) -> Result<SaveFile, JsValue> {
-    let game = Game::from_flatbuffer(&game_data);
-    // Cast away the lifetime so that we can store it in a wasm-bindgen compatible struct
-    let game: Game<'static> = unsafe { std::mem::transmute(game) };
-
-    let query = Query::from_save(save.0);
-    let province_owners = query.province_owners();
-    let nation_events = query.nation_events(&province_owners);
-    let player_histories = query.player_histories(&nation_events);
-    let tag_resolver = query.tag_resolver(&nation_events);
-    let war_participants = query.resolved_war_participants(&tag_resolver);
-    let religion_lookup = query.religion_lookup();
-    Ok(SaveFile(SaveFileImpl {
-        query,
-        game,
-        _game_data: game_data,
-        encoding: save.1,
-        province_owners,
-        nation_events,
-        tag_resolver,
-        war_participants,
-        player_histories,
-        religion_lookup,
+    Ok(SaveFile(SaveFileImpl::new(
+        save.0,
+        save.1,
+        game_data,
         province_id_to_color_index,
-    }))
+    )))
+}
+
+impl SaveFileImpl {
+    /// Builds the save outside of wasm-bindgen so that native tools (like
+    /// the timelapse encoder) can use the same analysis.
+    pub fn new(
+        save: Eu4Save,
+        encoding: Encoding,
+        game_data: Vec<u8>,
+        province_id_to_color_index: Vec<u16>,
+    ) -> Self {
+        let game = Game::from_flatbuffer(&game_data);
+        // Cast away the lifetime so that we can store it in a wasm-bindgen compatible struct
+        let game: Game<'static> = unsafe { std::mem::transmute(game) };
+
+        let query = Query::from_save(save);
+        let province_owners = query.province_owners();
+        let owner_history = history::OwnerHistory::from_owners(&province_owners);
+        let nation_events = query.nation_events(&province_owners);
+        let player_histories = query.player_histories(&nation_events);
+        let tag_resolver = query.tag_resolver(&nation_events);
+        let war_participants = query.resolved_war_participants(&tag_resolver);
+        let religion_lookup = query.religion_lookup();
+
+        let colors = province_id_to_color_index
+            .iter()
+            .max()
+            .map_or(0, |x| usize::from(*x) + 1);
+        let mut color_index_to_province_id = vec![0u16; colors];
+        for (id, &index) in province_id_to_color_index.iter().enumerate().skip(1) {
+            if index != 0 {
+                color_index_to_province_id[usize::from(index)] = id as u16;
+            }
+        }
+
+        SaveFileImpl {
+            query,
+            game,
+            _game_data: game_data,
+            encoding,
+            province_owners,
+            owner_history,
+            nation_events,
+            tag_resolver,
+            war_participants,
+            player_histories,
+            religion_lookup,
+            province_id_to_color_index,
+            color_index_to_province_id,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+
+/// Casualties decoded from the 21 loss members the save records per country
+/// and war participant. Each unit type occupies three members: land units
+/// record battle, attrition, and an unused member, while ships record
+/// battle (sunk), attrition, and capture.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LossBreakdown {
+    pub infantry_battle: u32,
+    pub infantry_attrition: u32,
+    pub cavalry_battle: u32,
+    pub cavalry_attrition: u32,
+    pub artillery_battle: u32,
+    pub artillery_attrition: u32,
+    pub heavy_ship_battle: u32,
+    pub heavy_ship_attrition: u32,
+    pub heavy_ship_capture: u32,
+    pub light_ship_battle: u32,
+    pub light_ship_attrition: u32,
+    pub light_ship_capture: u32,
+    pub galley_ship_battle: u32,
+    pub galley_ship_attrition: u32,
+    pub galley_ship_capture: u32,
+    pub transport_ship_battle: u32,
+    pub transport_ship_attrition: u32,
+    pub transport_ship_capture: u32,
+
+    pub land_total_battle: u32,
+    pub land_total_attrition: u32,
+    pub land_total: u32,
+    pub navy_total_battle: u32,
+    pub navy_total_attrition: u32,
+    pub navy_total_capture: u32,
+    pub navy_total: u32,
+    pub total_battle: u32,
+    pub total_attrition: u32,
+    pub total: u32,
+}
+
+impl LossBreakdown {
+    pub fn from_members(data: &[i32]) -> Self {
+        let mut values = [0u32; 21];
+        const LOSSES_MAX: i32 = i32::MAX / 1000;
+        const LOSSES_MIN: i32 = -LOSSES_MAX;
+        for (&x, y) in data.iter().zip(values.iter_mut()) {
+            *y += match x {
+                0.. => x as u32,
+                LOSSES_MIN..=-1 => (x + 2 * LOSSES_MAX) as u32,
+                _ => x.unsigned_abs(),
+            };
+        }
+
+        let mut result = LossBreakdown {
+            infantry_battle: values[0],
+            infantry_attrition: values[1],
+            cavalry_battle: values[3],
+            cavalry_attrition: values[4],
+            artillery_battle: values[6],
+            artillery_attrition: values[7],
+            heavy_ship_battle: values[9],
+            heavy_ship_attrition: values[10],
+            heavy_ship_capture: values[11],
+            light_ship_battle: values[12],
+            light_ship_attrition: values[13],
+            light_ship_capture: values[14],
+            galley_ship_battle: values[15],
+            galley_ship_attrition: values[16],
+            galley_ship_capture: values[17],
+            transport_ship_battle: values[18],
+            transport_ship_attrition: values[19],
+            transport_ship_capture: values[20],
+            ..Default::default()
+        };
+
+        result.land_total_battle =
+            result.infantry_battle + result.cavalry_battle + result.artillery_battle;
+        result.land_total_attrition =
+            result.infantry_attrition + result.cavalry_attrition + result.artillery_attrition;
+        result.land_total = result.land_total_battle + result.land_total_attrition;
+
+        result.navy_total_battle = result.heavy_ship_battle
+            + result.light_ship_battle
+            + result.galley_ship_battle
+            + result.transport_ship_battle;
+        result.navy_total_attrition = result.heavy_ship_attrition
+            + result.light_ship_attrition
+            + result.galley_ship_attrition
+            + result.transport_ship_attrition;
+        result.navy_total_capture = result.heavy_ship_capture
+            + result.light_ship_capture
+            + result.galley_ship_capture
+            + result.transport_ship_capture;
+        result.navy_total =
+            result.navy_total_battle + result.navy_total_attrition + result.navy_total_capture;
+
+        result.total_battle = result.land_total_battle + result.navy_total_battle;
+        result.total_attrition = result.land_total_attrition + result.navy_total_attrition;
+        result.total = result.land_total + result.navy_total;
+        result
+    }
+}
+
+/// Sums saturate as totals across every country of a long campaign can
+/// exceed a u32
+impl std::ops::AddAssign<&LossBreakdown> for LossBreakdown {
+    fn add_assign(&mut self, rhs: &LossBreakdown) {
+        let add = |x: &mut u32, y: u32| *x = x.saturating_add(y);
+        add(&mut self.infantry_battle, rhs.infantry_battle);
+        add(&mut self.infantry_attrition, rhs.infantry_attrition);
+        add(&mut self.cavalry_battle, rhs.cavalry_battle);
+        add(&mut self.cavalry_attrition, rhs.cavalry_attrition);
+        add(&mut self.artillery_battle, rhs.artillery_battle);
+        add(&mut self.artillery_attrition, rhs.artillery_attrition);
+        add(&mut self.heavy_ship_battle, rhs.heavy_ship_battle);
+        add(&mut self.heavy_ship_attrition, rhs.heavy_ship_attrition);
+        add(&mut self.heavy_ship_capture, rhs.heavy_ship_capture);
+        add(&mut self.light_ship_battle, rhs.light_ship_battle);
+        add(&mut self.light_ship_attrition, rhs.light_ship_attrition);
+        add(&mut self.light_ship_capture, rhs.light_ship_capture);
+        add(&mut self.galley_ship_battle, rhs.galley_ship_battle);
+        add(&mut self.galley_ship_attrition, rhs.galley_ship_attrition);
+        add(&mut self.galley_ship_capture, rhs.galley_ship_capture);
+        add(&mut self.transport_ship_battle, rhs.transport_ship_battle);
+        add(
+            &mut self.transport_ship_attrition,
+            rhs.transport_ship_attrition,
+        );
+        add(&mut self.transport_ship_capture, rhs.transport_ship_capture);
+        add(&mut self.land_total_battle, rhs.land_total_battle);
+        add(&mut self.land_total_attrition, rhs.land_total_attrition);
+        add(&mut self.land_total, rhs.land_total);
+        add(&mut self.navy_total_battle, rhs.navy_total_battle);
+        add(&mut self.navy_total_attrition, rhs.navy_total_attrition);
+        add(&mut self.navy_total_capture, rhs.navy_total_capture);
+        add(&mut self.navy_total, rhs.navy_total);
+        add(&mut self.total_battle, rhs.total_battle);
+        add(&mut self.total_attrition, rhs.total_attrition);
+        add(&mut self.total, rhs.total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loss_members() {
+        let data: Vec<i32> = (1..=21).collect();
+        let losses = LossBreakdown::from_members(&data);
+        let expected = LossBreakdown {
+            infantry_battle: 1,
+            infantry_attrition: 2,
+            cavalry_battle: 4,
+            cavalry_attrition: 5,
+            artillery_battle: 7,
+            artillery_attrition: 8,
+            heavy_ship_battle: 10,
+            heavy_ship_attrition: 11,
+            heavy_ship_capture: 12,
+            light_ship_battle: 13,
+            light_ship_attrition: 14,
+            light_ship_capture: 15,
+            galley_ship_battle: 16,
+            galley_ship_attrition: 17,
+            galley_ship_capture: 18,
+            transport_ship_battle: 19,
+            transport_ship_attrition: 20,
+            transport_ship_capture: 21,
+            land_total_battle: 12,
+            land_total_attrition: 15,
+            land_total: 27,
+            navy_total_battle: 58,
+            navy_total_attrition: 62,
+            navy_total_capture: 66,
+            navy_total: 186,
+            total_battle: 70,
+            total_attrition: 77,
+            total: 213,
+        };
+        assert_eq!(losses, expected);
+    }
+
+    #[test]
+    fn test_loss_members_overflow() {
+        // Members wrap around to negative values past i32::MAX / 1000
+        let losses = LossBreakdown::from_members(&[-1, -3_000_000]);
+        assert_eq!(losses.infantry_battle, 4_294_965);
+        assert_eq!(losses.infantry_attrition, 3_000_000);
+        assert_eq!(losses.total, 7_294_965);
+
+        let losses = LossBreakdown::from_members(&[5]);
+        assert_eq!(losses.cavalry_battle, 0);
+        assert_eq!(losses.total, 5);
+    }
+
+    #[test]
+    fn test_loss_sum_saturates() {
+        let mut losses = LossBreakdown::from_members(&[1000, 200]);
+        losses += &LossBreakdown::from_members(&[500]);
+        assert_eq!(losses.infantry_battle, 1500);
+        assert_eq!(losses.infantry_attrition, 200);
+        assert_eq!(losses.total, 1700);
+
+        let max = LossBreakdown {
+            total: u32::MAX - 1,
+            ..Default::default()
+        };
+        losses += &max;
+        assert_eq!(losses.total, u32::MAX);
+        assert_eq!(losses.infantry_battle, 1500);
+    }
+}
@@ -0,0 +1,522 @@
+use crate::{
+    dynasty::Reign,
+    history::months_between,
+    tag_filter::{TagFilterPayload, TagFilterPayloadRaw},
+    LocalizedTag, SaveFileImpl,
+};
+use eu4save::{
+    models::{Country, ProvinceEvent},
+    query::CountryManaSpend,
+    CountryTag, Eu4Date, PdsDate,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+// Every country generates this much of each power per month regardless of
+// ruler and advisors
+const BASE_MONTHLY_MANA: i32 = 3;
+
+const ADM_ADVISORS: &[&str] = &[
+    "philosopher",
+    "natural_scientist",
+    "artist",
+    "treasurer",
+    "theologian",
+    "master_of_mint",
+    "inquisitor",
+];
+
+const DIP_ADVISORS: &[&str] = &[
+    "statesman",
+    "naval_reformer",
+    "trader",
+    "spymaster",
+    "colonial_governor",
+    "diplomat",
+    "navigator",
+];
+
+const MIL_ADVISORS: &[&str] = &[
+    "army_reformer",
+    "army_organiser",
+    "commandant",
+    "quartermaster",
+    "recruitmaster",
+    "fortification_expert",
+    "grand_captain",
+];
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ManaAmounts {
+    pub adm: i32,
+    pub dip: i32,
+    pub mil: i32,
+}
+
+impl ManaAmounts {
+    fn total(&self) -> i32 {
+        self.adm + self.dip + self.mil
+    }
+
+    fn get_mut(&mut self, kind: ManaKind) -> &mut i32 {
+        match kind {
+            ManaKind::Adm => &mut self.adm,
+            ManaKind::Dip => &mut self.dip,
+            ManaKind::Mil => &mut self.mil,
+        }
+    }
+}
+
+impl std::ops::Add for ManaAmounts {
+    type Output = ManaAmounts;
+
+    fn add(self, rhs: ManaAmounts) -> ManaAmounts {
+        ManaAmounts {
+            adm: self.adm + rhs.adm,
+            dip: self.dip + rhs.dip,
+            mil: self.mil + rhs.mil,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ManaKind {
+    Adm,
+    Dip,
+    Mil,
+}
+
+/// Mana generated from the start of the campaign up to the date
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManaGenerationPoint {
+    pub date: Eu4Date,
+    pub base: ManaAmounts,
+    pub ruler: ManaAmounts,
+    pub advisors: ManaAmounts,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ManaSpentCategories {
+    pub technology: ManaAmounts,
+    pub development: ManaAmounts,
+    pub ideas: ManaAmounts,
+    pub stability: ManaAmounts,
+    pub cores: ManaAmounts,
+    pub other: ManaAmounts,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorSkillTally {
+    pub skill: u8,
+    pub advisors: usize,
+    pub months: i32,
+    pub mana: i32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManaEfficiency {
+    pub country: LocalizedTag,
+    pub generation: Vec<ManaGenerationPoint>,
+    pub generated: ManaAmounts,
+    pub spent: ManaSpentCategories,
+    pub stockpile: ManaAmounts,
+
+    /// Generated mana that was neither spent nor is in the stockpile, so was
+    /// lost to overflowing the cap. Mana from sources outside of rulers and
+    /// advisors (events, modifiers) lowers this estimate.
+    pub wasted: ManaAmounts,
+    pub wasted_share: f64,
+    pub advisors: Vec<AdvisorSkillTally>,
+    pub advisor_ducats: f32,
+
+    /// Ducats paid in advisor salaries per point of mana they generated
+    pub ducats_per_mana: Option<f64>,
+}
+
+/// An advisor hired by a country, as recorded in province history
+struct HiredAdvisor {
+    kind: ManaKind,
+    skill: u8,
+    start: Eu4Date,
+    end: Eu4Date,
+}
+
+impl SaveFileImpl {
+    /// Mana generation, spending, and wastage of the countries matching the
+    /// filter so that they can be benchmarked against each other.
+    ///
+    /// Ruler generation is derived from the monarch history and advisor
+    /// generation from advisors in province history. Advisors that died are
+    /// sometimes purged from the history so advisor generation is a lower
+    /// bound.
+    pub fn get_mana_efficiency(&self, payload: TagFilterPayloadRaw) -> Vec<ManaEfficiency> {
+        let payload = TagFilterPayload::from(payload);
+        let tags = self.matching_tags(&payload);
+        let mut advisors = self.hired_advisors();
+
+        let mut reigns: HashMap<CountryTag, Vec<_>> = HashMap::new();
+        for reign in self.all_reigns() {
+            reigns.entry(reign.tag).or_default().push(reign);
+        }
+
+        let mut result: Vec<_> = tags
+            .iter()
+            .filter_map(|tag| {
+                let country = self.query.country(tag)?;
+                let reigns = reigns.remove(tag).unwrap_or_default();
+                let advisors = advisors.remove(tag).unwrap_or_default();
+                Some(self.mana_efficiency(*tag, country, &reigns, &advisors))
+            })
+            .collect();
+
+        result.sort_by(|a, b| a.country.name.cmp(&b.country.name));
+        result
+    }
+
+    fn mana_efficiency(
+        &self,
+        tag: CountryTag,
+        country: &Country,
+        reigns: &[Reign],
+        advisors: &[HiredAdvisor],
+    ) -> ManaEfficiency {
+        let start_date = self.query.save().game.start_date;
+        let save_date = self.query.save().meta.date;
+
+        let mut dates: Vec<_> = (start_date.year() + 1..=save_date.year())
+            .map(|year| Eu4Date::from_ymd(year, 1, 1))
+            .collect();
+        dates.push(save_date);
+
+        let generation: Vec<_> = dates
+            .into_iter()
+            .map(|date| {
+                let months = months_between(start_date, date);
+                let base = BASE_MONTHLY_MANA * months;
+
+                let mut ruler = ManaAmounts::default();
+                for reign in reigns {
+                    let end = reign.end.unwrap_or(save_date).min(date);
+                    if end <= reign.start {
+                        continue;
+                    }
+
+                    let months = months_between(reign.start, end);
+                    ruler.adm += months * i32::from(reign.monarch.adm);
+                    ruler.dip += months * i32::from(reign.monarch.dip);
+                    ruler.mil += months * i32::from(reign.monarch.mil);
+                }
+
+                let mut from_advisors = ManaAmounts::default();
+                for advisor in advisors {
+                    let end = advisor.end.min(date);
+                    if end <= advisor.start {
+                        continue;
+                    }
+
+                    let months = months_between(advisor.start, end);
+                    *from_advisors.get_mut(advisor.kind) += months * i32::from(advisor.skill);
+                }
+
+                ManaGenerationPoint {
+                    date,
+                    base: ManaAmounts {
+                        adm: base,
+                        dip: base,
+                        mil: base,
+                    },
+                    ruler,
+                    advisors: from_advisors,
+                }
+            })
+            .collect();
+
+        let generated = generation
+            .last()
+            .map(|x| x.base + x.ruler + x.advisors)
+            .unwrap_or_default();
+
+        let usage = self.query.country_mana_breakdown(country);
+        let spent = ManaSpentCategories::from_usage([&usage.adm, &usage.dip, &usage.mil]);
+        let total_spent = spent.technology
+            + spent.development
+            + spent.ideas
+            + spent.stability
+            + spent.cores
+            + spent.other;
+
+        let stockpile = ManaAmounts {
+            adm: country.powers[0],
+            dip: country.powers[1],
+            mil: country.powers[2],
+        };
+
+        let wasted = ManaAmounts {
+            adm: (generated.adm - total_spent.adm - stockpile.adm).max(0),
+            dip: (generated.dip - total_spent.dip - stockpile.dip).max(0),
+            mil: (generated.mil - total_spent.mil - stockpile.mil).max(0),
+        };
+
+        let wasted_share = if generated.total() > 0 {
+            f64::from(wasted.total()) / f64::from(generated.total())
+        } else {
+            0.0
+        };
+
+        let mut skills: BTreeMap<u8, AdvisorSkillTally> = BTreeMap::new();
+        for advisor in advisors {
+            let tally = skills.entry(advisor.skill).or_default();
+            let months = months_between(advisor.start, advisor.end).max(0);
+            tally.skill = advisor.skill;
+            tally.advisors += 1;
+            tally.months += months;
+            tally.mana += months * i32::from(advisor.skill);
+        }
+
+        let advisor_ducats = self
+            .query
+            .country_total_expense_breakdown(country)
+            .advisor_maintenance;
+        let advisor_mana: i32 = skills.values().map(|x| x.mana).sum();
+        let ducats_per_mana =
+            (advisor_mana > 0).then(|| f64::from(advisor_ducats) / f64::from(advisor_mana));
+
+        ManaEfficiency {
+            country: self.localize_tag(tag),
+            generation,
+            generated,
+            spent,
+            stockpile,
+            wasted,
+            wasted_share,
+            advisors: skills.into_values().collect(),
+            advisor_ducats,
+            ducats_per_mana,
+        }
+    }
+
+    /// Advisors in province history attributed to the stored tag of the
+    /// owner of the province at the time of hire. As a country employs one
+    /// advisor per category, an advisor that outlives its replacement is
+    /// only counted until the replacement is hired.
+    fn hired_advisors(&self) -> HashMap<CountryTag, Vec<HiredAdvisor>> {
+        let save_date = self.query.save().meta.date;
+        let mut hires: Vec<_> = self
+            .query
+            .save()
+            .game
+            .provinces
+            .iter()
+            .flat_map(|(id, prov)| {
+                prov.history
+                    .events
+                    .iter()
+                    .filter_map(move |(_, event)| match event {
+                        ProvinceEvent::Advisor(x) => Some((*id, x)),
+                        _ => None,
+                    })
+            })
+            .filter_map(|(id, advisor)| {
+                let kind = advisor_kind(&advisor._type)?;
+                let start = advisor.hire_date?;
+                let end = advisor.death_date.unwrap_or(save_date);
+                let hired = HiredAdvisor {
+                    kind,
+                    skill: advisor.skill,
+                    start,
+                    end,
+                };
+                Some((id, hired))
+            })
+            .collect();
+        hires.sort_by_key(|(_, x)| x.start);
+
+        let mut result: HashMap<CountryTag, Vec<HiredAdvisor>> = HashMap::new();
+        for (id, hired) in hires {
+            let Some(owner) = self.owner_history.owner_at(id, hired.start) else {
+                continue;
+            };
+
+            let owner = self
+                .tag_resolver
+                .resolve(owner, hired.start)
+                .map(|x| x.stored)
+                .unwrap_or(owner);
+            result.entry(owner).or_default().push(hired);
+        }
+
+        for advisors in result.values_mut() {
+            end_at_replacement(advisors);
+        }
+
+        result
+    }
+}
+
+/// Ends each advisor no later than the hire of the next advisor of the same
+/// category. Advisors are expected to be ordered by hire date.
+fn end_at_replacement(advisors: &mut [HiredAdvisor]) {
+    for i in 0..advisors.len() {
+        let kind = advisors[i].kind;
+        let replaced = advisors[i + 1..]
+            .iter()
+            .find(|x| x.kind == kind)
+            .map(|x| x.start);
+
+        if let Some(replaced) = replaced {
+            let advisor = &mut advisors[i];
+            advisor.end = advisor.end.min(replaced).max(advisor.start);
+        }
+    }
+}
+
+impl ManaSpentCategories {
+    fn from_usage(usage: [&CountryManaSpend; 3]) -> Self {
+        let mut result = ManaSpentCategories::default();
+        let kinds = [ManaKind::Adm, ManaKind::Dip, ManaKind::Mil];
+        for (spend, kind) in usage.into_iter().zip(kinds) {
+            let categorized = [
+                (&mut result.technology, spend.advance_tech),
+                (&mut result.development, spend.develop_prov),
+                (&mut result.ideas, spend.buy_idea),
+                (&mut result.stability, spend.boost_stab),
+                (&mut result.cores, spend.make_province_core),
+            ];
+
+            let mut named = 0;
+            for (amounts, value) in categorized {
+                *amounts.get_mut(kind) += value;
+                named += value;
+            }
+
+            *result.other.get_mut(kind) += total_spent(spend) - named;
+        }
+
+        result
+    }
+}
+
+fn total_spent(x: &CountryManaSpend) -> i32 {
+    x.buy_idea
+        + x.advance_tech
+        + x.boost_stab
+        + x.buy_general
+        + x.buy_admiral
+        + x.buy_conq
+        + x.buy_explorer
+        + x.develop_prov
+        + x.force_march
+        + x.assault
+        + x.seize_colony
+        + x.burn_colony
+        + x.attack_natives
+        + x.scorch_earth
+        + x.demand_non_wargoal_prov
+        + x.reduce_inflation
+        + x.move_capital
+        + x.make_province_core
+        + x.replace_rival
+        + x.change_gov
+        + x.change_culture
+        + x.harsh_treatment
+        + x.reduce_we
+        + x.boost_faction
+        + x.raise_war_taxes
+        + x.buy_native_advancement
+        + x.increse_tariffs
+        + x.promote_merc
+        + x.decrease_tariffs
+        + x.move_trade_port
+        + x.create_trade_post
+        + x.siege_sorties
+        + x.buy_religious_reform
+        + x.set_primary_culture
+        + x.add_accepted_culture
+        + x.remove_accepted_culture
+        + x.strengthen_government
+        + x.boost_militarization
+        + x.artillery_barrage
+        + x.establish_siberian_frontier
+        + x.government_interaction
+        + x.naval_barrage
+        + x.create_leader
+        + x.enforce_culture
+        + x.effect
+        + x.minority_expulsion
+        + x.other
+}
+
+fn advisor_kind(kind: &str) -> Option<ManaKind> {
+    if ADM_ADVISORS.contains(&kind) {
+        Some(ManaKind::Adm)
+    } else if DIP_ADVISORS.contains(&kind) {
+        Some(ManaKind::Dip)
+    } else if MIL_ADVISORS.contains(&kind) {
+        Some(ManaKind::Mil)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advisor(kind: ManaKind, start: i16, end: i16) -> HiredAdvisor {
+        HiredAdvisor {
+            kind,
+            skill: 3,
+            start: Eu4Date::from_ymd(start, 1, 1),
+            end: Eu4Date::from_ymd(end, 1, 1),
+        }
+    }
+
+    #[test]
+    fn test_advisors_end_at_replacement() {
+        let mut advisors = vec![
+            advisor(ManaKind::Adm, 1450, 1480),
+            advisor(ManaKind::Dip, 1455, 1470),
+            advisor(ManaKind::Adm, 1460, 1490),
+            advisor(ManaKind::Dip, 1475, 1500),
+            advisor(ManaKind::Adm, 1495, 1510),
+        ];
+        end_at_replacement(&mut advisors);
+
+        let years: Vec<_> = advisors
+            .iter()
+            .map(|x| (x.start.year(), x.end.year()))
+            .collect();
+        let expected = vec![
+            (1450, 1460),
+            (1455, 1470),
+            (1460, 1490),
+            (1475, 1500),
+            (1495, 1510),
+        ];
+        assert_eq!(years, expected);
+    }
+
+    #[test]
+    fn test_advisors_hired_on_the_same_day() {
+        let mut advisors = vec![
+            advisor(ManaKind::Mil, 1450, 1480),
+            advisor(ManaKind::Mil, 1450, 1470),
+        ];
+        end_at_replacement(&mut advisors);
+        assert_eq!(advisors[0].end, advisors[0].start);
+        assert_eq!(advisors[1].end.year(), 1470);
+    }
+
+    #[test]
+    fn test_advisor_kind() {
+        assert_eq!(advisor_kind("theologian"), Some(ManaKind::Adm));
+        assert_eq!(advisor_kind("navigator"), Some(ManaKind::Dip));
+        assert_eq!(advisor_kind("grand_captain"), Some(ManaKind::Mil));
+        assert_eq!(advisor_kind("court_jester"), None);
+    }
+}
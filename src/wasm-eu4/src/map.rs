@@ -1,6 +1,8 @@
 use crate::{
+    palette::{MapLegend, Palette, PalettePayload},
     tag_filter::{TagFilterPayload, TagFilterPayloadRaw},
     utils::to_json_value,
+    war::{war_dates, ParticipantSide, WarSide},
     LocalizedObj, LocalizedTag, SaveFileImpl,
 };
 use eu4game::SaveGameQuery;
@@ -13,6 +15,15 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, str::FromStr};
 use wasm_bindgen::prelude::*;
 
+/// Brightens each channel of a subject painted in its overlord's hue by a
+/// tenth of the channel's range so the subject remains distinguishable
+const SUBJECT_LIGHTEN: u8 = 25;
+
+/// Saves don't record when a truce expires, so truces are approximated as
+/// lasting for the shortest truce a peace deal grants, counted from the war's
+/// last history event
+const TRUCE_YEARS: i32 = 5;
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum MapPayloadKind {
@@ -22,6 +33,16 @@ pub enum MapPayloadKind {
     Battles,
     Technology,
     Terrain,
+    Diplomacy {
+        tag: CountryTag,
+    },
+
+    /// Highlights provinces colonized between the given number of days from
+    /// the start of the campaign, open ended when absent
+    Colonial {
+        start: Option<i32>,
+        end: Option<i32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,6 +52,17 @@ pub struct MapPayload {
     tag_filter: TagFilterPayloadRaw,
     paint_subject_in_overlord_hue: bool,
     date: Option<i32>,
+    #[serde(default)]
+    palette: PalettePayload,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MapCursorError {
+    #[error("war timelapse requires a war")]
+    MissingWar,
+
+    #[error("unknown war: {0}")]
+    UnknownWar(String),
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -39,11 +71,15 @@ pub enum MapCursorPayloadKind {
     Political,
     Religion,
     Battles,
+
+    /// Provinces of the war's participants colored by the side that owns
+    /// and controls them, from the war's declaration to peace
+    War,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
-enum Interval {
+pub enum Interval {
     Year,
     Month,
     Week,
@@ -55,9 +91,169 @@ pub struct MapCursorPayload {
     kind: MapCursorPayloadKind,
     interval: Interval,
     start: Option<i32>,
+    #[serde(default)]
+    palette: PalettePayload,
+
+    /// Name of the war for the war timelapse
+    #[serde(default)]
+    war: Option<String>,
+}
+
+impl MapCursorPayload {
+    pub fn new(kind: MapCursorPayloadKind, interval: Interval, start: Option<i32>) -> Self {
+        Self {
+            kind,
+            interval,
+            start,
+            palette: PalettePayload::default(),
+            war: None,
+        }
+    }
+
+    pub fn with_war(mut self, war: String) -> Self {
+        self.war = Some(war);
+        self
+    }
+}
+
+/// How a country relates to the country selected in the diplomacy map mode.
+/// Variants are ordered by precedence: when two countries share multiple
+/// relationships, the first listed is the one that is painted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum DiplomaticRelation {
+    Selected,
+    Overlord,
+    Subject,
+    AtWar,
+    Ally,
+    Guaranteed,
+    Rival,
+    Truce,
+    Neutral,
+}
+
+impl DiplomaticRelation {
+    pub const ALL: [DiplomaticRelation; 9] = [
+        DiplomaticRelation::Selected,
+        DiplomaticRelation::Overlord,
+        DiplomaticRelation::Subject,
+        DiplomaticRelation::AtWar,
+        DiplomaticRelation::Ally,
+        DiplomaticRelation::Guaranteed,
+        DiplomaticRelation::Rival,
+        DiplomaticRelation::Truce,
+        DiplomaticRelation::Neutral,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            DiplomaticRelation::Selected => "selected",
+            DiplomaticRelation::Overlord => "overlord",
+            DiplomaticRelation::Subject => "subject",
+            DiplomaticRelation::AtWar => "atWar",
+            DiplomaticRelation::Ally => "ally",
+            DiplomaticRelation::Guaranteed => "guaranteed",
+            DiplomaticRelation::Rival => "rival",
+            DiplomaticRelation::Truce => "truce",
+            DiplomaticRelation::Neutral => "neutral",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DiplomaticRelationEntry {
+    relation: DiplomaticRelation,
+    since: Option<Eu4Date>,
+    war: Option<String>,
+
+    // Set when the relation was inherited from the country's overlord
+    via: Option<CountryTag>,
+}
+
+impl DiplomaticRelationEntry {
+    fn new(relation: DiplomaticRelation, since: Option<Eu4Date>) -> Self {
+        Self {
+            relation,
+            since,
+            war: None,
+            via: None,
+        }
+    }
+}
+
+/// The relationships countries have with the selected country as of a date,
+/// keeping the relation that takes precedence when there are several
+struct DiplomaticRelations {
+    at: Eu4Date,
+    relations: HashMap<CountryTag, DiplomaticRelationEntry>,
+}
+
+impl DiplomaticRelations {
+    fn new(tag: CountryTag, at: Eu4Date) -> Self {
+        let mut result = Self {
+            at,
+            relations: HashMap::new(),
+        };
+
+        let selected = DiplomaticRelationEntry::new(DiplomaticRelation::Selected, None);
+        result.insert(tag, selected);
+        result
+    }
+
+    /// Records the relation unless it was established after the date or a
+    /// relation with higher precedence is already recorded
+    fn insert(&mut self, other: CountryTag, entry: DiplomaticRelationEntry) {
+        if entry.since.map_or(false, |x| x > self.at) {
+            return;
+        }
+
+        self.relations
+            .entry(other)
+            .and_modify(|x| {
+                if entry.relation < x.relation {
+                    *x = entry.clone();
+                }
+            })
+            .or_insert(entry);
+    }
+
+    /// Subjects without a relation of their own take on their overlord's
+    /// relation. Takes (overlord, subject) pairs.
+    fn inherit(&mut self, dependencies: impl Iterator<Item = (CountryTag, CountryTag)>) {
+        let inherited: Vec<_> = dependencies
+            .filter(|(_, subject)| !self.relations.contains_key(subject))
+            .filter_map(|(overlord, subject)| {
+                self.relations.get(&overlord).map(|entry| {
+                    let entry = DiplomaticRelationEntry {
+                        via: Some(overlord),
+                        ..entry.clone()
+                    };
+                    (subject, entry)
+                })
+            })
+            .collect();
+
+        self.relations.extend(inherited);
+    }
 }
 
-pub const WASTELAND: [u8; 4] = [61, 61, 61, 0];
+/// The relation left by a war that has ended by the save date: wars that
+/// ended after the requested date were still being fought, and wars that
+/// ended within [TRUCE_YEARS] of it are a truce
+fn previous_war_relation(
+    start: Eu4Date,
+    ended: Eu4Date,
+    at: Eu4Date,
+) -> Option<(DiplomaticRelation, Eu4Date)> {
+    if ended < at.add_days(-365 * TRUCE_YEARS) {
+        None
+    } else if ended > at {
+        Some((DiplomaticRelation::AtWar, start))
+    } else {
+        Some((DiplomaticRelation::Truce, ended))
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "camelCase")]
@@ -109,6 +305,29 @@ pub enum MapQuickTipPayload {
         dip_tech: u8,
         mil_tech: u8,
     },
+
+    #[serde(rename_all = "camelCase")]
+    Diplomacy {
+        owner: LocalizedTag,
+        controller: LocalizedTag,
+        province_id: ProvinceId,
+        province_name: String,
+        selected: LocalizedTag,
+        relation: DiplomaticRelation,
+        since: Option<Eu4Date>,
+        war: Option<String>,
+        via: Option<LocalizedTag>,
+    },
+
+    #[serde(rename_all = "camelCase")]
+    Colonial {
+        owner: LocalizedTag,
+        controller: LocalizedTag,
+        province_id: ProvinceId,
+        province_name: String,
+        colonized: Option<Eu4Date>,
+        colonizer: Option<LocalizedTag>,
+    },
 }
 
 impl SaveFileImpl {
@@ -137,6 +356,7 @@ impl SaveFileImpl {
         province_id: i32,
         payload: MapPayloadKind,
         days: Option<i32>,
+        paint_subject_in_overlord_hue: bool,
     ) -> Option<MapQuickTipPayload> {
         let province_id = ProvinceId::new(province_id);
         let province = self.query.save().game.provinces.get(&province_id)?;
@@ -395,11 +615,152 @@ impl SaveFileImpl {
                     losses,
                 })
             }
+
+            MapPayloadKind::Diplomacy { tag } => {
+                let owner_tag = province.owner.as_ref()?;
+                let relations =
+                    self.diplomatic_relations(&tag, paint_subject_in_overlord_hue, requested_date);
+                let neutral = DiplomaticRelationEntry::new(DiplomaticRelation::Neutral, None);
+                let entry = relations.get(owner_tag).cloned().unwrap_or(neutral);
+
+                Some(MapQuickTipPayload::Diplomacy {
+                    province_id,
+                    province_name: province.name.clone(),
+                    owner: local_owner,
+                    controller: local_controller,
+                    selected: self.localize_tag(tag),
+                    relation: entry.relation,
+                    since: entry.since,
+                    war: entry.war,
+                    via: entry.via.map(|x| self.localize_tag(x)),
+                })
+            }
+
+            MapPayloadKind::Colonial { .. } => {
+                let colonization = self.province_colonization(province_id);
+
+                Some(MapQuickTipPayload::Colonial {
+                    province_id,
+                    province_name: province.name.clone(),
+                    owner: local_owner,
+                    controller: local_controller,
+                    colonized: colonization.map(|x| x.date),
+                    colonizer: colonization.map(|x| self.localize_tag(x.colonizer)),
+                })
+            }
             _ => None,
         }
     }
 
-    pub fn map_colors(&self, payload: MapPayload) -> Vec<u8> {
+    /// Computes the relationship every country has with the given tag. When
+    /// `inherit_from_overlord` is set, subjects without a relationship of
+    /// their own take on the relationship of their overlord.
+    ///
+    /// Saves only record the diplomacy in effect at the save date, so for an
+    /// earlier date the relationships established after it are omitted
+    /// while ones that have since ended can't be recovered (except for wars).
+    fn diplomatic_relations(
+        &self,
+        tag: &CountryTag,
+        inherit_from_overlord: bool,
+        at: Option<Eu4Date>,
+    ) -> HashMap<CountryTag, DiplomaticRelationEntry> {
+        let at = at.unwrap_or(self.query.save().meta.date);
+        let mut relations = DiplomaticRelations::new(*tag, at);
+
+        let save = self.query.save();
+        let diplomacy = &save.game.diplomacy;
+        for dependency in &diplomacy.dependencies {
+            let (other, relation) = if dependency.first == *tag {
+                (dependency.second, DiplomaticRelation::Subject)
+            } else if dependency.second == *tag {
+                (dependency.first, DiplomaticRelation::Overlord)
+            } else {
+                continue;
+            };
+
+            let entry = DiplomaticRelationEntry::new(relation, dependency.start_date);
+            relations.insert(other, entry);
+        }
+
+        let pairs = diplomacy
+            .alliances
+            .iter()
+            .map(|x| (x.first, x.second, x.start_date, DiplomaticRelation::Ally))
+            .chain(diplomacy.guarantees.iter().map(|x| {
+                (
+                    x.first,
+                    x.second,
+                    x.start_date,
+                    DiplomaticRelation::Guaranteed,
+                )
+            }));
+
+        for (first, second, since, relation) in pairs {
+            let other = if first == *tag {
+                second
+            } else if second == *tag {
+                first
+            } else {
+                continue;
+            };
+
+            relations.insert(other, DiplomaticRelationEntry::new(relation, since));
+        }
+
+        // Rivalries are recorded by the country that declared them, so look
+        // for the selected country in everyone's rivals too
+        for (other, country) in &save.game.countries {
+            let rivals = country.rivals.iter();
+            for rival in rivals.filter(|x| other == tag || x.country == *tag) {
+                let other = if other == tag { rival.country } else { *other };
+                let entry = DiplomaticRelationEntry::new(DiplomaticRelation::Rival, rival.date);
+                relations.insert(other, entry);
+            }
+        }
+
+        for war in &save.game.active_wars {
+            let Some((start, _)) = war_dates(&war.history) else {
+                continue;
+            };
+
+            for enemy in self.war_enemies(&war.name, &war.history, tag, true) {
+                let entry = DiplomaticRelationEntry {
+                    war: Some(war.name.clone()),
+                    ..DiplomaticRelationEntry::new(DiplomaticRelation::AtWar, Some(start))
+                };
+                relations.insert(enemy, entry);
+            }
+        }
+
+        for war in &save.game.previous_wars {
+            let Some((start, ended)) = war_dates(&war.history) else {
+                continue;
+            };
+
+            let Some((relation, since)) = previous_war_relation(start, ended, at) else {
+                continue;
+            };
+
+            for enemy in self.war_enemies(&war.name, &war.history, tag, false) {
+                let entry = DiplomaticRelationEntry {
+                    war: Some(war.name.clone()),
+                    ..DiplomaticRelationEntry::new(relation, Some(since))
+                };
+                relations.insert(enemy, entry);
+            }
+        }
+
+        if inherit_from_overlord {
+            let dependencies = diplomacy.dependencies.iter();
+            relations.inherit(dependencies.map(|x| (x.first, x.second)));
+        }
+
+        relations.relations
+    }
+
+    pub fn map_colors(&self, payload: MapPayload) -> MapColors {
+        let palette = Palette::from_payload(&payload.palette);
         if matches!(
             payload.kind,
             MapPayloadKind::Political | MapPayloadKind::Religion | MapPayloadKind::Battles
@@ -409,7 +770,7 @@ impl SaveFileImpl {
                 .map(|x| self.query.save().game.start_date.add_days(x));
 
             if let Some(date) = date {
-                return self.historical_map_color(date, payload.kind);
+                return self.historical_map_color(date, payload.kind, palette);
             }
         }
 
@@ -418,10 +779,15 @@ impl SaveFileImpl {
         let mut result: Vec<u8> = vec![0; result_len * 2];
         let (primary, secondary) = result.split_at_mut(result_len);
 
-        let excluded_color = [106, 108, 128, 255];
+        let excluded_color = palette.excluded;
+        let unowned_color = palette.unowned;
+        let mut legend = MapLegend::new(&palette);
         let filter = TagFilterPayload::from(payload.tag_filter);
         let tags = self.matching_tags(&filter);
 
+        // An empty selection includes every country
+        let in_filter = |tag: &CountryTag| tags.is_empty() || tags.contains(tag);
+
         let provs: Vec<(&ProvinceId, &Province, bool)> = self
             .query
             .save()
@@ -433,7 +799,7 @@ impl SaveFileImpl {
                 (
                     id,
                     prov,
-                    tags.is_empty() || prov.owner.as_ref().map_or(false, |x| tags.contains(x)),
+                    prov.owner.as_ref().map_or(tags.is_empty(), in_filter),
                 )
             })
             .collect();
@@ -450,9 +816,9 @@ impl SaveFileImpl {
                     if payload.paint_subject_in_overlord_hue {
                         for sub in &country.subjects {
                             let data = [
-                                country.colors.map_color[0].saturating_add((255.0 * 0.1) as u8),
-                                country.colors.map_color[1].saturating_add((255.0 * 0.1) as u8),
-                                country.colors.map_color[2].saturating_add((255.0 * 0.1) as u8),
+                                country.colors.map_color[0].saturating_add(SUBJECT_LIGHTEN),
+                                country.colors.map_color[1].saturating_add(SUBJECT_LIGHTEN),
+                                country.colors.map_color[2].saturating_add(SUBJECT_LIGHTEN),
                                 255,
                             ];
                             country_colors.insert(sub, data);
@@ -465,8 +831,8 @@ impl SaveFileImpl {
                     let primary_color = &mut primary[offset..offset + 4];
                     let secondary_color = &mut secondary[offset..offset + 4];
 
-                    primary_color.copy_from_slice(&WASTELAND);
-                    secondary_color.copy_from_slice(&WASTELAND);
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
 
                     if let Some(controller_tag) = prov.controller.as_ref() {
                         primary_color.copy_from_slice(&excluded_color);
@@ -488,8 +854,8 @@ impl SaveFileImpl {
                         }
                     } else if let Some(prov) = self.game.get_province(&id) {
                         if prov.is_habitable() {
-                            primary_color.copy_from_slice(&[94, 94, 94, 128]);
-                            secondary_color.copy_from_slice(&[94, 94, 94, 128]);
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
                         }
                     }
                 }
@@ -501,8 +867,8 @@ impl SaveFileImpl {
                     let primary_color = &mut primary[offset..offset + 4];
                     let secondary_color = &mut secondary[offset..offset + 4];
 
-                    primary_color.copy_from_slice(&WASTELAND);
-                    secondary_color.copy_from_slice(&WASTELAND);
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
 
                     if let Some(owner_tag) = prov.owner.as_ref() {
                         let owner = self.query.country(owner_tag).unwrap();
@@ -534,17 +900,14 @@ impl SaveFileImpl {
                         }
                     } else if let Some(prov) = self.game.get_province(&id) {
                         if prov.is_habitable() {
-                            primary_color.copy_from_slice(&[94, 94, 94, 128]);
-                            secondary_color.copy_from_slice(&[94, 94, 94, 128]);
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
                         }
                     }
                 }
             }
 
             MapPayloadKind::Development => {
-                let min_color = [127., 0., 0.];
-                let diff_color = [0. - 127., 212. - 0., 144. - 0.];
-
                 let max_dev = provs
                     .iter()
                     .filter(|(_, _, include)| *include)
@@ -552,14 +915,15 @@ impl SaveFileImpl {
                     .max_by(|a, b| a.partial_cmp(b).unwrap())
                     .unwrap_or(0.0);
                 let max_dev = max_dev.min(50.0).max(10.0);
+                legend = legend.with_gradient(&palette.gradient, 0.0, max_dev.into());
 
                 for (&id, prov, include) in provs {
                     let offset = province_id_to_color_index[usize::from(id.as_u16())] as usize * 4;
                     let primary_color = &mut primary[offset..offset + 4];
                     let secondary_color = &mut secondary[offset..offset + 4];
 
-                    primary_color.copy_from_slice(&WASTELAND);
-                    secondary_color.copy_from_slice(&WASTELAND);
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
 
                     if prov.owner.is_some() {
                         primary_color.copy_from_slice(&excluded_color);
@@ -571,37 +935,25 @@ impl SaveFileImpl {
 
                         let prov_dev = prov.base_tax + prov.base_manpower + prov.base_production;
                         let ratio = prov_dev.min(max_dev) / max_dev;
-                        let color = [
-                            (min_color[0] + ratio * diff_color[0]).round() as u8,
-                            (min_color[1] + ratio * diff_color[1]).round() as u8,
-                            (min_color[2] + ratio * diff_color[2]).round() as u8,
-                        ];
+                        let color = palette.gradient.sample(ratio.into());
 
                         primary_color[..3].copy_from_slice(&color);
                         secondary_color[..3].copy_from_slice(&color);
                     } else if let Some(prov) = self.game.get_province(&id) {
                         if prov.is_habitable() {
-                            primary_color.copy_from_slice(&[94, 94, 94, 128]);
-                            secondary_color.copy_from_slice(&[94, 94, 94, 128]);
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
                         }
                     }
                 }
             }
 
             MapPayloadKind::Battles => {
-                let mut timelapse = BattleTimelapse::new(self);
                 let final_date = self.query.save().meta.date;
-                let prep_date = final_date
-                    .add_days(-365)
-                    .max(self.query.save().game.start_date);
-                let _ = timelapse.advance_to(prep_date);
-                return timelapse.advance_to(final_date);
+                return self.historical_map_color(final_date, payload.kind, palette);
             }
 
             MapPayloadKind::Technology => {
-                let min_color = [127., 0., 0.];
-                let diff_color = [0. - 127., 212. - 0., 144. - 0.];
-
                 let max_tech = self
                     .query
                     .countries()
@@ -616,14 +968,19 @@ impl SaveFileImpl {
                     .unwrap_or(15);
 
                 let min_tech = max_tech - 15;
+                legend = legend.with_gradient(
+                    &palette.gradient,
+                    f64::from(min_tech),
+                    f64::from(max_tech),
+                );
 
                 for (&id, prov, include) in provs {
                     let offset = province_id_to_color_index[usize::from(id.as_u16())] as usize * 4;
                     let primary_color = &mut primary[offset..offset + 4];
                     let secondary_color = &mut secondary[offset..offset + 4];
 
-                    primary_color.copy_from_slice(&WASTELAND);
-                    secondary_color.copy_from_slice(&WASTELAND);
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
 
                     if let Some(owner) = prov.owner.as_ref() {
                         primary_color.copy_from_slice(&excluded_color);
@@ -644,18 +1001,124 @@ impl SaveFileImpl {
                             + i16::from(owner.technology.mil_tech);
                         let c_tech = c_tech.max(min_tech);
                         let ratio = ((c_tech - min_tech) as f64) / ((max_tech - min_tech) as f64);
-                        let color = [
-                            (min_color[0] + ratio * diff_color[0]).round() as u8,
-                            (min_color[1] + ratio * diff_color[1]).round() as u8,
-                            (min_color[2] + ratio * diff_color[2]).round() as u8,
-                        ];
+                        let color = palette.gradient.sample(ratio);
+
+                        primary_color[..3].copy_from_slice(&color);
+                        secondary_color[..3].copy_from_slice(&color);
+                    } else if let Some(prov) = self.game.get_province(&id) {
+                        if prov.is_habitable() {
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
+                        }
+                    }
+                }
+            }
+
+            MapPayloadKind::Diplomacy { tag } => {
+                let date = payload
+                    .date
+                    .map(|x| self.query.save().game.start_date.add_days(x));
+                let relations =
+                    self.diplomatic_relations(&tag, payload.paint_subject_in_overlord_hue, date);
+                for relation in DiplomaticRelation::ALL {
+                    legend.push(relation.key(), palette.relation(relation));
+                }
+
+                let relation_color = |tag: &CountryTag| {
+                    let Some(entry) = relations.get(tag) else {
+                        return palette.relation(DiplomaticRelation::Neutral);
+                    };
+
+                    let mut color = palette.relation(entry.relation);
+                    if entry.via.is_some() {
+                        for c in &mut color[..3] {
+                            *c = c.saturating_add(SUBJECT_LIGHTEN);
+                        }
+                    }
+
+                    color
+                };
+
+                for (&id, prov, include) in provs {
+                    let offset = province_id_to_color_index[usize::from(id.as_u16())] as usize * 4;
+                    let primary_color = &mut primary[offset..offset + 4];
+                    let secondary_color = &mut secondary[offset..offset + 4];
+
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
+
+                    if let Some(owner_tag) = prov.owner.as_ref() {
+                        primary_color.copy_from_slice(&excluded_color);
+                        secondary_color.copy_from_slice(&excluded_color);
+
+                        if !include {
+                            continue;
+                        }
+
+                        let owner_color = relation_color(owner_tag);
+                        primary_color.copy_from_slice(&owner_color);
+                        secondary_color.copy_from_slice(&owner_color);
+
+                        if let Some(controller_tag) = prov.controller.as_ref() {
+                            secondary_color.copy_from_slice(&relation_color(controller_tag));
+                        }
+                    } else if let Some(prov) = self.game.get_province(&id) {
+                        if prov.is_habitable() {
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
+                        }
+                    }
+                }
+            }
 
+            MapPayloadKind::Colonial { start, end } => {
+                let start_date = self.query.save().game.start_date;
+                let from = start.map_or(start_date, |x| start_date.add_days(x));
+                let to = end.map_or(self.query.save().meta.date, |x| start_date.add_days(x));
+                let colonized: HashMap<_, _> = self
+                    .colonizations()
+                    .into_iter()
+                    .filter(|x| x.date >= from && x.date <= to)
+                    .map(|x| (x.province, x))
+                    .collect();
+
+                // Color by when the province was colonized so that the spread
+                // of colonization across the range is visible
+                let range = f64::from(from.days_until(&to).max(1));
+                legend = legend.with_gradient(
+                    &palette.gradient,
+                    f64::from(from.year()),
+                    f64::from(to.year()),
+                );
+
+                for (&id, prov, _) in provs {
+                    let offset = province_id_to_color_index[usize::from(id.as_u16())] as usize * 4;
+                    let primary_color = &mut primary[offset..offset + 4];
+                    let secondary_color = &mut secondary[offset..offset + 4];
+
+                    primary_color.copy_from_slice(&palette.wasteland);
+                    secondary_color.copy_from_slice(&palette.wasteland);
+
+                    if prov.owner.is_some() {
+                        primary_color.copy_from_slice(&excluded_color);
+                        secondary_color.copy_from_slice(&excluded_color);
+
+                        let Some(colonization) = colonized.get(&id) else {
+                            continue;
+                        };
+
+                        if !in_filter(&colonization.colonizer) {
+                            continue;
+                        }
+
+                        let ratio = f64::from(from.days_until(&colonization.date)) / range;
+                        let color = palette.gradient.sample(ratio);
                         primary_color[..3].copy_from_slice(&color);
                         secondary_color[..3].copy_from_slice(&color);
                     } else if let Some(prov) = self.game.get_province(&id) {
                         if prov.is_habitable() {
-                            primary_color.copy_from_slice(&[94, 94, 94, 128]);
-                            secondary_color.copy_from_slice(&[94, 94, 94, 128]);
+                            primary_color.copy_from_slice(&unowned_color);
+                            secondary_color.copy_from_slice(&unowned_color);
                         }
                     }
                 }
@@ -664,45 +1127,92 @@ impl SaveFileImpl {
             MapPayloadKind::Terrain => {}
         }
 
-        result
+        MapColors {
+            legend,
+            data: result,
+        }
     }
 
-    fn historical_map_color(&self, date: eu4save::Eu4Date, kind: MapPayloadKind) -> Vec<u8> {
+    fn historical_map_color(
+        &self,
+        date: eu4save::Eu4Date,
+        kind: MapPayloadKind,
+        palette: Palette,
+    ) -> MapColors {
         match kind {
             MapPayloadKind::Religion => {
-                let mut timelapse = ReligionTimelapse::new(self);
-                timelapse.advance_to(date)
+                let mut timelapse = ReligionTimelapse::new(self, palette.clone());
+                MapColors {
+                    legend: MapLegend::new(&palette),
+                    data: timelapse.advance_to(date),
+                }
             }
             MapPayloadKind::Battles => {
-                let mut timelapse = BattleTimelapse::new(self);
+                let mut timelapse = BattleTimelapse::new(self, palette);
                 let prep_date = date.add_days(-365).max(self.query.save().game.start_date);
                 let _ = timelapse.advance_to(prep_date);
-                timelapse.advance_to(date)
+                let data = timelapse.advance_to(date);
+                MapColors {
+                    legend: timelapse.legend(),
+                    data,
+                }
             }
             _ => {
-                let mut timelapse = PoliticalTimelapse::new(self);
-                timelapse.advance_to(date)
+                let mut timelapse = PoliticalTimelapse::new(self, palette.clone());
+                MapColors {
+                    legend: MapLegend::new(&palette),
+                    data: timelapse.advance_to(date),
+                }
             }
         }
     }
 
-    pub fn map_cursor(&self, payload: MapCursorPayload) -> TimelapseIter {
+    pub fn map_cursor(&self, payload: MapCursorPayload) -> Result<TimelapseIter, MapCursorError> {
+        let palette = Palette::from_payload(&payload.palette);
         let timelapse = match payload.kind {
-            MapCursorPayloadKind::Political => Timelapse::Political(PoliticalTimelapse::new(self)),
-            MapCursorPayloadKind::Religion => Timelapse::Religion(ReligionTimelapse::new(self)),
-            MapCursorPayloadKind::Battles => Timelapse::Battles(BattleTimelapse::new(self)),
+            MapCursorPayloadKind::Political => {
+                Timelapse::Political(PoliticalTimelapse::new(self, palette))
+            }
+            MapCursorPayloadKind::Religion => {
+                Timelapse::Religion(ReligionTimelapse::new(self, palette))
+            }
+            MapCursorPayloadKind::Battles => {
+                Timelapse::Battles(BattleTimelapse::new(self, palette))
+            }
+            MapCursorPayloadKind::War => Timelapse::War(WarTimelapse::new(
+                self,
+                payload.war.as_deref().ok_or(MapCursorError::MissingWar)?,
+                palette,
+            )?),
         };
 
+        let mut start = self
+            .query
+            .save()
+            .game
+            .start_date
+            .add_days(payload.start.unwrap_or(0));
+        let mut end = self.query.save().meta.date;
+
+        // Scope the timelapse to the war, leaving ongoing wars open ended
+        let war = payload
+            .war
+            .as_deref()
+            .and_then(|x| self.find_war_history(x));
+        if let (MapCursorPayloadKind::War, Some((history, active))) = (&payload.kind, war) {
+            if let Some((war_start, war_end)) = war_dates(history) {
+                start = start.max(war_start);
+                if !active {
+                    end = war_end;
+                }
+            }
+        }
+
         let mut result = TimelapseIter {
             save_start: self.query.save().game.start_date,
-            start: self
-                .query
-                .save()
-                .game
-                .start_date
-                .add_days(payload.start.unwrap_or(0)),
+            start,
             current: Eu4Date::from_ymd(1, 1, 1),
-            end: self.query.save().meta.date,
+            end,
             interval: payload.interval,
             timelapse,
         };
@@ -713,7 +1223,7 @@ impl SaveFileImpl {
             let _ = result.next();
         }
 
-        result
+        Ok(result)
     }
 }
 
@@ -725,6 +1235,7 @@ enum ProvinceTracking {
 struct OwnerTimelapse {
     wasm: &'static SaveFileImpl,
     country_colors: HashMap<CountryTag, [u8; 4]>,
+    unowned: [u8; 4],
     current_owners: Vec<(Eu4Date, CountryTag)>,
     current_controllers: Vec<(Eu4Date, CountryTag)>,
     conflicts: HashMap<(CountryTag, CountryTag), Vec<ProvinceId>>,
@@ -734,7 +1245,7 @@ struct OwnerTimelapse {
 }
 
 impl OwnerTimelapse {
-    pub fn new(wasm: &SaveFileImpl, tracking: ProvinceTracking) -> Self {
+    pub fn new(wasm: &SaveFileImpl, tracking: ProvinceTracking, palette: &Palette) -> Self {
         let no_owner = "---".parse::<CountryTag>().unwrap();
         let country_colors = {
             let mut colors: HashMap<CountryTag, [u8; 4]> = wasm
@@ -749,7 +1260,7 @@ impl OwnerTimelapse {
                 })
                 .collect();
 
-            colors.insert(no_owner, [94, 94, 94, 128]);
+            colors.insert(no_owner, palette.unowned);
 
             colors
         };
@@ -889,6 +1400,7 @@ impl OwnerTimelapse {
 
         OwnerTimelapse {
             country_colors,
+            unowned: palette.unowned,
             current_owners,
             current_controllers,
             tracking,
@@ -899,6 +1411,12 @@ impl OwnerTimelapse {
         }
     }
 
+    /// The country's color, falling back to the unowned color for countries
+    /// missing from the save
+    fn country_color(&self, tag: &CountryTag) -> &[u8; 4] {
+        self.country_colors.get(tag).unwrap_or(&self.unowned)
+    }
+
     fn advance_to(&mut self, date: Eu4Date) {
         // let result_len = self.wasm.province_id_to_color_index.len() * 4;
         // let mut result: Vec<u8> = vec![0; result_len * 2];
@@ -1014,6 +1532,7 @@ enum Timelapse {
     Political(PoliticalTimelapse),
     Religion(ReligionTimelapse),
     Battles(BattleTimelapse),
+    War(WarTimelapse),
 }
 
 impl Timelapse {
@@ -1022,6 +1541,7 @@ impl Timelapse {
             Timelapse::Political(x) => x.advance_to(date),
             Timelapse::Religion(x) => x.advance_to(date),
             Timelapse::Battles(x) => x.advance_to(date),
+            Timelapse::War(x) => x.advance_to(date),
         }
     }
 
@@ -1029,7 +1549,7 @@ impl Timelapse {
     // primary color and country color is the same.
     fn parts(&self) -> usize {
         match self {
-            Timelapse::Political(_) => 2,
+            Timelapse::Political(_) | Timelapse::War(_) => 2,
             _ => 3,
         }
     }
@@ -1062,15 +1582,17 @@ enum PoliticalEventKind {
 struct PoliticalTimelapse {
     wasm: &'static SaveFileImpl,
     owners: OwnerTimelapse,
+    palette: Palette,
 }
 
 impl PoliticalTimelapse {
-    pub fn new(wasm: &SaveFileImpl) -> Self {
-        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OwnerAndController);
+    pub fn new(wasm: &SaveFileImpl, palette: Palette) -> Self {
+        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OwnerAndController, &palette);
 
         Self {
             wasm: unsafe { std::mem::transmute(wasm) },
             owners,
+            palette,
         }
     }
 
@@ -1083,13 +1605,14 @@ impl PoliticalTimelapse {
 
         for province in self.wasm.game.provinces() {
             let prov_ind = usize::from(province.id.as_u16());
+            let wasteland = &self.palette.wasteland;
             let color = 'color: {
                 if !province.is_habitable() {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 }
 
                 let Some((date, owner)) = self.owners.current_owners.get(prov_ind) else {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 };
 
                 let tag = resolver
@@ -1097,19 +1620,16 @@ impl PoliticalTimelapse {
                     .map(|x| x.current)
                     .unwrap_or(*owner);
 
-                self.owners
-                    .country_colors
-                    .get(&tag)
-                    .unwrap_or(&[94, 94, 94, 128])
+                self.owners.country_color(&tag)
             };
 
             let secondary_color = 'color: {
                 if !province.is_habitable() {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 }
 
                 let Some((date, tag)) = self.owners.current_controllers.get(prov_ind) else {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 };
 
                 let tag = resolver
@@ -1117,10 +1637,7 @@ impl PoliticalTimelapse {
                     .map(|x| x.current)
                     .unwrap_or(*tag);
 
-                self.owners
-                    .country_colors
-                    .get(&tag)
-                    .unwrap_or(&[94, 94, 94, 128])
+                self.owners.country_color(&tag)
             };
 
             let ind = self.wasm.province_id_to_color_index[prov_ind];
@@ -1133,6 +1650,91 @@ impl PoliticalTimelapse {
     }
 }
 
+struct WarTimelapse {
+    wasm: &'static SaveFileImpl,
+    owners: OwnerTimelapse,
+    sides: HashMap<CountryTag, WarSide>,
+    palette: Palette,
+}
+
+/// The side of every participant, including those that left the war, or an
+/// error when the war isn't in the save
+fn timelapse_sides(
+    war: &str,
+    participants: Option<HashMap<CountryTag, ParticipantSide>>,
+) -> Result<HashMap<CountryTag, WarSide>, MapCursorError> {
+    let participants = participants.ok_or_else(|| MapCursorError::UnknownWar(String::from(war)))?;
+    Ok(participants
+        .into_iter()
+        .map(|(tag, x)| (tag, x.side))
+        .collect())
+}
+
+impl WarTimelapse {
+    pub fn new(wasm: &SaveFileImpl, war: &str, palette: Palette) -> Result<Self, MapCursorError> {
+        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OwnerAndController, &palette);
+        let participants = wasm
+            .find_war_history(war)
+            .map(|(history, _)| wasm.war_sides(war, history));
+        let sides = timelapse_sides(war, participants)?;
+
+        Ok(Self {
+            wasm: unsafe { std::mem::transmute(wasm) },
+            owners,
+            sides,
+            palette,
+        })
+    }
+
+    fn advance_to(&mut self, date: Eu4Date) -> Vec<u8> {
+        let result_len = self.wasm.province_id_to_color_index.len() * 4;
+        let mut result: Vec<u8> = vec![0; result_len * 2];
+        let (primary, secondary) = result.split_at_mut(result_len);
+        let resolver = self.wasm.tag_resolver.at(date);
+        self.owners.advance_to(date);
+
+        let side_color = |tag: CountryTag, tag_date: Eu4Date| {
+            let stored = resolver
+                .resolve(tag, tag_date)
+                .map(|x| x.stored)
+                .unwrap_or(tag);
+            match self.sides.get(&stored) {
+                Some(WarSide::Attacker) => self.palette.attacker,
+                Some(WarSide::Defender) => self.palette.defender,
+                None => self.palette.excluded,
+            }
+        };
+
+        let wasteland = self.palette.wasteland;
+        for province in self.wasm.game.provinces() {
+            let prov_ind = usize::from(province.id.as_u16());
+            let owner = self.owners.current_owners.get(prov_ind);
+            let controller = self.owners.current_controllers.get(prov_ind);
+            let (primary_color, secondary_color) = match (owner, controller) {
+                _ if !province.is_habitable() => (wasteland, wasteland),
+                (Some((owner_date, owner)), Some((controller_date, controller))) => {
+                    if owner.as_bytes() == b"---" {
+                        (self.palette.unowned, self.palette.unowned)
+                    } else {
+                        (
+                            side_color(*owner, *owner_date),
+                            side_color(*controller, *controller_date),
+                        )
+                    }
+                }
+                _ => (wasteland, wasteland),
+            };
+
+            let ind = self.wasm.province_id_to_color_index[prov_ind];
+            let offset = usize::from(ind) * 4;
+            primary[offset..offset + 4].copy_from_slice(&primary_color);
+            secondary[offset..offset + 4].copy_from_slice(&secondary_color);
+        }
+
+        result
+    }
+}
+
 struct ReligionEvent {
     date: Eu4Date,
     kind: ReligionEventKind,
@@ -1155,6 +1757,7 @@ struct ReligionTimelapse {
     owners: OwnerTimelapse,
     country_religions: HashMap<CountryTag, ReligionIndex>,
     religion_colors: HashMap<ReligionIndex, [u8; 4]>,
+    palette: Palette,
 
     current_religions: Vec<ReligionIndex>,
     event_index: usize,
@@ -1162,8 +1765,8 @@ struct ReligionTimelapse {
 }
 
 impl ReligionTimelapse {
-    pub fn new(wasm: &SaveFileImpl) -> Self {
-        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OnlyOwner);
+    pub fn new(wasm: &SaveFileImpl, palette: Palette) -> Self {
+        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OnlyOwner, &palette);
 
         let religion_colors = wasm
             .query
@@ -1293,6 +1896,7 @@ impl ReligionTimelapse {
             current_religions,
             country_religions,
             religion_colors,
+            palette,
             events,
             wasm: unsafe { std::mem::transmute(wasm) },
             event_index: 0,
@@ -1332,19 +1936,21 @@ impl ReligionTimelapse {
         }
 
         let noone = "---".parse::<CountryTag>().unwrap();
+        let wasteland = &self.palette.wasteland;
+        let unowned = &self.palette.unowned;
         for province in self.wasm.game.provinces() {
             let prov_ind = usize::from(province.id.as_u16());
             let (primary_color, country_color) = 'color: {
                 if !province.is_habitable() {
-                    break 'color (&WASTELAND, &WASTELAND);
+                    break 'color (wasteland, wasteland);
                 }
 
                 let Some((date, owner)) = self.owners.current_owners.get(prov_ind) else {
-                    break 'color (&WASTELAND, &WASTELAND);
+                    break 'color (wasteland, wasteland);
                 };
 
                 if *owner == noone {
-                    break 'color (&[94, 94, 94, 128], &[94, 94, 94, 128]);
+                    break 'color (unowned, unowned);
                 }
 
                 let tag = resolver
@@ -1356,35 +1962,28 @@ impl ReligionTimelapse {
                     .country_religions
                     .get(&tag)
                     .and_then(|religion| self.religion_colors.get(religion))
-                    .unwrap_or(&[94, 94, 94, 128]);
+                    .unwrap_or(unowned);
 
-                let country_color = self
-                    .owners
-                    .country_colors
-                    .get(&tag)
-                    .unwrap_or(&[94, 94, 94, 128]);
-                (owner_religion_color, country_color)
+                (owner_religion_color, self.owners.country_color(&tag))
             };
 
             let secondary_color = 'color: {
                 // If the owner doesn't have a religion color then also mark the
                 // province as not having a religion color. This is so we don't
                 // clutter the map by showing colors for unowned provinces.
-                if primary_color == &[94, 94, 94, 128] {
+                if primary_color == unowned {
                     break 'color primary_color;
                 }
 
                 if !province.is_habitable() {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 }
 
                 let Some(religion) = self.current_religions.get(prov_ind) else {
-                    break 'color &WASTELAND;
+                    break 'color wasteland;
                 };
 
-                self.religion_colors
-                    .get(religion)
-                    .unwrap_or(&[94, 94, 94, 128])
+                self.religion_colors.get(religion).unwrap_or(unowned)
             };
 
             let ind = self.wasm.province_id_to_color_index[prov_ind];
@@ -1415,11 +2014,12 @@ struct BattleTimelapse {
     current_losses: Vec<i32>,
     event_index: usize,
     events: Vec<BattleEvent>,
+    palette: Palette,
 }
 
 impl BattleTimelapse {
-    pub fn new(wasm: &SaveFileImpl) -> Self {
-        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OnlyOwner);
+    pub fn new(wasm: &SaveFileImpl, palette: Palette) -> Self {
+        let owners = OwnerTimelapse::new(wasm, ProvinceTracking::OnlyOwner, &palette);
 
         let previous_events = wasm
             .query
@@ -1461,9 +2061,18 @@ impl BattleTimelapse {
             events,
             wasm: unsafe { std::mem::transmute(wasm) },
             event_index: 0,
+            palette,
         }
     }
 
+    fn legend(&self) -> MapLegend {
+        let max_losses = self.current_losses.iter().max().copied().unwrap_or(0);
+        let mut result =
+            MapLegend::default().with_gradient(&self.palette.heat, 0.0, f64::from(max_losses));
+        result.push("battle", self.palette.battle);
+        result
+    }
+
     fn advance_to(&mut self, date: Eu4Date) -> Vec<u8> {
         let result_len = self.wasm.province_id_to_color_index.len() * 4;
         let mut result: Vec<u8> = vec![0; result_len * 3];
@@ -1494,18 +2103,17 @@ impl BattleTimelapse {
         }
 
         let max_losses = self.current_losses.iter().max().copied().unwrap_or(0);
-        let min_color = [203., 213., 225.];
-        let diff_color = [244. - 203., 63. - 213., 94. - 225.];
+        let wasteland = self.palette.wasteland;
 
         for province in self.wasm.game.provinces() {
             let prov_ind = usize::from(province.id.as_u16());
             let (primary_color, country_color) = 'color: {
                 if !province.is_habitable() {
-                    break 'color (WASTELAND, &WASTELAND);
+                    break 'color (wasteland, &wasteland);
                 }
 
                 let Some((date, owner)) = self.owners.current_owners.get(prov_ind) else {
-                    break 'color (WASTELAND, &WASTELAND);
+                    break 'color (wasteland, &wasteland);
                 };
 
                 let tag = resolver
@@ -1515,19 +2123,9 @@ impl BattleTimelapse {
 
                 let losses = self.current_losses.get(prov_ind).copied().unwrap_or(0);
                 let ratio = losses as f64 / max_losses as f64;
-                let battle_color = [
-                    (min_color[0] + ratio * diff_color[0]).round() as u8,
-                    (min_color[1] + ratio * diff_color[1]).round() as u8,
-                    (min_color[2] + ratio * diff_color[2]).round() as u8,
-                    255,
-                ];
-
-                let country_color = self
-                    .owners
-                    .country_colors
-                    .get(&tag)
-                    .unwrap_or(&[94, 94, 94, 128]);
-                (battle_color, country_color)
+                let [r, g, b] = self.palette.heat.sample(ratio);
+                let battle_color = [r, g, b, 255];
+                (battle_color, self.owners.country_color(&tag))
             };
 
             let secondary_color = primary_color;
@@ -1542,7 +2140,7 @@ impl BattleTimelapse {
             let prov_ind = usize::from(province.as_u16());
             let ind = self.wasm.province_id_to_color_index[prov_ind];
             let offset = usize::from(ind) * 4;
-            secondary[offset..offset + 4].copy_from_slice(&[15, 23, 42, 255]);
+            secondary[offset..offset + 4].copy_from_slice(&self.palette.battle);
         }
 
         result
@@ -1563,34 +2161,7 @@ pub struct TimelapseIter {
 impl TimelapseIter {
     #[wasm_bindgen]
     pub fn next(&mut self) -> Option<TimelapseItem> {
-        use std::cmp::Ordering::{Equal, Greater, Less};
-        let next_date = match (self.current.cmp(&self.start), self.current.cmp(&self.end)) {
-            (_, Equal | Greater) => return None,
-            (Less, _) => self.start,
-            _ => match self.interval {
-                Interval::Year => self.current.add_days(365),
-                Interval::Month => {
-                    if self.current.month() + 1 > 12 {
-                        Eu4Date::from_ymd(self.current.year() + 1, 1, self.current.day().min(28))
-                    } else {
-                        Eu4Date::from_ymd(
-                            self.current.year(),
-                            self.current.month() + 1,
-                            self.current.day(),
-                        )
-                    }
-                }
-                Interval::Week => self.current.add_days(7),
-                Interval::Day => self.current.add_days(1),
-            },
-        };
-
-        let next_date = if next_date > self.end {
-            self.end
-        } else {
-            next_date
-        };
-
+        let next_date = self.step(self.current)?;
         let data = self.timelapse.advance_to(next_date);
         let date = MapDate {
             days: self.save_start.days_until(&next_date),
@@ -1607,6 +2178,35 @@ impl TimelapseIter {
     }
 }
 
+impl TimelapseIter {
+    fn step(&self, current: Eu4Date) -> Option<Eu4Date> {
+        use std::cmp::Ordering::{Equal, Greater, Less};
+        let next_date = match (current.cmp(&self.start), current.cmp(&self.end)) {
+            (_, Equal | Greater) => return None,
+            (Less, _) => self.start,
+            _ => match self.interval {
+                Interval::Year => current.add_days(365),
+                Interval::Month => {
+                    if current.month() + 1 > 12 {
+                        Eu4Date::from_ymd(current.year() + 1, 1, current.day().min(28))
+                    } else {
+                        Eu4Date::from_ymd(current.year(), current.month() + 1, current.day())
+                    }
+                }
+                Interval::Week => current.add_days(7),
+                Interval::Day => current.add_days(1),
+            },
+        };
+
+        Some(next_date.min(self.end))
+    }
+
+    /// The number of items left in the timelapse
+    pub fn remaining(&self) -> usize {
+        std::iter::successors(self.step(self.current), |date| self.step(*date)).count()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MapDate {
     pub days: i32,
@@ -1614,6 +2214,25 @@ pub struct MapDate {
     pub date: Eu4Date,
 }
 
+#[wasm_bindgen]
+pub struct MapColors {
+    legend: MapLegend,
+    data: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl MapColors {
+    #[wasm_bindgen]
+    pub fn legend(&self) -> JsValue {
+        to_json_value(&self.legend)
+    }
+
+    #[wasm_bindgen]
+    pub fn data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
 #[wasm_bindgen]
 pub struct TimelapseItem {
     date: MapDate,
@@ -1632,3 +2251,134 @@ impl TimelapseItem {
         self.data
     }
 }
+
+impl TimelapseItem {
+    pub fn map_date(&self) -> &MapDate {
+        &self.date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> CountryTag {
+        s.parse().unwrap()
+    }
+
+    fn entry(relation: DiplomaticRelation, year: i16) -> DiplomaticRelationEntry {
+        DiplomaticRelationEntry::new(relation, Some(Eu4Date::from_ymd(year, 1, 1)))
+    }
+
+    #[test]
+    fn test_relation_precedence() {
+        let expected = [
+            DiplomaticRelation::Selected,
+            DiplomaticRelation::Overlord,
+            DiplomaticRelation::Subject,
+            DiplomaticRelation::AtWar,
+            DiplomaticRelation::Ally,
+            DiplomaticRelation::Guaranteed,
+            DiplomaticRelation::Rival,
+            DiplomaticRelation::Truce,
+            DiplomaticRelation::Neutral,
+        ];
+        assert_eq!(DiplomaticRelation::ALL, expected);
+        assert!(expected.windows(2).all(|x| x[0] < x[1]));
+
+        let mut relations = DiplomaticRelations::new(tag("FRA"), Eu4Date::from_ymd(1500, 1, 1));
+        for relation in [
+            DiplomaticRelation::Rival,
+            DiplomaticRelation::Ally,
+            DiplomaticRelation::Subject,
+            DiplomaticRelation::Rival,
+        ] {
+            relations.insert(tag("SCO"), entry(relation, 1490));
+        }
+        relations.insert(tag("CAS"), entry(DiplomaticRelation::Rival, 1490));
+        relations.insert(tag("CAS"), entry(DiplomaticRelation::Ally, 1495));
+        relations.insert(tag("FRA"), entry(DiplomaticRelation::Ally, 1490));
+
+        // Established after the date
+        relations.insert(tag("ENG"), entry(DiplomaticRelation::Ally, 1501));
+
+        let relation = |x: &str| relations.relations.get(&tag(x)).map(|x| x.relation);
+        assert_eq!(relation("SCO"), Some(DiplomaticRelation::Subject));
+        assert_eq!(relation("CAS"), Some(DiplomaticRelation::Ally));
+        assert_eq!(relation("FRA"), Some(DiplomaticRelation::Selected));
+        assert_eq!(relation("ENG"), None);
+    }
+
+    #[test]
+    fn test_relation_inheritance() {
+        let mut relations = DiplomaticRelations::new(tag("FRA"), Eu4Date::from_ymd(1500, 1, 1));
+        relations.insert(tag("ENG"), entry(DiplomaticRelation::AtWar, 1490));
+        relations.insert(tag("WLS"), entry(DiplomaticRelation::Ally, 1490));
+        relations.inherit(
+            [
+                (tag("ENG"), tag("IRE")),
+                (tag("ENG"), tag("WLS")),
+                (tag("CAS"), tag("GRA")),
+            ]
+            .into_iter(),
+        );
+
+        let ire = relations.relations.get(&tag("IRE")).unwrap();
+        assert_eq!(ire.relation, DiplomaticRelation::AtWar);
+        assert_eq!(ire.via, Some(tag("ENG")));
+
+        // Subjects keep their own relation
+        let wls = relations.relations.get(&tag("WLS")).unwrap();
+        assert_eq!(wls.relation, DiplomaticRelation::Ally);
+        assert_eq!(wls.via, None);
+
+        // Overlords without a relation have nothing to pass on
+        assert!(!relations.relations.contains_key(&tag("GRA")));
+    }
+
+    #[test]
+    fn test_previous_war_relation() {
+        let at = Eu4Date::from_ymd(1500, 1, 1);
+        let start = Eu4Date::from_ymd(1480, 1, 1);
+
+        let ended = Eu4Date::from_ymd(1494, 12, 31);
+        assert_eq!(previous_war_relation(start, ended, at), None);
+
+        let ended = Eu4Date::from_ymd(1495, 6, 1);
+        let expected = Some((DiplomaticRelation::Truce, ended));
+        assert_eq!(previous_war_relation(start, ended, at), expected);
+
+        let ended = Eu4Date::from_ymd(1501, 1, 1);
+        let expected = Some((DiplomaticRelation::AtWar, start));
+        assert_eq!(previous_war_relation(start, ended, at), expected);
+    }
+
+    #[test]
+    fn test_timelapse_sides() {
+        let err = timelapse_sides("Unknown War", None).unwrap_err();
+        assert!(matches!(err, MapCursorError::UnknownWar(x) if x == "Unknown War"));
+
+        let participants = HashMap::from([
+            (
+                tag("FRA"),
+                ParticipantSide {
+                    side: WarSide::Attacker,
+                    active: true,
+                },
+            ),
+            (
+                tag("ENG"),
+                ParticipantSide {
+                    side: WarSide::Defender,
+                    active: false,
+                },
+            ),
+        ]);
+        let sides = timelapse_sides("Anglo-French War", Some(participants)).unwrap();
+        let expected = HashMap::from([
+            (tag("FRA"), WarSide::Attacker),
+            (tag("ENG"), WarSide::Defender),
+        ]);
+        assert_eq!(sides, expected);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{hex_color, map::DiplomaticRelation};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PaletteName {
+    #[default]
+    Default,
+
+    /// Okabe-Ito categories and viridis gradients, which remain
+    /// distinguishable under all common forms of color vision deficiency
+    Colorblind,
+
+    /// Saturated colors on dark backgrounds for low vision
+    HighContrast,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PalettePayload {
+    #[serde(default)]
+    pub name: PaletteName,
+
+    /// Color stops that override the palette's gradient for continuous map
+    /// modes (development and technology). Battles keep the palette's heat
+    /// gradient so that losses remain distinguishable from development.
+    #[serde(default)]
+    pub gradient: Option<Vec<[u8; 3]>>,
+}
+
+/// Colors sampled evenly along a line of color stops
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    stops: Vec<[u8; 3]>,
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<[u8; 3]>) -> Option<Self> {
+        if stops.is_empty() {
+            None
+        } else {
+            Some(Self { stops })
+        }
+    }
+
+    /// Linearly interpolates between the stops that surround the ratio,
+    /// which is clamped between 0 and 1
+    pub fn sample(&self, ratio: f64) -> [u8; 3] {
+        let ratio = if ratio.is_nan() {
+            0.0
+        } else {
+            ratio.clamp(0.0, 1.0)
+        };
+
+        let segments = self.stops.len() - 1;
+        if segments == 0 {
+            return self.stops[0];
+        }
+
+        let position = ratio * segments as f64;
+        let index = (position.floor() as usize).min(segments - 1);
+        let t = position - index as f64;
+        let (from, to) = (self.stops[index], self.stops[index + 1]);
+        [
+            (f64::from(from[0]) + t * (f64::from(to[0]) - f64::from(from[0]))).round() as u8,
+            (f64::from(from[1]) + t * (f64::from(to[1]) - f64::from(from[1]))).round() as u8,
+            (f64::from(from[2]) + t * (f64::from(to[2]) - f64::from(from[2]))).round() as u8,
+        ]
+    }
+
+    pub fn hex_stops(&self) -> Vec<String> {
+        self.stops.iter().map(|x| hex_color(*x)).collect()
+    }
+}
+
+/// The colors that are not sourced from the save (like country and religion
+/// colors) used to paint the map
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub name: PaletteName,
+
+    /// Uninhabitable provinces. Transparent so terrain shows through
+    pub wasteland: [u8; 4],
+
+    /// Habitable provinces without an owner
+    pub unowned: [u8; 4],
+
+    /// Owned provinces that are filtered out by the tag filter
+    pub excluded: [u8; 4],
+
+    /// Marks provinces that saw a battle since the last interval
+    pub battle: [u8; 4],
+
+    /// Sides of a war
+    pub attacker: [u8; 4],
+    pub defender: [u8; 4],
+
+    /// Continuous modes like development and technology
+    pub gradient: Gradient,
+
+    /// Battle losses
+    pub heat: Gradient,
+}
+
+impl Palette {
+    pub fn new(name: PaletteName) -> Self {
+        match name {
+            PaletteName::Default => Palette {
+                name,
+                wasteland: [61, 61, 61, 0],
+                unowned: [94, 94, 94, 128],
+                excluded: [106, 108, 128, 255],
+                battle: [15, 23, 42, 255],
+                attacker: [220, 38, 38, 255],
+                defender: [37, 99, 235, 255],
+                gradient: Gradient {
+                    stops: vec![[127, 0, 0], [0, 212, 144]],
+                },
+                heat: Gradient {
+                    stops: vec![[203, 213, 225], [244, 63, 94]],
+                },
+            },
+            PaletteName::Colorblind => Palette {
+                name,
+                wasteland: [61, 61, 61, 0],
+                unowned: [94, 94, 94, 128],
+                excluded: [106, 108, 128, 255],
+                battle: [0, 0, 0, 255],
+                attacker: [213, 94, 0, 255],
+                defender: [0, 114, 178, 255],
+                gradient: Gradient {
+                    stops: vec![
+                        [68, 1, 84],
+                        [59, 82, 139],
+                        [33, 145, 140],
+                        [94, 201, 98],
+                        [253, 231, 37],
+                    ],
+                },
+                heat: Gradient {
+                    stops: vec![
+                        [252, 253, 191],
+                        [252, 137, 97],
+                        [183, 55, 121],
+                        [81, 18, 124],
+                    ],
+                },
+            },
+            PaletteName::HighContrast => Palette {
+                name,
+                wasteland: [0, 0, 0, 0],
+                unowned: [160, 160, 160, 160],
+                excluded: [48, 48, 48, 255],
+                battle: [255, 255, 255, 255],
+                attacker: [255, 0, 0, 255],
+                defender: [0, 64, 255, 255],
+                gradient: Gradient {
+                    stops: vec![[0, 0, 160], [255, 255, 0]],
+                },
+                heat: Gradient {
+                    stops: vec![[64, 64, 64], [255, 0, 0]],
+                },
+            },
+        }
+    }
+
+    pub fn from_payload(payload: &PalettePayload) -> Self {
+        let mut result = Palette::new(payload.name);
+        if let Some(gradient) = payload.gradient.clone().and_then(Gradient::new) {
+            result.gradient = gradient;
+        }
+        result
+    }
+
+    pub fn relation(&self, relation: DiplomaticRelation) -> [u8; 4] {
+        use DiplomaticRelation::*;
+        match self.name {
+            PaletteName::Default => match relation {
+                Selected => [250, 204, 21, 255],
+                Overlord => [147, 51, 234, 255],
+                Subject => [20, 184, 166, 255],
+                AtWar => [220, 38, 38, 255],
+                Ally => [37, 99, 235, 255],
+                Guaranteed => [34, 197, 94, 255],
+                Rival => [234, 88, 12, 255],
+                Truce => [253, 230, 138, 255],
+                Neutral => [106, 108, 128, 255],
+            },
+            PaletteName::Colorblind => match relation {
+                Selected => [240, 228, 66, 255],
+                Overlord => [204, 121, 167, 255],
+                Subject => [0, 158, 115, 255],
+                AtWar => [213, 94, 0, 255],
+                Ally => [0, 114, 178, 255],
+                Guaranteed => [86, 180, 233, 255],
+                Rival => [230, 159, 0, 255],
+                Truce => [230, 230, 230, 255],
+                Neutral => [106, 108, 128, 255],
+            },
+            PaletteName::HighContrast => match relation {
+                Selected => [255, 255, 0, 255],
+                Overlord => [255, 0, 255, 255],
+                Subject => [0, 255, 255, 255],
+                AtWar => [255, 0, 0, 255],
+                Ally => [0, 64, 255, 255],
+                Guaranteed => [0, 255, 0, 255],
+                Rival => [255, 128, 0, 255],
+                Truce => [255, 255, 255, 255],
+                Neutral => [48, 48, 48, 255],
+            },
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new(PaletteName::Default)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LegendEntry {
+    pub key: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LegendGradient {
+    pub min: f64,
+    pub max: f64,
+    pub stops: Vec<String>,
+}
+
+/// Describes the colors painted by a map mode so the frontend can render a
+/// key. Colors sourced from the save (countries, religions) are omitted.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MapLegend {
+    pub entries: Vec<LegendEntry>,
+    pub gradient: Option<LegendGradient>,
+}
+
+impl MapLegend {
+    pub fn new(palette: &Palette) -> Self {
+        let mut result = MapLegend::default();
+        result.push("excluded", palette.excluded);
+        result.push("unowned", palette.unowned);
+        result
+    }
+
+    pub fn push(&mut self, key: &str, color: [u8; 4]) {
+        self.entries.push(LegendEntry {
+            key: String::from(key),
+            color: hex_color([color[0], color[1], color[2]]),
+        });
+    }
+
+    pub fn with_gradient(mut self, gradient: &Gradient, min: f64, max: f64) -> Self {
+        self.gradient = Some(LegendGradient {
+            min,
+            max,
+            stops: gradient.hex_stops(),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_endpoints() {
+        let gradient = Gradient::new(vec![[0, 0, 0], [100, 200, 50], [200, 0, 250]]).unwrap();
+        assert_eq!(gradient.sample(0.0), [0, 0, 0]);
+        assert_eq!(gradient.sample(0.5), [100, 200, 50]);
+        assert_eq!(gradient.sample(1.0), [200, 0, 250]);
+        assert_eq!(gradient.sample(0.25), [50, 100, 25]);
+        assert_eq!(gradient.sample(0.75), [150, 100, 150]);
+    }
+
+    #[test]
+    fn test_gradient_out_of_range() {
+        let gradient = Gradient::new(vec![[10, 20, 30], [110, 120, 130]]).unwrap();
+        assert_eq!(gradient.sample(-1.0), [10, 20, 30]);
+        assert_eq!(gradient.sample(2.0), [110, 120, 130]);
+        assert_eq!(gradient.sample(f64::NAN), [10, 20, 30]);
+        assert_eq!(gradient.sample(f64::INFINITY), [110, 120, 130]);
+        assert_eq!(gradient.sample(f64::NEG_INFINITY), [10, 20, 30]);
+    }
+
+    #[test]
+    fn test_gradient_single_stop() {
+        let gradient = Gradient::new(vec![[1, 2, 3]]).unwrap();
+        for ratio in [0.0, 0.5, 1.0, -1.0, 2.0] {
+            assert_eq!(gradient.sample(ratio), [1, 2, 3]);
+        }
+
+        assert!(Gradient::new(Vec::new()).is_none());
+    }
+}
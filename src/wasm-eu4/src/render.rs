@@ -0,0 +1,487 @@
+use crate::{map::MapPayload, map_fill_borders, SaveFileImpl};
+use eu4save::CountryTag;
+use pdxmap::BORDER_COLOR;
+use serde::{Deserialize, Serialize};
+
+pub const MAP_WIDTH: u32 = 5632;
+pub const MAP_HEIGHT: u32 = 2048;
+
+/// Rendered images may upscale the map up to twice its size in either
+/// dimension. Anything larger exhausts the memory available to wasm.
+pub const MAX_RENDER_WIDTH: u32 = MAP_WIDTH * 2;
+pub const MAX_RENDER_HEIGHT: u32 = MAP_HEIGHT * 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("expected raster of {expected} pixels but received {actual}")]
+    InvalidRaster { expected: usize, actual: usize },
+
+    #[error("expected terrain of {expected} bytes but received {actual}")]
+    InvalidTerrain { expected: usize, actual: usize },
+
+    #[error("raster references color index {index} but only {colors} colors exist")]
+    InvalidColorIndex { index: u16, colors: usize },
+
+    #[error("crop region is empty or outside of the map")]
+    EmptyCrop,
+
+    #[error("image dimensions of {width}x{height} exceed the maximum of {max_width}x{max_height}")]
+    TooLarge {
+        width: u32,
+        height: u64,
+        max_width: u32,
+        max_height: u32,
+    },
+
+    #[error("no provinces owned by {0} to fit the map to")]
+    EmptyRealm(CountryTag),
+
+    #[error("unable to encode png: {0}")]
+    Png(#[from] png::EncodingError),
+
+    #[error("unable to encode webp: {0}")]
+    Webp(#[from] image_webp::EncodingError),
+
+    #[error("unable to encode gif: {0}")]
+    Gif(#[from] gif::EncodingError),
+
+    #[error("gif dimensions of {width}x{height} exceed the format's limits")]
+    GifTooLarge { width: u32, height: u32 },
+
+    #[error("timelapse has no frames")]
+    EmptyTimelapse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageFormat {
+    Png,
+    Webp,
+}
+
+/// Mirrors the fill modes accepted by `map_fill_borders`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderFill {
+    None,
+    Provinces,
+    Countries,
+}
+
+impl BorderFill {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BorderFill::None => "None",
+            BorderFill::Provinces => "Provinces",
+            BorderFill::Countries => "Countries",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRegion {
+    /// The region is non-empty and lies within the map
+    pub fn within_map(&self) -> bool {
+        let right = self.x.checked_add(self.width);
+        let bottom = self.y.checked_add(self.height);
+        self.width > 0
+            && self.height > 0
+            && right.map_or(false, |x| x <= MAP_WIDTH)
+            && bottom.map_or(false, |y| y <= MAP_HEIGHT)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MapCrop {
+    Full,
+    Region(CropRegion),
+
+    /// Fit to the provinces owned by the country and its subjects. Defaults
+    /// to the player's country.
+    Realm {
+        tag: Option<CountryTag>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderOptions {
+    pub format: ImageFormat,
+    pub fill: BorderFill,
+    pub crop: MapCrop,
+
+    /// Width of the encoded image. The height is derived from the crop
+    /// region's aspect ratio. Defaults to the crop region's width.
+    pub width: Option<u32>,
+}
+
+/// The static map inputs required to render a map outside of the browser
+pub struct MapRaster<'a> {
+    // Each pixel is the color index of the province (see color-index.bin)
+    color_indices: &'a [u16],
+
+    // Optional RGBA terrain that shows through translucent provinces
+    terrain: Option<&'a [u8]>,
+}
+
+impl<'a> MapRaster<'a> {
+    /// Validates the raster against the number of province colors that
+    /// `map_colors` produces so that painting can't index out of bounds
+    pub fn new(
+        color_indices: &'a [u16],
+        terrain: Option<&'a [u8]>,
+        colors: usize,
+    ) -> Result<Self, RenderError> {
+        let pixels = (MAP_WIDTH * MAP_HEIGHT) as usize;
+        if color_indices.len() != pixels {
+            return Err(RenderError::InvalidRaster {
+                expected: pixels,
+                actual: color_indices.len(),
+            });
+        }
+
+        if let Some(&index) = color_indices.iter().max() {
+            if usize::from(index) >= colors {
+                return Err(RenderError::InvalidColorIndex { index, colors });
+            }
+        }
+
+        if let Some(terrain) = terrain {
+            if terrain.len() != pixels * 4 {
+                return Err(RenderError::InvalidTerrain {
+                    expected: pixels * 4,
+                    actual: terrain.len(),
+                });
+            }
+        }
+
+        Ok(Self {
+            color_indices,
+            terrain,
+        })
+    }
+
+    /// The color index of the pixel, where the origin is the top left
+    pub fn color_index_at(&self, x: u32, y: u32) -> Option<u16> {
+        if x >= MAP_WIDTH || y >= MAP_HEIGHT {
+            return None;
+        }
+
+        self.color_indices
+            .get((y * MAP_WIDTH + x) as usize)
+            .copied()
+    }
+
+    /// Paints the primary and secondary RGBA province colors (as returned
+    /// by map_colors) onto a full size RGBA canvas
+    pub fn paint(&self, primary: &[u8], secondary: &[u8], fill: BorderFill) -> Vec<u8> {
+        let rgb = |colors: &[u8]| -> Vec<u8> {
+            colors
+                .chunks_exact(4)
+                .flat_map(|x| [x[0], x[1], x[2]])
+                .collect()
+        };
+
+        let primary_rgb = rgb(primary);
+        let secondary_rgb = rgb(secondary);
+        let mut data = vec![0u8; self.color_indices.len() * 4];
+        map_fill_borders(
+            &mut data,
+            self.color_indices,
+            &primary_rgb,
+            &secondary_rgb,
+            fill.as_str(),
+        );
+
+        let Some(terrain) = self.terrain else {
+            return data;
+        };
+        for ((pixel, terrain), &index) in data
+            .chunks_exact_mut(4)
+            .zip(terrain.chunks_exact(4))
+            .zip(self.color_indices)
+        {
+            let alpha = primary
+                .get(usize::from(index) * 4 + 3)
+                .copied()
+                .map_or(255, u16::from);
+
+            if alpha == 255 || pixel[..3] == BORDER_COLOR {
+                continue;
+            }
+
+            for (c, t) in pixel[..3].iter_mut().zip(terrain) {
+                *c = ((u16::from(*c) * alpha + u16::from(*t) * (255 - alpha)) / 255) as u8;
+            }
+        }
+
+        data
+    }
+}
+
+impl SaveFileImpl {
+    /// The raster validated against the province colors of this save
+    pub fn map_raster<'a>(
+        &self,
+        color_indices: &'a [u16],
+        terrain: Option<&'a [u8]>,
+    ) -> Result<MapRaster<'a>, RenderError> {
+        MapRaster::new(
+            color_indices,
+            terrain,
+            self.province_id_to_color_index.len(),
+        )
+    }
+
+    pub fn render_map(
+        &self,
+        payload: MapPayload,
+        raster: &MapRaster,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, RenderError> {
+        let crop = self.crop_region(&options.crop)?;
+        let colors = self.map_colors(payload).data();
+        let result_len = self.province_id_to_color_index.len() * 4;
+        let (primary, rest) = colors.split_at(result_len);
+        let secondary = &rest[..result_len];
+
+        let canvas = raster.paint(primary, secondary, options.fill);
+        let width = options.width.unwrap_or(crop.width);
+        let image = crop_and_scale(&canvas, crop, width)?;
+        encode_image(&image, width, options.format)
+    }
+
+    pub(crate) fn crop_region(&self, crop: &MapCrop) -> Result<CropRegion, RenderError> {
+        let region = match crop {
+            MapCrop::Full => CropRegion {
+                x: 0,
+                y: 0,
+                width: MAP_WIDTH,
+                height: MAP_HEIGHT,
+            },
+            MapCrop::Region(region) => *region,
+            MapCrop::Realm { tag } => {
+                let tag = tag.unwrap_or(self.query.save().meta.player);
+                self.realm_bounds(&tag)
+                    .ok_or(RenderError::EmptyRealm(tag))?
+            }
+        };
+
+        if region.within_map() {
+            Ok(region)
+        } else {
+            Err(RenderError::EmptyCrop)
+        }
+    }
+}
+
+/// Crops a full size RGBA canvas and resizes it with nearest neighbor
+/// sampling so that it is `width` pixels wide
+pub fn crop_and_scale(canvas: &[u8], crop: CropRegion, width: u32) -> Result<Vec<u8>, RenderError> {
+    if !crop.within_map() {
+        return Err(RenderError::EmptyCrop);
+    }
+
+    let height = scaled_height(crop, width)?;
+    let (width, height) = (width as usize, height as usize);
+    let (crop_x, crop_y) = (crop.x as usize, crop.y as usize);
+    let (crop_width, crop_height) = (crop.width as usize, crop.height as usize);
+    let map_width = MAP_WIDTH as usize;
+
+    let len = width
+        .checked_mul(height)
+        .and_then(|x| x.checked_mul(4))
+        .ok_or(RenderError::TooLarge {
+            width: width as u32,
+            height: height as u64,
+            max_width: MAX_RENDER_WIDTH,
+            max_height: MAX_RENDER_HEIGHT,
+        })?;
+
+    let mut result = Vec::with_capacity(len);
+    for y in 0..height {
+        let src_y = crop_y + y * crop_height / height;
+        for x in 0..width {
+            let src_x = crop_x + x * crop_width / width;
+            let offset = (src_y * map_width + src_x) * 4;
+            result.extend_from_slice(&canvas[offset..offset + 4]);
+        }
+    }
+
+    Ok(result)
+}
+
+/// The height of an image that is `width` pixels wide and preserves the
+/// aspect ratio of the crop region. Errors when either dimension exceeds
+/// the render limits.
+pub fn scaled_height(crop: CropRegion, width: u32) -> Result<u32, RenderError> {
+    if width == 0 || crop.width == 0 || crop.height == 0 {
+        return Err(RenderError::EmptyCrop);
+    }
+
+    let height = (u64::from(crop.height) * u64::from(width) / u64::from(crop.width)).max(1);
+    match u32::try_from(height) {
+        Ok(height) if width <= MAX_RENDER_WIDTH && height <= MAX_RENDER_HEIGHT => Ok(height),
+        _ => Err(RenderError::TooLarge {
+            width,
+            height,
+            max_width: MAX_RENDER_WIDTH,
+            max_height: MAX_RENDER_HEIGHT,
+        }),
+    }
+}
+
+/// Encodes RGBA data that is `width` pixels wide into the desired format
+pub fn encode_image(data: &[u8], width: u32, format: ImageFormat) -> Result<Vec<u8>, RenderError> {
+    let height = (data.len() / 4) as u32 / width;
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::Png => {
+            let mut encoder = png::Encoder::new(&mut out, width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(data)?;
+            writer.finish()?;
+        }
+        ImageFormat::Webp => {
+            image_webp::WebPEncoder::new(&mut out).encode(
+                data,
+                width,
+                height,
+                image_webp::ColorType::Rgba8,
+            )?;
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIXELS: usize = (MAP_WIDTH * MAP_HEIGHT) as usize;
+
+    fn region(x: u32, y: u32, width: u32, height: u32) -> CropRegion {
+        CropRegion {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_raster_validation() {
+        let indices = vec![0u16; PIXELS];
+        assert!(MapRaster::new(&indices, None, 1).is_ok());
+
+        let short = &indices[..PIXELS - 1];
+        assert!(matches!(
+            MapRaster::new(short, None, 1),
+            Err(RenderError::InvalidRaster { actual, .. }) if actual == PIXELS - 1
+        ));
+
+        let mut out_of_range = indices.clone();
+        out_of_range[PIXELS / 2] = 3;
+        assert!(matches!(
+            MapRaster::new(&out_of_range, None, 3),
+            Err(RenderError::InvalidColorIndex {
+                index: 3,
+                colors: 3
+            })
+        ));
+        assert!(MapRaster::new(&out_of_range, None, 4).is_ok());
+
+        let terrain = vec![0u8; PIXELS * 3];
+        assert!(matches!(
+            MapRaster::new(&indices, Some(&terrain), 1),
+            Err(RenderError::InvalidTerrain { .. })
+        ));
+    }
+
+    #[test]
+    fn test_color_index_at() {
+        let mut indices = vec![0u16; PIXELS];
+        indices[MAP_WIDTH as usize + 2] = 1;
+        let raster = MapRaster::new(&indices, None, 2).unwrap();
+        assert_eq!(raster.color_index_at(2, 1), Some(1));
+        assert_eq!(raster.color_index_at(0, 0), Some(0));
+        assert_eq!(raster.color_index_at(MAP_WIDTH, 0), None);
+        assert_eq!(raster.color_index_at(0, MAP_HEIGHT), None);
+    }
+
+    #[test]
+    fn test_crop_within_map() {
+        assert!(region(0, 0, MAP_WIDTH, MAP_HEIGHT).within_map());
+        assert!(!region(0, 0, 0, 10).within_map());
+        assert!(!region(0, 0, 10, 0).within_map());
+        assert!(!region(1, 0, MAP_WIDTH, 10).within_map());
+        assert!(!region(0, MAP_HEIGHT, 10, 1).within_map());
+        assert!(!region(u32::MAX, 0, 1, 1).within_map());
+    }
+
+    #[test]
+    fn test_scaled_height() {
+        let crop = region(0, 0, 200, 100);
+        assert_eq!(scaled_height(crop, 400).unwrap(), 200);
+        assert_eq!(scaled_height(crop, 1).unwrap(), 1);
+        assert!(matches!(
+            scaled_height(crop, 0),
+            Err(RenderError::EmptyCrop)
+        ));
+        assert!(matches!(
+            scaled_height(region(0, 0, 0, 100), 10),
+            Err(RenderError::EmptyCrop)
+        ));
+        assert!(matches!(
+            scaled_height(crop, MAX_RENDER_WIDTH + 1),
+            Err(RenderError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            scaled_height(region(0, 0, 1, MAP_HEIGHT), 10),
+            Err(RenderError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_crop_and_scale() {
+        let mut canvas = vec![0u8; PIXELS * 4];
+        for (i, pixel) in canvas
+            .chunks_exact_mut(4)
+            .enumerate()
+            .take(MAP_WIDTH as usize * 3)
+        {
+            let (x, y) = (i % MAP_WIDTH as usize, i / MAP_WIDTH as usize);
+            pixel.copy_from_slice(&[x as u8, y as u8, 0, 255]);
+        }
+
+        let image = crop_and_scale(&canvas, region(1, 1, 2, 2), 4).unwrap();
+        let pixels: Vec<_> = image.chunks_exact(4).map(|x| (x[0], x[1])).collect();
+        #[rustfmt::skip]
+        let expected = [
+            (1, 1), (1, 1), (2, 1), (2, 1),
+            (1, 1), (1, 1), (2, 1), (2, 1),
+            (1, 2), (1, 2), (2, 2), (2, 2),
+            (1, 2), (1, 2), (2, 2), (2, 2),
+        ];
+        assert_eq!(pixels, expected);
+
+        assert!(matches!(
+            crop_and_scale(&canvas, region(0, 0, 0, 0), 4),
+            Err(RenderError::EmptyCrop)
+        ));
+        assert!(matches!(
+            crop_and_scale(&canvas, region(MAP_WIDTH - 1, 0, 2, 1), 4),
+            Err(RenderError::EmptyCrop)
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    render::{CropRegion, MapRaster, MAP_HEIGHT, MAP_WIDTH},
+    LocalizedTag, SaveFileImpl,
+};
+use eu4save::{CountryTag, ProvinceId};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvinceLocation {
+    pub id: ProvinceId,
+    pub name: String,
+    pub owner: Option<LocalizedTag>,
+    pub center_x: u16,
+    pub center_y: u16,
+}
+
+impl SaveFileImpl {
+    /// Provinces whose localized name fuzzily matches the query, best
+    /// matches first. Matching ignores case and common diacritics.
+    pub fn search_provinces(&self, query: &str, limit: usize) -> Vec<ProvinceLocation> {
+        let needle = normalize(query);
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<_> = self
+            .query
+            .save()
+            .game
+            .provinces
+            .iter()
+            .filter_map(|(id, prov)| {
+                let name = self.game.localize_province(id).unwrap_or(&prov.name);
+                let score = fuzzy_score(&needle, &normalize(name))?;
+                Some((score, name.len(), id.as_u16()))
+            })
+            .collect();
+
+        matches.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        matches
+            .into_iter()
+            .filter_map(|(_, _, id)| self.province_location(ProvinceId::from(i32::from(id))))
+            .take(limit)
+            .collect()
+    }
+
+    /// Provinces whose center lies within the region
+    pub fn provinces_in_bounds(&self, bounds: CropRegion) -> Vec<ProvinceLocation> {
+        let x_range = bounds.x..bounds.x.saturating_add(bounds.width);
+        let y_range = bounds.y..bounds.y.saturating_add(bounds.height);
+        let mut result: Vec<_> = self
+            .game
+            .provinces()
+            .filter(|prov| {
+                x_range.contains(&u32::from(prov.center_x))
+                    && y_range.contains(&u32::from(prov.center_y))
+            })
+            .filter_map(|prov| self.province_location(prov.id))
+            .collect();
+
+        result.sort_unstable_by_key(|x| x.id.as_u16());
+        result
+    }
+
+    /// The province under the map pixel, or none for the void around the
+    /// map and for pixels outside of it
+    pub fn province_at(&self, raster: &MapRaster, x: u32, y: u32) -> Option<ProvinceId> {
+        raster
+            .color_index_at(x, y)
+            .and_then(|index| self.province_at_color_index(index))
+    }
+
+    /// The province painted with the color index. Callers that already
+    /// resolved a pixel to its color index with `MapRaster::color_index_at`
+    /// can skip straight to this lookup
+    pub fn province_at_color_index(&self, color_index: u16) -> Option<ProvinceId> {
+        province_with_color_index(&self.color_index_to_province_id, color_index)
+    }
+
+    /// The region enclosing the province centers of the country and its
+    /// subjects, padded so that border provinces aren't flush with the edge
+    pub fn realm_bounds(&self, tag: &CountryTag) -> Option<CropRegion> {
+        let mut realm: HashSet<CountryTag> = HashSet::new();
+        realm.insert(*tag);
+        if let Some(country) = self.query.country(tag) {
+            realm.extend(country.subjects.iter());
+        }
+
+        let centers = self
+            .query
+            .save()
+            .game
+            .provinces
+            .iter()
+            .filter(|(_, prov)| prov.owner.as_ref().map_or(false, |x| realm.contains(x)))
+            .filter_map(|(id, _)| self.game.get_province(id))
+            .map(|prov| (u32::from(prov.center_x), u32::from(prov.center_y)));
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+        for (x, y) in centers {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+
+        if min_x > max_x {
+            return None;
+        }
+
+        // Centers don't capture the extent of a province so pad generously
+        let pad_x = ((max_x - min_x) / 10).max(64);
+        let pad_y = ((max_y - min_y) / 10).max(64);
+        let x = min_x.saturating_sub(pad_x);
+        let y = min_y.saturating_sub(pad_y);
+        Some(CropRegion {
+            x,
+            y,
+            width: (max_x + pad_x + 1).min(MAP_WIDTH) - x,
+            height: (max_y + pad_y + 1).min(MAP_HEIGHT) - y,
+        })
+    }
+
+    fn province_location(&self, id: ProvinceId) -> Option<ProvinceLocation> {
+        let prov = self.query.save().game.provinces.get(&id)?;
+        let game_prov = self.game.get_province(&id)?;
+        let name = self.game.localize_province(&id).unwrap_or(&prov.name);
+        Some(ProvinceLocation {
+            id,
+            name: String::from(name),
+            owner: prov.owner.map(|x| self.localize_tag(x)),
+            center_x: game_prov.center_x,
+            center_y: game_prov.center_y,
+        })
+    }
+}
+
+/// Lowercases and strips common latin diacritics so that "Lubeck" finds
+/// "Lübeck"
+fn province_with_color_index(color_index_to_province_id: &[u16], index: u16) -> Option<ProvinceId> {
+    let id = color_index_to_province_id
+        .get(usize::from(index))
+        .copied()
+        .unwrap_or(0);
+
+    // Index zero is reserved for the black void around the map
+    (id != 0).then(|| ProvinceId::new(i32::from(id)))
+}
+
+fn normalize(s: &str) -> String {
+    s.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+            'ç' | 'ć' | 'č' => 'c',
+            'ď' | 'đ' => 'd',
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+            'ğ' => 'g',
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => 'i',
+            'ł' | 'ľ' => 'l',
+            'ñ' | 'ń' | 'ň' => 'n',
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => 'o',
+            'ř' => 'r',
+            'ś' | 'ş' | 'š' => 's',
+            'ţ' | 'ť' => 't',
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => 'u',
+            'ý' | 'ÿ' => 'y',
+            'ź' | 'ż' | 'ž' => 'z',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Scores how well the needle matches the haystack (both normalized).
+/// Prefix matches outrank matches at the start of a word, which outrank
+/// substring matches, which outrank matches of the needle's characters in
+/// order.
+fn fuzzy_score(needle: &str, haystack: &str) -> Option<u32> {
+    if haystack == needle {
+        return Some(1000);
+    }
+
+    if haystack.starts_with(needle) {
+        return Some(800);
+    }
+
+    if let Some(pos) = haystack.find(needle) {
+        let at_word = haystack[..pos]
+            .chars()
+            .last()
+            .map_or(false, |c| !c.is_alphanumeric());
+        return Some(if at_word { 600 } else { 400 });
+    }
+
+    // Subsequence match penalized by the characters skipped between matches
+    let mut gaps = 0u32;
+    let mut haystack_chars = haystack.chars();
+    for n in needle.chars() {
+        let mut found = false;
+        for h in haystack_chars.by_ref() {
+            if h == n {
+                found = true;
+                break;
+            }
+            gaps += 1;
+        }
+
+        if !found {
+            return None;
+        }
+    }
+
+    Some(200u32.saturating_sub(gaps * 5).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_province_with_color_index() {
+        // Color index 1 paints province 3 and color index 2 paints province 1
+        let lookup = [0u16, 3, 1];
+        let mut indices = vec![0u16; (MAP_WIDTH * MAP_HEIGHT) as usize];
+        indices[MAP_WIDTH as usize + 2] = 1;
+        indices[indices.len() - 1] = 2;
+        let raster = MapRaster::new(&indices, None, lookup.len()).unwrap();
+
+        let province_at = |x, y| {
+            raster
+                .color_index_at(x, y)
+                .and_then(|index| province_with_color_index(&lookup, index))
+        };
+
+        assert_eq!(province_at(2, 1), Some(ProvinceId::new(3)));
+        assert_eq!(
+            province_at(MAP_WIDTH - 1, MAP_HEIGHT - 1),
+            Some(ProvinceId::new(1))
+        );
+        assert_eq!(province_at(0, 0), None);
+        assert_eq!(province_at(MAP_WIDTH, 0), None);
+        assert_eq!(province_with_color_index(&lookup, 3), None);
+    }
+}
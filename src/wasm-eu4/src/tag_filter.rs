@@ -1,8 +1,11 @@
 use crate::{LocalizedTag, SaveFileImpl};
 use eu4game::SaveGameQuery;
-use eu4save::CountryTag;
+use eu4save::{
+    query::{CountryIncomeLedger, NationEventKind},
+    CountryTag, ProvinceId,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
 pub enum TagsState {
@@ -30,6 +33,17 @@ pub enum AiTagsState {
     None,
 }
 
+/// The metric used to decide which countries are kept when a tag filter
+/// matches more countries than a chart can display
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TagRanking {
+    #[default]
+    Development,
+    Score,
+    Income,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(tag = "kind", rename_all = "camelCase")]
 pub struct TagFilterPayloadRaw {
@@ -42,6 +56,10 @@ pub struct TagFilterPayloadRaw {
     #[serde(default)]
     exclude: Vec<String>,
     include_subjects: bool,
+    #[serde(default)]
+    expr: Option<TagFilterExpr>,
+    #[serde(default)]
+    ranking: TagRanking,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -53,6 +71,81 @@ pub struct TagFilterPayload {
     pub include: Vec<CountryTag>,
     pub exclude: Vec<CountryTag>,
     pub include_subjects: bool,
+    pub expr: Option<TagFilterExpr>,
+    pub ranking: TagRanking,
+}
+
+/// Countries that matched a tag filter after truncation
+#[derive(Debug, Clone, Default)]
+pub struct TagSelection {
+    pub tags: HashSet<CountryTag>,
+
+    /// Matching countries that didn't make the cut, best ranked first
+    pub dropped: Vec<LocalizedTag>,
+}
+
+/// A composable predicate on countries that narrows down the countries
+/// selected by the player and AI states. Countries explicitly included or
+/// excluded by tag are unaffected.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TagFilterExpr {
+    And {
+        exprs: Vec<TagFilterExpr>,
+    },
+    Or {
+        exprs: Vec<TagFilterExpr>,
+    },
+    Not {
+        expr: Box<TagFilterExpr>,
+    },
+
+    /// State religion belongs to the religion group (eg: "christian")
+    ReligionGroup {
+        group: String,
+    },
+
+    /// Primary culture belongs to the culture group (eg: "germanic")
+    CultureGroup {
+        group: String,
+    },
+
+    /// Government type (eg: "monarchy") or an enacted government reform
+    Government {
+        government: String,
+    },
+
+    MinDevelopment {
+        development: f32,
+    },
+
+    /// Within the top N great powers, ranked by their most recent score
+    GreatPowerRank {
+        max: usize,
+    },
+
+    /// Capital is on the continent
+    Continent {
+        continent: String,
+    },
+
+    /// Capital is in the region
+    Region {
+        region: String,
+    },
+
+    /// Capital is in the area
+    Area {
+        area: String,
+    },
+
+    /// Has been played by a human at any point in the campaign
+    EverPlayer,
+
+    /// Currently at war with the country
+    AtWar {
+        tag: CountryTag,
+    },
 }
 
 impl From<TagFilterPayloadRaw> for TagFilterPayload {
@@ -74,6 +167,8 @@ impl From<TagFilterPayloadRaw> for TagFilterPayload {
             include,
             exclude,
             include_subjects: x.include_subjects,
+            expr: x.expr,
+            ranking: x.ranking,
         }
     }
 }
@@ -125,6 +220,8 @@ impl SaveFileImpl {
             )
             .collect();
 
+        let expr_tags = payload.expr.as_ref().map(|x| self.evaluate_tag_expr(x));
+
         let mut tags: HashSet<CountryTag> = HashSet::new();
         tags.extend(payload.include.iter());
 
@@ -154,7 +251,9 @@ impl SaveFileImpl {
             let geographically_relevant = provs_in_subcontinent.is_empty()
                 || provs_in_subcontinent.contains(&country.capital);
 
-            if insert && geographically_relevant {
+            let expr_relevant = expr_tags.as_ref().map_or(true, |x| x.contains(tag));
+
+            if insert && geographically_relevant && expr_relevant {
                 tags.insert(*tag);
                 if payload.include_subjects {
                     tags.extend(country.subjects.iter());
@@ -168,4 +267,399 @@ impl SaveFileImpl {
 
         tags
     }
+
+    /// Keeps the top countries that match the filter. Players are ranked
+    /// ahead of AI countries, then countries are ranked by the payload's
+    /// metric with ties broken by tag so that the selection is stable
+    /// across reloads.
+    pub fn select_tags(&self, payload: &TagFilterPayload, limit: usize) -> TagSelection {
+        let players: HashSet<_> = self.all_players().into_iter().collect();
+        let scores = match payload.ranking {
+            TagRanking::Score => self.latest_scores(),
+            _ => HashMap::new(),
+        };
+
+        let ranked: Vec<_> = self
+            .matching_tags(payload)
+            .into_iter()
+            .map(|tag| (tag, self.tag_metric(&tag, payload.ranking, &scores)))
+            .collect();
+
+        let (kept, dropped) = rank_tags(ranked, &players, limit);
+        TagSelection {
+            tags: kept.into_iter().collect(),
+            dropped: dropped
+                .into_iter()
+                .map(|tag| self.localize_tag(tag))
+                .collect(),
+        }
+    }
+
+    fn tag_metric(
+        &self,
+        tag: &CountryTag,
+        ranking: TagRanking,
+        scores: &HashMap<CountryTag, f32>,
+    ) -> f64 {
+        let Some(country) = self.query.country(tag) else {
+            return f64::MIN;
+        };
+
+        match ranking {
+            TagRanking::Development => f64::from(country.development),
+            TagRanking::Income => {
+                f64::from(income_total(&self.query.country_income_breakdown(country)))
+            }
+            TagRanking::Score => scores.get(tag).map_or(f64::MIN, |x| f64::from(*x)),
+        }
+    }
+
+    /// Evaluates the expression into the set of countries that satisfy it
+    pub fn evaluate_tag_expr(&self, expr: &TagFilterExpr) -> HashSet<CountryTag> {
+        let countries = self.query.save().game.countries.iter();
+        let universe: HashSet<_> = countries.map(|(tag, _)| *tag).collect();
+        compose_tag_expr(expr, &universe, &|x| self.evaluate_tag_predicate(x))
+    }
+
+    /// Evaluates a single predicate of an expression, leaving the boolean
+    /// composition to `compose_tag_expr`
+    fn evaluate_tag_predicate(&self, expr: &TagFilterExpr) -> HashSet<CountryTag> {
+        let countries = &self.query.save().game.countries;
+        let select = |f: &dyn Fn(&CountryTag, &eu4save::models::Country) -> bool| {
+            countries
+                .iter()
+                .filter(|(tag, country)| f(tag, country))
+                .map(|(tag, _)| *tag)
+                .collect::<HashSet<_>>()
+        };
+
+        let capital_in = |provinces: HashSet<ProvinceId>| {
+            select(&|_, country| provinces.contains(&country.capital))
+        };
+
+        match expr {
+            TagFilterExpr::And { .. } | TagFilterExpr::Or { .. } | TagFilterExpr::Not { .. } => {
+                self.evaluate_tag_expr(expr)
+            }
+            TagFilterExpr::ReligionGroup { group } => select(&|_, country| {
+                country
+                    .religion
+                    .as_ref()
+                    .and_then(|x| self.game.religion(x))
+                    .and_then(|x| x.group)
+                    .map_or(false, |x| x == group)
+            }),
+            TagFilterExpr::CultureGroup { group } => {
+                let cultures: HashSet<_> = self
+                    .game
+                    .culture_group_cultures(group)
+                    .map(|x| x.collect())
+                    .unwrap_or_default();
+                select(&|_, country| {
+                    country
+                        .primary_culture
+                        .as_ref()
+                        .map_or(false, |x| cultures.contains(x.as_str()))
+                })
+            }
+            TagFilterExpr::Government { government } => select(&|_, country| {
+                country.government.as_ref().map_or(false, |x| {
+                    &x.government == government
+                        || x.reform_stack.reforms.iter().any(|y| y == government)
+                })
+            }),
+            TagFilterExpr::MinDevelopment { development } => {
+                select(&|_, country| country.development >= *development)
+            }
+            TagFilterExpr::GreatPowerRank { max } => {
+                let scores = self.latest_scores();
+                let score = |tag: &CountryTag| scores.get(tag).copied().unwrap_or(f32::MIN);
+                let mut ranked: Vec<_> = countries
+                    .iter()
+                    .filter(|(_, country)| country.is_great_power)
+                    .map(|(tag, _)| *tag)
+                    .collect();
+                ranked.sort_by(|a, b| score(b).total_cmp(&score(a)).then_with(|| a.cmp(b)));
+                ranked.truncate(*max);
+                ranked.into_iter().collect()
+            }
+            TagFilterExpr::Continent { continent } => capital_in(
+                self.game
+                    .continent_provinces(continent)
+                    .map(|x| x.collect())
+                    .unwrap_or_default(),
+            ),
+            TagFilterExpr::Region { region } => capital_in(
+                self.game
+                    .region_areas(region)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|area| self.game.area_provinces(area))
+                    .flatten()
+                    .collect(),
+            ),
+            TagFilterExpr::Area { area } => capital_in(
+                self.game
+                    .area_provinces(area)
+                    .map(|x| x.collect())
+                    .unwrap_or_default(),
+            ),
+            TagFilterExpr::EverPlayer => self
+                .player_histories
+                .iter()
+                .flat_map(|x| {
+                    x.history
+                        .events
+                        .iter()
+                        .filter_map(|evt| match evt.kind {
+                            NationEventKind::TagSwitch(x) => Some(x),
+                            _ => None,
+                        })
+                        .chain(std::iter::once(x.history.initial))
+                })
+                .collect(),
+            TagFilterExpr::AtWar { tag } => self.current_enemies(tag),
+        }
+    }
+
+    /// Countries on the opposing side of the country's active wars
+    fn current_enemies(&self, tag: &CountryTag) -> HashSet<CountryTag> {
+        self.query
+            .save()
+            .game
+            .active_wars
+            .iter()
+            .flat_map(|war| self.war_enemies(&war.name, &war.history, tag, true))
+            .collect()
+    }
+
+    /// The score of each country in the latest year of its score ledger
+    pub(crate) fn latest_scores(&self) -> HashMap<CountryTag, f32> {
+        self.nation_events
+            .iter()
+            .filter_map(|events| {
+                self.query
+                    .score_statistics_ledger(events)
+                    .into_iter()
+                    .max_by_key(|x| x.year)
+                    .map(|x| (events.stored, x.value))
+            })
+            .collect()
+    }
+}
+
+/// Combines the countries that satisfy each predicate according to the
+/// boolean operators of the expression. An empty conjunction is satisfied
+/// by every country in the universe while an empty disjunction is
+/// satisfied by none.
+fn compose_tag_expr(
+    expr: &TagFilterExpr,
+    universe: &HashSet<CountryTag>,
+    predicate: &dyn Fn(&TagFilterExpr) -> HashSet<CountryTag>,
+) -> HashSet<CountryTag> {
+    match expr {
+        TagFilterExpr::And { exprs } => {
+            let mut sets = exprs
+                .iter()
+                .map(|x| compose_tag_expr(x, universe, predicate));
+            let Some(first) = sets.next() else {
+                return universe.clone();
+            };
+            sets.fold(first, |acc, x| acc.intersection(&x).copied().collect())
+        }
+        TagFilterExpr::Or { exprs } => exprs
+            .iter()
+            .flat_map(|x| compose_tag_expr(x, universe, predicate))
+            .collect(),
+        TagFilterExpr::Not { expr } => {
+            let negated = compose_tag_expr(expr, universe, predicate);
+            universe.difference(&negated).copied().collect()
+        }
+        _ => predicate(expr),
+    }
+}
+
+/// Ranks players ahead of AI countries, then countries by descending
+/// metric with ties broken by tag, and splits the ranking into the
+/// countries within the limit and those dropped, best ranked first
+fn rank_tags(
+    mut ranked: Vec<(CountryTag, f64)>,
+    players: &HashSet<CountryTag>,
+    limit: usize,
+) -> (Vec<CountryTag>, Vec<CountryTag>) {
+    ranked.sort_unstable_by(|(atag, a), (btag, b)| {
+        players
+            .contains(btag)
+            .cmp(&players.contains(atag))
+            .then_with(|| b.total_cmp(a))
+            .then_with(|| atag.cmp(btag))
+    });
+
+    let dropped = ranked.split_off(limit.min(ranked.len()));
+    let tags = |x: Vec<(CountryTag, f64)>| x.into_iter().map(|(tag, _)| tag).collect();
+    (tags(ranked), tags(dropped))
+}
+
+/// The monthly income across every source, matching the total of the
+/// income table
+fn income_total(income: &CountryIncomeLedger) -> f32 {
+    income.taxation
+        + income.production
+        + income.trade
+        + income.gold
+        + income.tariffs
+        + income.vassals
+        + income.harbor_fees
+        + income.subsidies
+        + income.war_reparations
+        + income.interest
+        + income.gifts
+        + income.events
+        + income.spoils_of_war
+        + income.treasure_fleet
+        + income.siphoning_income
+        + income.condottieri
+        + income.knowledge_sharing
+        + income.blockading_foreign_ports
+        + income.looting_foreign_cities
+        + income.other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(x: &[&str]) -> HashSet<CountryTag> {
+        x.iter().map(|x| x.parse().unwrap()).collect()
+    }
+
+    fn tags_vec(x: &[&str]) -> Vec<CountryTag> {
+        x.iter().map(|x| x.parse().unwrap()).collect()
+    }
+
+    fn religion(group: &str) -> TagFilterExpr {
+        TagFilterExpr::ReligionGroup {
+            group: String::from(group),
+        }
+    }
+
+    fn evaluate(expr: &TagFilterExpr) -> HashSet<CountryTag> {
+        let universe = tags(&["FRA", "ENG", "TUR", "MAM", "MNG"]);
+        compose_tag_expr(expr, &universe, &|x| match x {
+            TagFilterExpr::ReligionGroup { group } if group == "christian" => tags(&["FRA", "ENG"]),
+            TagFilterExpr::ReligionGroup { group } if group == "muslim" => tags(&["TUR", "MAM"]),
+            TagFilterExpr::MinDevelopment { .. } => tags(&["FRA", "TUR"]),
+            _ => HashSet::new(),
+        })
+    }
+
+    #[test]
+    fn test_compose_tag_expr() {
+        let large = TagFilterExpr::MinDevelopment { development: 500.0 };
+        let and = TagFilterExpr::And {
+            exprs: vec![religion("christian"), large.clone()],
+        };
+        assert_eq!(evaluate(&and), tags(&["FRA"]));
+
+        let or = TagFilterExpr::Or {
+            exprs: vec![religion("christian"), religion("muslim")],
+        };
+        assert_eq!(evaluate(&or), tags(&["FRA", "ENG", "TUR", "MAM"]));
+
+        let not = TagFilterExpr::Not {
+            expr: Box::new(or.clone()),
+        };
+        assert_eq!(evaluate(&not), tags(&["MNG"]));
+
+        let nested = TagFilterExpr::And {
+            exprs: vec![
+                or,
+                TagFilterExpr::Not {
+                    expr: Box::new(large),
+                },
+            ],
+        };
+        assert_eq!(evaluate(&nested), tags(&["ENG", "MAM"]));
+    }
+
+    #[test]
+    fn test_compose_empty_tag_expr() {
+        let and = TagFilterExpr::And { exprs: vec![] };
+        assert_eq!(evaluate(&and), tags(&["FRA", "ENG", "TUR", "MAM", "MNG"]));
+
+        let or = TagFilterExpr::Or { exprs: vec![] };
+        assert_eq!(evaluate(&or), HashSet::new());
+
+        let not_and = TagFilterExpr::Not {
+            expr: Box::new(and),
+        };
+        assert_eq!(evaluate(&not_and), HashSet::new());
+    }
+
+    #[test]
+    fn test_rank_tags_order_and_limit() {
+        let tag = |x: &str| x.parse::<CountryTag>().unwrap();
+        let ranked = vec![
+            (tag("FRA"), 900.0),
+            (tag("ENG"), 400.0),
+            (tag("TUR"), 700.0),
+            (tag("POR"), 100.0),
+        ];
+        let players = tags(&["POR", "ENG"]);
+
+        let (kept, dropped) = rank_tags(ranked.clone(), &players, 3);
+        assert_eq!(kept, vec![tag("ENG"), tag("POR"), tag("FRA")]);
+        assert_eq!(dropped, vec![tag("TUR")]);
+
+        let (kept, dropped) = rank_tags(ranked.clone(), &players, 10);
+        assert_eq!(kept.len(), 4);
+        assert!(dropped.is_empty());
+
+        let (kept, dropped) = rank_tags(ranked, &players, 0);
+        assert!(kept.is_empty());
+        assert_eq!(dropped.len(), 4);
+    }
+
+    #[test]
+    fn test_rank_tags_reports_every_dropped_tag() {
+        let ranked: Vec<_> = ["FRA", "ENG", "TUR", "POR", "CAS", "MNG", "MAM"]
+            .iter()
+            .zip([900.0, 400.0, 700.0, 100.0, 300.0, 800.0, 200.0])
+            .map(|(tag, metric)| (tag.parse::<CountryTag>().unwrap(), metric))
+            .collect();
+
+        let (kept, dropped) = rank_tags(ranked.clone(), &HashSet::new(), 4);
+        assert_eq!(kept.len(), 4);
+        assert_eq!(dropped.len(), 3);
+
+        let metric = |tag: &CountryTag| ranked.iter().find(|(x, _)| x == tag).unwrap().1;
+        let worst_kept = kept.iter().map(metric).fold(f64::MAX, f64::min);
+        assert!(dropped.iter().all(|x| metric(x) < worst_kept));
+
+        let all: HashSet<_> = kept.iter().chain(dropped.iter()).copied().collect();
+        assert_eq!(
+            all,
+            tags(&["FRA", "ENG", "TUR", "POR", "CAS", "MNG", "MAM"])
+        );
+        assert_eq!(dropped, tags_vec(&["CAS", "MAM", "POR"]));
+    }
+
+    #[test]
+    fn test_rank_tags_breaks_ties_by_tag() {
+        // Countries without a score rank at the minimum metric
+        let ranked: Vec<_> = ["TUR", "ENG", "FRA", "CAS", "POR"]
+            .iter()
+            .zip([500.0, 500.0, f64::MIN, 500.0, f64::MIN])
+            .map(|(tag, metric)| (tag.parse::<CountryTag>().unwrap(), metric))
+            .collect();
+
+        let expected = tags_vec(&["CAS", "ENG", "TUR", "FRA", "POR"]);
+        let (kept, dropped) = rank_tags(ranked.clone(), &HashSet::new(), 2);
+        assert_eq!(kept, expected[..2]);
+        assert_eq!(dropped, expected[2..]);
+
+        let mut reversed = ranked;
+        reversed.reverse();
+        assert_eq!(rank_tags(reversed, &HashSet::new(), 2), (kept, dropped));
+    }
 }
@@ -0,0 +1,445 @@
+use crate::{LocalizedTag, SaveFileImpl};
+use eu4save::{
+    models::{ProvinceEvent, WarEvent, WarHistory},
+    CountryTag, Eu4Date, ProvinceId,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WarSide {
+    Attacker,
+    Defender,
+}
+
+impl WarSide {
+    pub fn opposite(self) -> WarSide {
+        match self {
+            WarSide::Attacker => WarSide::Defender,
+            WarSide::Defender => WarSide::Attacker,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WarTimeline {
+    pub start: Eu4Date,
+
+    /// Absent while the war is ongoing
+    pub end: Option<Eu4Date>,
+    pub events: Vec<WarTimelineEvent>,
+    pub occupations: Vec<WarOccupation>,
+    pub fronts: Vec<WarFront>,
+
+    /// Provinces that changed hands between the sides when peace was signed
+    pub peace: Vec<ProvinceTransfer>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum WarTimelineEvent {
+    Joined {
+        date: Eu4Date,
+        country: LocalizedTag,
+        side: WarSide,
+    },
+    Left {
+        date: Eu4Date,
+        country: LocalizedTag,
+        side: WarSide,
+    },
+    Battle {
+        date: Eu4Date,
+        name: String,
+        location: ProvinceId,
+
+        /// Absent when the winner isn't a participant of the war (eg: rebels)
+        winner: Option<WarSide>,
+        losses: i32,
+    },
+}
+
+/// A change of controller of a province owned by a war participant
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WarOccupation {
+    pub date: Eu4Date,
+    pub province: ProvinceId,
+    pub name: String,
+    pub owner: LocalizedTag,
+    pub controller: LocalizedTag,
+
+    /// The side that now occupies the province. Absent when control returned
+    /// to the owner's side.
+    pub occupier: Option<WarSide>,
+}
+
+/// The number of enemy provinces held by each side at a date
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WarFront {
+    pub date: Eu4Date,
+    pub attacker_occupied: usize,
+    pub defender_occupied: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvinceTransfer {
+    pub province: ProvinceId,
+    pub name: String,
+    pub from: LocalizedTag,
+    pub to: LocalizedTag,
+}
+
+/// The side a participant fought on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ParticipantSide {
+    pub side: WarSide,
+
+    /// The participant hasn't left the war (eg: through a separate peace)
+    pub active: bool,
+}
+
+/// The first and last date of the war's history
+pub(crate) fn war_dates(history: &WarHistory) -> Option<(Eu4Date, Eu4Date)> {
+    let start = history.events.iter().map(|(date, _)| *date).min()?;
+    let end = history.events.iter().map(|(date, _)| *date).max()?;
+    Some((start, end))
+}
+
+/// Replays the join and leave events of a war with the participants keyed
+/// by their stored tag, as given by the mapping of the tag a participant
+/// fought under to the tag it's stored under
+fn participant_sides(
+    events: &[(Eu4Date, WarEvent)],
+    participants: &HashMap<CountryTag, CountryTag>,
+) -> HashMap<CountryTag, ParticipantSide> {
+    let stored = |tag: &CountryTag| participants.get(tag).copied().unwrap_or(*tag);
+
+    let mut sides: HashMap<CountryTag, ParticipantSide> = HashMap::new();
+    for (_, event) in events {
+        let (tag, side, active) = match event {
+            WarEvent::AddAttacker(x) => (x, WarSide::Attacker, true),
+            WarEvent::AddDefender(x) => (x, WarSide::Defender, true),
+            WarEvent::RemoveAttacker(x) => (x, WarSide::Attacker, false),
+            WarEvent::RemoveDefender(x) => (x, WarSide::Defender, false),
+            WarEvent::Battle(_) => continue,
+        };
+
+        sides
+            .entry(stored(tag))
+            .and_modify(|x| x.active = active)
+            .or_insert(ParticipantSide { side, active });
+    }
+
+    sides
+}
+
+impl SaveFileImpl {
+    /// The side of each participant keyed by the tag the participant is
+    /// stored under (see `war_participants`) so that countries that tag
+    /// switch mid war keep their side. Saves only record sides through the
+    /// join and leave events of the war's history.
+    pub(crate) fn war_sides(
+        &self,
+        name: &str,
+        history: &WarHistory,
+    ) -> HashMap<CountryTag, ParticipantSide> {
+        let participants: HashMap<CountryTag, CountryTag> = self
+            .war_participants
+            .iter()
+            .find(|x| x.war == name)
+            .map(|x| x.participants.iter().map(|x| (x.tag, x.stored)).collect())
+            .unwrap_or_default();
+
+        participant_sides(&history.events, &participants)
+    }
+
+    /// The stored tags of the participants on the opposing side of the given
+    /// stored tag. When `active` is set, only participants still fighting
+    /// are returned and nothing is returned if the tag itself left the war.
+    pub(crate) fn war_enemies(
+        &self,
+        name: &str,
+        history: &WarHistory,
+        tag: &CountryTag,
+        active: bool,
+    ) -> Vec<CountryTag> {
+        let sides = self.war_sides(name, history);
+        let Some(own) = sides.get(tag).filter(|x| !active || x.active) else {
+            return Vec::new();
+        };
+
+        sides
+            .iter()
+            .filter(|(_, x)| x.side != own.side && (!active || x.active))
+            .map(|(tag, _)| *tag)
+            .collect()
+    }
+
+    /// The tag a country is stored under given the tag it had at a date
+    pub(crate) fn stored_tag(&self, tag: CountryTag, date: Eu4Date) -> CountryTag {
+        self.tag_resolver
+            .resolve(tag, date)
+            .map(|x| x.stored)
+            .unwrap_or(tag)
+    }
+
+    pub(crate) fn find_war_history(&self, name: &str) -> Option<(&WarHistory, bool)> {
+        let game = &self.query.save().game;
+        game.active_wars
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| (&x.history, true))
+            .or_else(|| {
+                game.previous_wars
+                    .iter()
+                    .find(|x| x.name == name)
+                    .map(|x| (&x.history, false))
+            })
+    }
+
+    /// Replays the war from its declaration to peace (or the save date for
+    /// ongoing wars)
+    pub fn get_war_timeline(&self, name: &str) -> Option<WarTimeline> {
+        let (history, active) = self.find_war_history(name)?;
+        let (start, last_event) = war_dates(history)?;
+        let end = if active { None } else { Some(last_event) };
+        let replay_end = end.unwrap_or(self.query.save().meta.date);
+        let sides = self.war_sides(name, history);
+        let side_at =
+            |tag: CountryTag, date: Eu4Date| sides.get(&self.stored_tag(tag, date)).map(|x| x.side);
+
+        let events = self.war_timeline_events(history, &side_at);
+
+        let rebels = "REB".parse::<CountryTag>().unwrap();
+        let mut controls: Vec<_> = self
+            .query
+            .save()
+            .game
+            .provinces
+            .iter()
+            .flat_map(|(id, prov)| {
+                prov.history
+                    .events
+                    .iter()
+                    .filter_map(move |(date, event)| match event {
+                        ProvinceEvent::Controller(x) if x.tag != rebels => {
+                            Some((*date, *id, x.tag))
+                        }
+                        _ => None,
+                    })
+            })
+            .filter(|(date, _, _)| *date >= start && *date <= replay_end)
+            .collect();
+        controls.sort_by_key(|(date, id, _)| (*date, id.as_u16()));
+
+        let mut occupations = Vec::new();
+        let mut fronts = Vec::new();
+        let mut occupied: HashMap<ProvinceId, WarSide> = HashMap::new();
+        let mut peace = Vec::new();
+        let mut last_front: Option<Eu4Date> = None;
+        for (date, id, controller) in controls {
+            let Some(owner) = self.owner_history.owner_at(id, date) else {
+                continue;
+            };
+
+            let Some(owner_side) = side_at(owner, date) else {
+                continue;
+            };
+
+            let occupier = side_at(controller, date).filter(|x| *x != owner_side);
+
+            match occupier {
+                Some(side) => occupied.insert(id, side),
+                None => occupied.remove(&id),
+            };
+
+            occupations.push(WarOccupation {
+                date,
+                province: id,
+                name: self.province_name(&id),
+                owner: self.localize_tag(owner),
+                controller: self.localize_tag(controller),
+                occupier,
+            });
+
+            let front = WarFront {
+                date,
+                attacker_occupied: occupied
+                    .values()
+                    .filter(|x| **x == WarSide::Attacker)
+                    .count(),
+                defender_occupied: occupied
+                    .values()
+                    .filter(|x| **x == WarSide::Defender)
+                    .count(),
+            };
+
+            if last_front == Some(date) {
+                fronts.pop();
+            }
+            last_front = Some(date);
+            fronts.push(front);
+        }
+
+        // Provinces that changed hands between the sides on the date of peace
+        let transfers = end.map(|x| self.owner_history.transfers_on(x));
+        for (province, previous, tag) in transfers.into_iter().flatten() {
+            let Some(previous) = previous else {
+                continue;
+            };
+
+            let from_side = side_at(previous, replay_end);
+            let to_side = side_at(tag, replay_end);
+            if matches!((from_side, to_side), (Some(a), Some(b)) if a != b) {
+                peace.push(ProvinceTransfer {
+                    province,
+                    name: self.province_name(&province),
+                    from: self.localize_tag(previous),
+                    to: self.localize_tag(tag),
+                });
+            }
+        }
+
+        Some(WarTimeline {
+            start,
+            end,
+            events,
+            occupations,
+            fronts,
+            peace,
+        })
+    }
+
+    fn war_timeline_events<F>(&self, history: &WarHistory, side_at: F) -> Vec<WarTimelineEvent>
+    where
+        F: Fn(CountryTag, Eu4Date) -> Option<WarSide>,
+    {
+        let mut events = Vec::new();
+        for (date, event) in &history.events {
+            let date = *date;
+            let event = match event {
+                WarEvent::AddAttacker(x) => WarTimelineEvent::Joined {
+                    date,
+                    country: self.localize_tag(*x),
+                    side: WarSide::Attacker,
+                },
+                WarEvent::AddDefender(x) => WarTimelineEvent::Joined {
+                    date,
+                    country: self.localize_tag(*x),
+                    side: WarSide::Defender,
+                },
+                WarEvent::RemoveAttacker(x) => WarTimelineEvent::Left {
+                    date,
+                    country: self.localize_tag(*x),
+                    side: WarSide::Attacker,
+                },
+                WarEvent::RemoveDefender(x) => WarTimelineEvent::Left {
+                    date,
+                    country: self.localize_tag(*x),
+                    side: WarSide::Defender,
+                },
+                WarEvent::Battle(b) => {
+                    let attacker_side = side_at(b.attacker.country, date);
+                    let winner = if b.attacker_won {
+                        attacker_side
+                    } else {
+                        attacker_side.map(WarSide::opposite)
+                    };
+
+                    WarTimelineEvent::Battle {
+                        date,
+                        name: b.name.clone(),
+                        location: b.location,
+                        winner,
+                        losses: b.attacker.losses + b.defender.losses,
+                    }
+                }
+            };
+            events.push(event);
+        }
+
+        events
+    }
+
+    pub(crate) fn province_name(&self, id: &ProvinceId) -> String {
+        self.query
+            .save()
+            .game
+            .provinces
+            .get(id)
+            .map(|x| x.name.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> CountryTag {
+        s.parse().unwrap()
+    }
+
+    fn date(year: i16) -> Eu4Date {
+        Eu4Date::from_ymd(year, 1, 1)
+    }
+
+    #[test]
+    fn test_participant_sides() {
+        // Castile joins and then leaves the war as Spain, while France is
+        // stored under its own tag
+        let participants = HashMap::from([(tag("CAS"), tag("SPA")), (tag("SPA"), tag("SPA"))]);
+        let events = vec![
+            (date(1480), WarEvent::AddAttacker(tag("CAS"))),
+            (date(1480), WarEvent::AddDefender(tag("FRA"))),
+            (date(1490), WarEvent::AddDefender(tag("POR"))),
+            (date(1495), WarEvent::RemoveAttacker(tag("SPA"))),
+        ];
+
+        let sides = participant_sides(&events, &participants);
+        assert_eq!(sides.len(), 3);
+        assert!(!sides.contains_key(&tag("CAS")));
+        assert_eq!(
+            sides.get(&tag("SPA")),
+            Some(&ParticipantSide {
+                side: WarSide::Attacker,
+                active: false,
+            })
+        );
+        assert_eq!(
+            sides.get(&tag("FRA")),
+            Some(&ParticipantSide {
+                side: WarSide::Defender,
+                active: true,
+            })
+        );
+        assert_eq!(
+            sides.get(&tag("POR")).map(|x| x.side),
+            Some(WarSide::Defender)
+        );
+    }
+
+    #[test]
+    fn test_participant_sides_keep_first_side() {
+        // A participant that leaves and rejoins keeps its original side
+        let events = vec![
+            (date(1480), WarEvent::AddDefender(tag("FRA"))),
+            (date(1485), WarEvent::RemoveDefender(tag("FRA"))),
+            (date(1486), WarEvent::AddAttacker(tag("FRA"))),
+        ];
+
+        let sides = participant_sides(&events, &HashMap::new());
+        assert_eq!(
+            sides.get(&tag("FRA")),
+            Some(&ParticipantSide {
+                side: WarSide::Defender,
+                active: true,
+            })
+        );
+    }
+}
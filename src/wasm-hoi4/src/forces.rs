@@ -0,0 +1,175 @@
+use crate::{models::Hoi4Country, SaveFileImpl};
+use pdxfilter::CountryFilterPayload;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipmentStock {
+    pub equipment: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4CountryForces {
+    pub tag: String,
+    pub name: String,
+    pub divisions: usize,
+    pub manpower: f64,
+    pub locked_manpower: f64,
+
+    /// Stockpiled equipment ordered by amount
+    pub equipment: Vec<EquipmentStock>,
+
+    /// Names of the wars the country is fighting
+    pub wars: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4FactionMember {
+    pub tag: String,
+    pub name: String,
+    pub divisions: usize,
+    pub manpower: f64,
+    pub states: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4FactionOverview {
+    pub name: String,
+    pub leader: String,
+    pub members: Vec<Hoi4FactionMember>,
+    pub divisions: usize,
+    pub manpower: f64,
+    pub states: usize,
+
+    /// Names of the wars a member is fighting
+    pub wars: Vec<String>,
+}
+
+impl SaveFileImpl {
+    fn war_names(&self, tags: &HashSet<&str>) -> Vec<String> {
+        self.gamestate
+            .wars
+            .iter()
+            .enumerate()
+            .filter(|(_, war)| war.participants().any(|x| tags.contains(x)))
+            .map(|(i, war)| war.name.clone().unwrap_or_else(|| format!("War {}", i + 1)))
+            .collect()
+    }
+
+    fn country_forces(&self, tag: &str, country: &Hoi4Country) -> Hoi4CountryForces {
+        let mut equipment: Vec<_> = country
+            .stockpile
+            .iter()
+            .filter(|(_, amount)| **amount > 0.0)
+            .map(|(equipment, amount)| EquipmentStock {
+                equipment: equipment.clone(),
+                amount: *amount,
+            })
+            .collect();
+        equipment.sort_by(|a, b| {
+            b.amount
+                .total_cmp(&a.amount)
+                .then_with(|| a.equipment.cmp(&b.equipment))
+        });
+
+        Hoi4CountryForces {
+            tag: String::from(tag),
+            name: self.country_name(tag),
+            divisions: country.units.division.len(),
+            manpower: country.manpower_pool.available,
+            locked_manpower: country.manpower_pool.locked,
+            equipment,
+            wars: self.war_names(&HashSet::from([tag])),
+        }
+    }
+
+    /// Manpower, divisions, and equipment of countries matching the filter,
+    /// ordered by division count
+    pub fn get_country_forces(&self, payload: CountryFilterPayload) -> Vec<Hoi4CountryForces> {
+        let owned = self.owned_states();
+        let mut result: Vec<_> = self
+            .gamestate
+            .countries
+            .iter()
+            .filter(|(tag, _)| payload.matches(tag))
+            .filter(|(tag, _)| owned.contains_key(tag.as_str()))
+            .map(|(tag, country)| self.country_forces(tag, country))
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.divisions
+                .cmp(&a.divisions)
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        result
+    }
+
+    /// The number of states owned by each country that owns a state
+    fn owned_states(&self) -> HashMap<&str, usize> {
+        let mut result = HashMap::new();
+        for state in self.gamestate.states.values() {
+            *result.entry(state.owner.as_str()).or_default() += 1;
+        }
+        result
+    }
+
+    /// Factions with the combined strength of their members, ordered by
+    /// division count
+    pub fn get_factions(&self) -> Vec<Hoi4FactionOverview> {
+        let owned = self.owned_states();
+        let mut result: Vec<_> = self
+            .gamestate
+            .factions
+            .faction
+            .iter()
+            .map(|faction| {
+                let mut members: Vec<_> = faction
+                    .members
+                    .iter()
+                    .chain(std::iter::once(&faction.leader))
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .map(|tag| {
+                        let country = self.gamestate.countries.get(tag);
+                        Hoi4FactionMember {
+                            tag: tag.clone(),
+                            name: self.country_name(tag),
+                            divisions: country.map_or(0, |x| x.units.division.len()),
+                            manpower: country.map_or(0.0, |x| x.manpower_pool.available),
+                            states: owned.get(tag.as_str()).copied().unwrap_or(0),
+                        }
+                    })
+                    .collect();
+
+                members.sort_by(|a, b| {
+                    b.divisions
+                        .cmp(&a.divisions)
+                        .then_with(|| a.tag.cmp(&b.tag))
+                });
+
+                let tags: HashSet<&str> = members.iter().map(|x| x.tag.as_str()).collect();
+                Hoi4FactionOverview {
+                    name: faction.name.clone(),
+                    leader: faction.leader.clone(),
+                    divisions: members.iter().map(|x| x.divisions).sum(),
+                    manpower: members.iter().map(|x| x.manpower).sum(),
+                    states: members.iter().map(|x| x.states).sum(),
+                    wars: self.war_names(&tags),
+                    members,
+                }
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.divisions
+                .cmp(&a.divisions)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        result
+    }
+}
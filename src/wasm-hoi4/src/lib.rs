@@ -1,8 +1,16 @@
+use hoi4game::Game;
 use hoi4save::{models::Hoi4Save, Encoding, FailedResolveStrategy, Hoi4Date, Hoi4Error, Hoi4File};
+use models::Hoi4Gamestate;
+use pdxgame::GameData;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+mod forces;
+mod map;
+mod models;
+mod territory;
 mod tokens;
+pub use pdxmap::map_fill_borders;
 pub use tokens::*;
 
 #[derive(Debug, Serialize)]
@@ -10,11 +18,22 @@ pub use tokens::*;
 pub struct Hoi4Metadata {
     date: Hoi4Date,
     is_meltable: bool,
+
+    /// Set when the gamestate could not be deserialized, in which case only
+    /// the header is available and the analytics are empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gamestate_error: Option<String>,
 }
 
 pub struct SaveFileImpl {
     save: Hoi4Save,
+    gamestate: Hoi4Gamestate,
+    gamestate_error: Option<String>,
     encoding: Encoding,
+
+    /// Localization from the game data, when the caller has supplied it.
+    /// Without it, names fall back to the keys found in the save.
+    game: Option<GameData<Game<'static>>>,
 }
 
 pub fn to_json_value<T: serde::ser::Serialize + ?Sized>(value: &T) -> JsValue {
@@ -30,6 +49,33 @@ impl SaveFile {
     pub fn metadata(&self) -> JsValue {
         to_json_value(&self.0.metadata())
     }
+
+    pub fn set_game_data(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.0.set_game_data(data).map_err(js_err)
+    }
+
+    pub fn get_factions(&self) -> JsValue {
+        to_json_value(&self.0.get_factions())
+    }
+
+    pub fn get_country_forces(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_country_forces(payload)))
+    }
+
+    pub fn get_territory(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_territory(payload)))
+    }
+
+    pub fn map_colors(&self, payload: JsValue) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(self.0.map_colors(payload))
+    }
+}
+
+fn js_err(err: impl std::error::Error) -> JsValue {
+    JsValue::from(err.to_string())
 }
 
 impl SaveFileImpl {
@@ -37,23 +83,42 @@ impl SaveFileImpl {
         Hoi4Metadata {
             date: self.save.date,
             is_meltable: self.is_meltable(),
+            gamestate_error: self.gamestate_error.clone(),
         }
     }
 
     fn is_meltable(&self) -> bool {
         matches!(self.encoding, Encoding::Binary)
     }
+
+    /// Loads the zstd compressed game data written by compile_assets
+    pub fn set_game_data(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.game = Some(GameData::from_zstd(data, Game::from_flatbuffer)?);
+        Ok(())
+    }
+
+    /// The localized name of a country, falling back to its tag
+    pub(crate) fn country_name(&self, tag: &str) -> String {
+        let localized = self.game.as_ref().and_then(|x| x.localize_country(tag));
+        localized.map_or_else(|| String::from(tag), |x| String::from(x.name))
+    }
 }
 
 fn _parse_save(data: &[u8]) -> Result<SaveFile, Hoi4Error> {
     let file = Hoi4File::from_slice(data)?;
-    let save = file
-        .parse()?
-        .deserializer(tokens::get_tokens())
-        .deserialize()?;
+    let parsed = file.parse()?;
+    let save = parsed.deserializer(tokens::get_tokens()).deserialize()?;
+    let gamestate = parsed.deserializer(tokens::get_tokens()).deserialize();
+    let (gamestate, gamestate_error) = match gamestate {
+        Ok(gamestate) => (gamestate, None),
+        Err(e) => (Hoi4Gamestate::default(), Some(e.to_string())),
+    };
     Ok(SaveFile(SaveFileImpl {
         save,
+        gamestate,
+        gamestate_error,
         encoding: file.encoding(),
+        game: None,
     }))
 }
 
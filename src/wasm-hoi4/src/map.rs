@@ -0,0 +1,45 @@
+use crate::SaveFileImpl;
+use pdxmap::{tag_color, ProvinceColors, UNOWNED_COLOR};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateProvinces {
+    pub id: u32,
+    pub provinces: Vec<u16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapPayload {
+    /// The number of province colors to allocate, one more than the largest
+    /// province id in the raster
+    pub provinces: usize,
+
+    /// The provinces of each state (the `states.json` written by
+    /// compile_assets)
+    pub states: Vec<StateProvinces>,
+}
+
+impl SaveFileImpl {
+    /// Political map colors where states are painted by their owner with
+    /// stripes of the controller when occupied
+    pub fn map_colors(&self, payload: MapPayload) -> Vec<u8> {
+        let mut colors = ProvinceColors::new(payload.provinces, UNOWNED_COLOR);
+        for state in &payload.states {
+            let Some(save_state) = self.gamestate.states.get(&state.id) else {
+                continue;
+            };
+
+            let owner = tag_color(&save_state.owner);
+            let controller = tag_color(save_state.controller());
+            for province in &state.provinces {
+                let id = usize::from(*province);
+                colors.set(id, owner);
+                colors.set_secondary(id, controller);
+            }
+        }
+
+        colors.into_data()
+    }
+}
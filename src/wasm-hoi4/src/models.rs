@@ -0,0 +1,120 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The subset of the gamestate used for analytics that isn't modeled by
+/// hoi4save
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Hoi4Gamestate {
+    #[serde(default)]
+    pub countries: HashMap<String, Hoi4Country>,
+
+    #[serde(default)]
+    pub states: HashMap<u32, Hoi4State>,
+
+    #[serde(default)]
+    pub factions: Hoi4Factions,
+
+    #[serde(default, rename = "active_war")]
+    pub wars: Vec<Hoi4War>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Hoi4Country {
+    #[serde(default)]
+    pub capital: Option<u32>,
+
+    #[serde(default)]
+    pub units: Hoi4Units,
+
+    #[serde(default)]
+    pub manpower_pool: Hoi4ManpowerPool,
+
+    /// Equipment in the stockpile keyed by equipment type
+    #[serde(default)]
+    pub stockpile: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Hoi4Units {
+    #[serde(default)]
+    pub division: Vec<Hoi4Division>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hoi4Division {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Province the division is located in
+    #[serde(default)]
+    pub location: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Hoi4ManpowerPool {
+    /// Manpower ready to be drafted
+    #[serde(default)]
+    pub available: f64,
+
+    /// Manpower reserved by divisions in training and reinforcements
+    #[serde(default)]
+    pub locked: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hoi4State {
+    #[serde(default)]
+    pub owner: String,
+
+    /// The country occupying the state, if not the owner
+    #[serde(default)]
+    pub controller: Option<String>,
+}
+
+impl Hoi4State {
+    pub fn controller(&self) -> &str {
+        self.controller.as_deref().unwrap_or(&self.owner)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Hoi4Factions {
+    #[serde(default)]
+    pub faction: Vec<Hoi4Faction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hoi4Faction {
+    pub name: String,
+    pub leader: String,
+
+    /// Tags of the members, including the leader
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hoi4War {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default, rename = "attacker")]
+    pub attackers: Vec<Hoi4WarParticipant>,
+
+    #[serde(default, rename = "defender")]
+    pub defenders: Vec<Hoi4WarParticipant>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hoi4WarParticipant {
+    pub country: String,
+}
+
+impl Hoi4War {
+    pub fn participants(&self) -> impl Iterator<Item = &str> {
+        self.attackers
+            .iter()
+            .chain(self.defenders.iter())
+            .map(|x| x.country.as_str())
+    }
+}
@@ -0,0 +1,92 @@
+use crate::SaveFileImpl;
+use pdxfilter::CountryFilterPayload;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4StateControl {
+    pub id: u32,
+
+    /// The localized state name when game data is available
+    pub name: Option<String>,
+    pub owner: String,
+    pub controller: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4CountryTerritory {
+    pub tag: String,
+    pub name: String,
+
+    /// States owned by the country
+    pub owned: usize,
+
+    /// States controlled by the country, whether owned or occupied
+    pub controlled: usize,
+
+    /// Owned states controlled by another country
+    pub lost: usize,
+
+    /// States owned by another country that the country controls
+    pub occupied: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hoi4Territory {
+    /// States owned or controlled by countries matching the filter
+    pub states: Vec<Hoi4StateControl>,
+
+    /// Ordered by the number of controlled states
+    pub countries: Vec<Hoi4CountryTerritory>,
+}
+
+impl SaveFileImpl {
+    /// State ownership and control for countries matching the filter
+    pub fn get_territory(&self, payload: CountryFilterPayload) -> Hoi4Territory {
+        let mut states = Vec::new();
+        let mut countries: BTreeMap<&str, Hoi4CountryTerritory> = BTreeMap::new();
+        for (id, state) in &self.gamestate.states {
+            let owner = state.owner.as_str();
+            let controller = state.controller();
+            if !payload.matches(owner) && !payload.matches(controller) {
+                continue;
+            }
+
+            states.push(Hoi4StateControl {
+                id: *id,
+                name: self
+                    .game
+                    .as_ref()
+                    .and_then(|x| x.localize_state(*id))
+                    .map(String::from),
+                owner: String::from(owner),
+                controller: String::from(controller),
+            });
+
+            countries.entry(owner).or_default().owned += 1;
+            countries.entry(controller).or_default().controlled += 1;
+            if owner != controller {
+                countries.entry(owner).or_default().lost += 1;
+                countries.entry(controller).or_default().occupied += 1;
+            }
+        }
+
+        states.sort_by_key(|x| x.id);
+
+        let mut countries: Vec<_> = countries
+            .into_iter()
+            .filter(|(tag, _)| payload.matches(tag))
+            .map(|(tag, territory)| Hoi4CountryTerritory {
+                tag: String::from(tag),
+                name: self.country_name(tag),
+                ..territory
+            })
+            .collect();
+        countries.sort_by(|a, b| b.controlled.cmp(&a.controlled));
+
+        Hoi4Territory { states, countries }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    models::{ImperatorCountry, ImperatorProvince},
+    SaveFileImpl,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryDetailsPayload {
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImperatorCountryOverview {
+    pub id: u32,
+    pub tag: String,
+    pub name: String,
+
+    /// The ruler's first name key. The game data does not localize
+    /// character names.
+    pub ruler: Option<String>,
+    pub provinces: usize,
+    pub pops: usize,
+
+    /// Average civilization value of owned provinces
+    pub civilization: f64,
+    pub gold: f64,
+    pub manpower: f64,
+    pub political_influence: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopCount {
+    pub key: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImperatorFamilySummary {
+    pub id: u32,
+    pub key: String,
+    pub prestige: f64,
+    pub members: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImperatorCountryDetails {
+    pub overview: ImperatorCountryOverview,
+    pub pop_types: Vec<PopCount>,
+    pub cultures: Vec<PopCount>,
+    pub religions: Vec<PopCount>,
+
+    /// Families of the country ordered by prestige
+    pub families: Vec<ImperatorFamilySummary>,
+    pub characters: usize,
+}
+
+fn counts<'a>(iter: impl Iterator<Item = &'a str>) -> Vec<PopCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for key in iter {
+        *counts.entry(key).or_default() += 1;
+    }
+
+    let mut result: Vec<_> = counts
+        .into_iter()
+        .map(|(key, count)| PopCount {
+            key: String::from(key),
+            count,
+        })
+        .collect();
+    result.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    result
+}
+
+/// Provinces grouped by the id of their owner
+fn provinces_by_owner(
+    provinces: &HashMap<u32, ImperatorProvince>,
+) -> HashMap<u32, Vec<&ImperatorProvince>> {
+    let mut result: HashMap<u32, Vec<_>> = HashMap::new();
+    for province in provinces.values() {
+        if let Some(owner) = province.owner {
+            result.entry(owner).or_default().push(province);
+        }
+    }
+    result
+}
+
+impl SaveFileImpl {
+    fn country_overview(
+        &self,
+        id: u32,
+        country: &ImperatorCountry,
+        owned: &[&ImperatorProvince],
+    ) -> ImperatorCountryOverview {
+        let provinces = owned.len();
+        let civilization: f64 = owned.iter().map(|x| x.civilization_value).sum();
+
+        let ruler = country
+            .monarch
+            .and_then(|x| self.gamestate.character(x))
+            .map(|x| x.first_name_loc.name.clone());
+
+        ImperatorCountryOverview {
+            id,
+            tag: country.tag.clone(),
+            name: self.country_name(&country.tag),
+            ruler,
+            provinces,
+            pops: owned.iter().map(|x| x.pop.len()).sum(),
+            civilization: if provinces > 0 {
+                civilization / provinces as f64
+            } else {
+                0.0
+            },
+            gold: country.currency_data.gold,
+            manpower: country.currency_data.manpower,
+            political_influence: country.currency_data.political_influence,
+        }
+    }
+
+    /// Countries that own at least one province, ordered by pops. There is
+    /// no accompanying time series as, unlike EU4's ledger, the gamestate
+    /// only records the current provinces, pops, and treasuries without any
+    /// dated snapshots of them.
+    pub fn get_countries(&self) -> Vec<ImperatorCountryOverview> {
+        let owners = provinces_by_owner(&self.gamestate.provinces);
+        let mut result: Vec<_> = self
+            .gamestate
+            .country
+            .country_database
+            .iter()
+            .filter_map(|(id, country)| {
+                let owned = owners.get(id)?;
+                Some(self.country_overview(*id, country, owned))
+            })
+            .collect();
+
+        result.sort_by(|a, b| b.pops.cmp(&a.pops).then_with(|| a.tag.cmp(&b.tag)));
+        result
+    }
+
+    /// A country's pops broken down by type, culture, and religion along
+    /// with its families
+    pub fn get_country_details(
+        &self,
+        payload: CountryDetailsPayload,
+    ) -> Option<ImperatorCountryDetails> {
+        let (id, country) = self.gamestate.country_by_tag(&payload.tag)?;
+        let owned: Vec<_> = self
+            .gamestate
+            .provinces
+            .values()
+            .filter(|x| x.owner == Some(id))
+            .collect();
+        let pops: Vec<_> = owned
+            .iter()
+            .flat_map(|x| x.pop.iter())
+            .filter_map(|x| self.gamestate.population.pop.get(x))
+            .collect();
+
+        let characters: Vec<_> = self
+            .gamestate
+            .character
+            .character_database
+            .values()
+            .filter(|x| x.country == Some(id) && x.death_date.is_none())
+            .collect();
+
+        let mut families: Vec<_> = self
+            .gamestate
+            .family
+            .families
+            .iter()
+            .filter(|(_, family)| family.owner == Some(id))
+            .map(|(family_id, family)| ImperatorFamilySummary {
+                id: *family_id,
+                key: family.key.clone(),
+                prestige: family.prestige,
+                members: characters
+                    .iter()
+                    .filter(|x| x.family == Some(*family_id))
+                    .count(),
+            })
+            .collect();
+        families.sort_by(|a, b| {
+            b.prestige
+                .total_cmp(&a.prestige)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+
+        Some(ImperatorCountryDetails {
+            overview: self.country_overview(id, country, &owned),
+            pop_types: counts(pops.iter().map(|x| x.kind.as_str())),
+            cultures: counts(pops.iter().filter_map(|x| x.culture.as_deref())),
+            religions: counts(pops.iter().filter_map(|x| x.religion.as_deref())),
+            families,
+            characters: characters.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn province(owner: Option<u32>, pops: usize) -> ImperatorProvince {
+        ImperatorProvince {
+            owner,
+            culture: None,
+            religion: None,
+            civilization_value: 0.0,
+            pop: (0..pops as u32).collect(),
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        let keys = [
+            "citizen", "slaves", "freemen", "slaves", "slaves", "citizen",
+        ];
+        let result: Vec<_> = counts(keys.into_iter())
+            .into_iter()
+            .map(|x| (x.key, x.count))
+            .collect();
+        let expected = vec![
+            (String::from("slaves"), 3),
+            (String::from("citizen"), 2),
+            (String::from("freemen"), 1),
+        ];
+        assert_eq!(result, expected);
+        assert!(counts(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_provinces_by_owner() {
+        let provinces = HashMap::from([
+            (1, province(Some(10), 1)),
+            (2, province(Some(20), 2)),
+            (3, province(None, 3)),
+            (4, province(Some(10), 4)),
+        ]);
+
+        let owners = provinces_by_owner(&provinces);
+        assert_eq!(owners.len(), 2);
+
+        let mut pops: Vec<_> = owners[&10].iter().map(|x| x.pop.len()).collect();
+        pops.sort_unstable();
+        assert_eq!(pops, vec![1, 4]);
+        assert_eq!(owners[&20].len(), 1);
+    }
+}
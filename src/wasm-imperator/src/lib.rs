@@ -2,10 +2,18 @@ use imperator_save::{
     models::MetadataOwned, Encoding, FailedResolveStrategy, ImperatorDate, ImperatorError,
     ImperatorFile,
 };
+use imperatorgame::Game;
+use models::ImperatorGamestate;
+use pdxgame::GameData;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+mod countries;
+mod map;
+mod models;
 mod tokens;
+mod wars;
+pub use pdxmap::map_fill_borders;
 pub use tokens::*;
 
 #[derive(Debug, Serialize)]
@@ -14,11 +22,22 @@ pub struct ImperatorMetadata {
     date: ImperatorDate,
     version: String,
     is_meltable: bool,
+
+    /// Set when the gamestate could not be deserialized, in which case only
+    /// the header is available and the analytics are empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gamestate_error: Option<String>,
 }
 
 pub struct SaveFileImpl {
     header: MetadataOwned,
+    gamestate: ImperatorGamestate,
+    gamestate_error: Option<String>,
     encoding: Encoding,
+
+    /// Localization from the game data, when the caller has supplied it.
+    /// Without it, names fall back to the keys found in the save.
+    game: Option<GameData<Game<'static>>>,
 }
 
 #[wasm_bindgen]
@@ -34,6 +53,33 @@ impl SaveFile {
     pub fn metadata(&self) -> JsValue {
         to_json_value(&self.0.metadata())
     }
+
+    pub fn set_game_data(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.0.set_game_data(data).map_err(js_err)
+    }
+
+    pub fn get_countries(&self) -> JsValue {
+        to_json_value(&self.0.get_countries())
+    }
+
+    pub fn get_country_details(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_country_details(payload)))
+    }
+
+    pub fn get_wars(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_wars(payload)))
+    }
+
+    pub fn map_colors(&self, payload: JsValue) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(self.0.map_colors(payload))
+    }
+}
+
+fn js_err(err: impl std::error::Error) -> JsValue {
+    JsValue::from(err.to_string())
 }
 
 impl SaveFileImpl {
@@ -42,21 +88,45 @@ impl SaveFileImpl {
             date: self.header.date,
             version: self.header.version.clone(),
             is_meltable: self.is_meltable(),
+            gamestate_error: self.gamestate_error.clone(),
         }
     }
 
     fn is_meltable(&self) -> bool {
         matches!(self.encoding, Encoding::Binary | Encoding::BinaryZip)
     }
+
+    /// Loads the zstd compressed game data written by compile_assets
+    pub fn set_game_data(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.game = Some(GameData::from_zstd(data, Game::from_flatbuffer)?);
+        Ok(())
+    }
+
+    /// The localized name of a country, falling back to its tag
+    pub(crate) fn country_name(&self, tag: &str) -> String {
+        let localized = self.game.as_ref().and_then(|x| x.localize_country(tag));
+        localized.map_or_else(|| String::from(tag), |x| String::from(x.name))
+    }
 }
 
 fn _parse_save(data: &[u8]) -> Result<SaveFile, ImperatorError> {
     let file = ImperatorFile::from_slice(data)?;
     let meta = file.meta().parse()?;
     let header = meta.deserializer(tokens::get_tokens()).deserialize()?;
+    let mut zip_sink = Vec::new();
+    let gamestate = file
+        .parse(&mut zip_sink)
+        .and_then(|x| x.deserializer(tokens::get_tokens()).deserialize());
+    let (gamestate, gamestate_error) = match gamestate {
+        Ok(gamestate) => (gamestate, None),
+        Err(e) => (ImperatorGamestate::default(), Some(e.to_string())),
+    };
     Ok(SaveFile(SaveFileImpl {
         header,
+        gamestate,
+        gamestate_error,
         encoding: file.encoding(),
+        game: None,
     }))
 }
 
@@ -0,0 +1,31 @@
+use crate::SaveFileImpl;
+use pdxmap::{tag_color, ProvinceColors, UNOWNED_COLOR};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapPayload {
+    /// The number of province colors to allocate, one more than the largest
+    /// province id in the raster
+    pub provinces: usize,
+}
+
+impl SaveFileImpl {
+    /// Political map colors where provinces are painted by their owner's map
+    /// color
+    pub fn map_colors(&self, payload: MapPayload) -> Vec<u8> {
+        let mut colors = ProvinceColors::new(payload.provinces, UNOWNED_COLOR);
+        for (id, province) in &self.gamestate.provinces {
+            let Some(country) = province.owner.and_then(|x| self.gamestate.country(x)) else {
+                continue;
+            };
+
+            let color = country
+                .color
+                .map_or_else(|| tag_color(&country.tag), |[r, g, b]| [r, g, b, 255]);
+            colors.set(*id as usize, color);
+        }
+
+        colors.into_data()
+    }
+}
@@ -0,0 +1,194 @@
+use imperator_save::ImperatorDate;
+use pdxde::{deserialize_database, deserialize_rgb};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The subset of the gamestate that is modeled
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorGamestate {
+    #[serde(default)]
+    pub country: ImperatorCountries,
+
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub provinces: HashMap<u32, ImperatorProvince>,
+
+    #[serde(default)]
+    pub population: ImperatorPopulation,
+
+    #[serde(default)]
+    pub character: ImperatorCharacters,
+
+    #[serde(default)]
+    pub family: ImperatorFamilies,
+
+    /// Both ongoing and concluded wars
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub war: HashMap<u32, ImperatorWar>,
+}
+
+impl ImperatorGamestate {
+    pub fn country(&self, id: u32) -> Option<&ImperatorCountry> {
+        self.country.country_database.get(&id)
+    }
+
+    pub fn country_by_tag(&self, tag: &str) -> Option<(u32, &ImperatorCountry)> {
+        self.country
+            .country_database
+            .iter()
+            .find(|(_, x)| x.tag == tag)
+            .map(|(id, x)| (*id, x))
+    }
+
+    pub fn character(&self, id: u32) -> Option<&ImperatorCharacter> {
+        self.character.character_database.get(&id)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorCountries {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub country_database: HashMap<u32, ImperatorCountry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorCountry {
+    #[serde(default)]
+    pub tag: String,
+
+    #[serde(default)]
+    pub capital: Option<u32>,
+
+    /// Id of the ruling character
+    #[serde(default)]
+    pub monarch: Option<u32>,
+
+    /// The map color of the country
+    #[serde(default, deserialize_with = "deserialize_rgb")]
+    pub color: Option<[u8; 3]>,
+
+    #[serde(default)]
+    pub currency_data: ImperatorCurrencies,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorCurrencies {
+    #[serde(default)]
+    pub gold: f64,
+
+    #[serde(default)]
+    pub manpower: f64,
+
+    #[serde(default)]
+    pub political_influence: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorProvince {
+    /// Id of the owning country
+    #[serde(default)]
+    pub owner: Option<u32>,
+
+    #[serde(default)]
+    pub culture: Option<String>,
+
+    #[serde(default)]
+    pub religion: Option<String>,
+
+    #[serde(default)]
+    pub civilization_value: f64,
+
+    /// Ids of the pops living in the province
+    #[serde(default)]
+    pub pop: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorPopulation {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub pop: HashMap<u32, ImperatorPop>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorPop {
+    /// Pop type (eg: `citizen`, `slaves`)
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    #[serde(default)]
+    pub culture: Option<String>,
+
+    #[serde(default)]
+    pub religion: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorCharacters {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub character_database: HashMap<u32, ImperatorCharacter>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorCharacter {
+    #[serde(default)]
+    pub first_name_loc: ImperatorName,
+
+    /// Id of the country the character belongs to
+    #[serde(default)]
+    pub country: Option<u32>,
+
+    #[serde(default)]
+    pub family: Option<u32>,
+
+    #[serde(default)]
+    pub birth_date: Option<ImperatorDate>,
+
+    #[serde(default)]
+    pub death_date: Option<ImperatorDate>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorName {
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImperatorFamilies {
+    #[serde(default, deserialize_with = "deserialize_database")]
+    pub families: HashMap<u32, ImperatorFamily>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorFamily {
+    /// Localization key of the family name
+    #[serde(default)]
+    pub key: String,
+
+    /// Id of the country the family belongs to
+    #[serde(default)]
+    pub owner: Option<u32>,
+
+    #[serde(default)]
+    pub prestige: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImperatorWar {
+    #[serde(default)]
+    pub war_name: Option<ImperatorName>,
+
+    #[serde(default)]
+    pub start_date: Option<ImperatorDate>,
+
+    /// Set once the war has concluded
+    #[serde(default)]
+    pub previous: bool,
+
+    /// Ids of the attacking countries, with the war leader first
+    #[serde(default)]
+    pub attacker: Vec<u32>,
+
+    /// Ids of the defending countries, with the war leader first
+    #[serde(default)]
+    pub defender: Vec<u32>,
+}
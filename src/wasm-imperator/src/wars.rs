@@ -0,0 +1,97 @@
+use crate::SaveFileImpl;
+use imperator_save::ImperatorDate;
+use pdxfilter::CountryFilterPayload;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImperatorWarSummary {
+    pub id: u32,
+    pub name: Option<String>,
+    pub start: Option<ImperatorDate>,
+    pub active: bool,
+
+    /// Tags of the attackers with the war leader first
+    pub attackers: Vec<String>,
+
+    /// Tags of the defenders with the war leader first
+    pub defenders: Vec<String>,
+}
+
+impl SaveFileImpl {
+    fn country_tags(&self, ids: &[u32]) -> Vec<String> {
+        ids.iter()
+            .filter_map(|x| self.gamestate.country(*x))
+            .map(|x| x.tag.clone())
+            .collect()
+    }
+
+    /// Wars involving a country matching the filter, with ongoing wars first
+    /// and then by most recent
+    pub fn get_wars(&self, payload: CountryFilterPayload) -> Vec<ImperatorWarSummary> {
+        let mut result: Vec<_> = self
+            .gamestate
+            .war
+            .iter()
+            .map(|(id, war)| ImperatorWarSummary {
+                id: *id,
+                name: war.war_name.as_ref().map(|x| x.name.clone()),
+                start: war.start_date,
+                active: !war.previous,
+                attackers: self.country_tags(&war.attacker),
+                defenders: self.country_tags(&war.defender),
+            })
+            .filter(|war| {
+                war.attackers
+                    .iter()
+                    .chain(war.defenders.iter())
+                    .any(|x| payload.matches(x))
+            })
+            .collect();
+
+        sort_wars(&mut result);
+        result
+    }
+}
+
+/// Orders ongoing wars first and then by most recent start
+fn sort_wars(wars: &mut [ImperatorWarSummary]) {
+    wars.sort_by(|a, b| {
+        b.active
+            .cmp(&a.active)
+            .then_with(|| b.start.cmp(&a.start))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn war(id: u32, start: Option<i16>, active: bool) -> ImperatorWarSummary {
+        ImperatorWarSummary {
+            id,
+            name: None,
+            start: start.map(|x| ImperatorDate::from_ymd(x, 1, 1)),
+            active,
+            attackers: Vec::new(),
+            defenders: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sort_wars() {
+        let mut wars = vec![
+            war(1, Some(450), false),
+            war(2, Some(460), true),
+            war(3, Some(470), false),
+            war(4, Some(455), true),
+            war(5, None, false),
+            war(6, Some(460), true),
+        ];
+
+        sort_wars(&mut wars);
+        let ids: Vec<_> = wars.iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![2, 6, 4, 3, 1, 5]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::SaveFileImpl;
+use pdxfilter::CountryFilterPayload;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use vic3save::{
+    models::{Vic3Country, Vic3TrackedValue},
+    Vic3Date,
+};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Vic3Metric {
+    Gdp,
+    Population,
+    StandardOfLiving,
+    Prestige,
+    Literacy,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopCountriesPayload {
+    pub metric: Vic3Metric,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3CountryOverview {
+    pub id: u32,
+    pub tag: String,
+    pub name: String,
+    pub gdp: Option<f64>,
+    pub population: f64,
+    pub standard_of_living: Option<f64>,
+    pub states: usize,
+    pub buildings: usize,
+
+    /// If the country leads its own market
+    pub market_leader: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3Sample {
+    pub date: Vic3Date,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3CountrySeries {
+    pub id: u32,
+    pub tag: String,
+    pub name: String,
+    pub data: Vec<Vic3Sample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3EconomySeries {
+    pub id: u32,
+    pub tag: String,
+    pub name: String,
+    pub gdp: Vec<Vic3Sample>,
+    pub prestige: Vec<Vic3Sample>,
+    pub literacy: Vec<Vic3Sample>,
+}
+
+impl Vic3Metric {
+    fn tracked(self, country: &Vic3Country) -> &Vic3TrackedValue {
+        match self {
+            Vic3Metric::Gdp => &country.gdp,
+            Vic3Metric::Population => &country.pop_statistics.trend_population,
+            Vic3Metric::StandardOfLiving => &country.avgsoltrend,
+            Vic3Metric::Prestige => &country.prestige,
+            Vic3Metric::Literacy => &country.literacy,
+        }
+    }
+
+    fn samples(self, country: &Vic3Country) -> Vec<Vic3Sample> {
+        self.tracked(country)
+            .iter()
+            .map(|(date, value)| Vic3Sample { date, value })
+            .collect()
+    }
+}
+
+impl SaveFileImpl {
+    /// Summary of every country in the save, ordered by GDP
+    pub fn get_countries(&self) -> Vec<Vic3CountryOverview> {
+        let state_owners: HashMap<u32, u32> = self
+            .gamestate
+            .states
+            .iter()
+            .map(|(id, state)| (id, state.country))
+            .collect();
+
+        let mut states: HashMap<u32, usize> = HashMap::new();
+        for owner in state_owners.values() {
+            *states.entry(*owner).or_default() += 1;
+        }
+
+        let mut buildings: HashMap<u32, usize> = HashMap::new();
+        for (_, building) in self.gamestate.building_manager.iter() {
+            if let Some(owner) = state_owners.get(&building.state) {
+                *buildings.entry(*owner).or_default() += 1;
+            }
+        }
+
+        // Only used when the country doesn't track its population
+        let mut pop_sizes: HashMap<u32, i64> = HashMap::new();
+        for (_, pop) in self.gamestate.pops.iter() {
+            if let Some(owner) = state_owners.get(&pop.location) {
+                *pop_sizes.entry(*owner).or_default() += pop.size();
+            }
+        }
+
+        let market_leaders: HashSet<u32> = self
+            .gamestate
+            .market_manager
+            .iter()
+            .filter_map(|(_, market)| market.owner)
+            .collect();
+
+        let mut result: Vec<_> = self
+            .gamestate
+            .country_manager
+            .iter()
+            .map(|(id, country)| {
+                let population = country
+                    .pop_statistics
+                    .trend_population
+                    .last()
+                    .map(|(_, x)| x)
+                    .unwrap_or_else(|| pop_sizes.get(&id).copied().unwrap_or(0) as f64);
+
+                Vic3CountryOverview {
+                    id,
+                    tag: country.definition.clone(),
+                    name: self.country_name(&country.definition),
+                    gdp: country.gdp.last().map(|(_, x)| x),
+                    population,
+                    standard_of_living: country.avgsoltrend.last().map(|(_, x)| x),
+                    states: states.get(&id).copied().unwrap_or(0),
+                    buildings: buildings.get(&id).copied().unwrap_or(0),
+                    market_leader: market_leaders.contains(&id),
+                }
+            })
+            .filter(|x| x.states > 0)
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.gdp
+                .unwrap_or(0.0)
+                .total_cmp(&a.gdp.unwrap_or(0.0))
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        result
+    }
+
+    /// History of the metric for the countries with the highest latest value
+    pub fn get_top_countries(&self, payload: TopCountriesPayload) -> Vec<Vic3CountrySeries> {
+        let mut result: Vec<_> = self
+            .gamestate
+            .country_manager
+            .iter()
+            .map(|(id, country)| Vic3CountrySeries {
+                id,
+                tag: country.definition.clone(),
+                name: self.country_name(&country.definition),
+                data: payload.metric.samples(country),
+            })
+            .filter(|x| !x.data.is_empty())
+            .collect();
+
+        let latest = |x: &Vic3CountrySeries| x.data.last().map(|x| x.value).unwrap_or(0.0);
+        result.sort_by(|a, b| {
+            latest(b)
+                .total_cmp(&latest(a))
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        result.truncate(payload.limit);
+        result
+    }
+
+    /// The GDP, prestige, and literacy history the game records for the
+    /// countries matching the filter, ordered by tag
+    pub fn get_economy_series(&self, payload: CountryFilterPayload) -> Vec<Vic3EconomySeries> {
+        let mut result: Vec<_> = self
+            .gamestate
+            .country_manager
+            .iter()
+            .filter(|(_, country)| payload.matches(&country.definition))
+            .map(|(id, country)| Vic3EconomySeries {
+                id,
+                tag: country.definition.clone(),
+                name: self.country_name(&country.definition),
+                gdp: Vic3Metric::Gdp.samples(country),
+                prestige: Vic3Metric::Prestige.samples(country),
+                literacy: Vic3Metric::Literacy.samples(country),
+            })
+            .collect();
+
+        result.sort_by(|a, b| a.tag.cmp(&b.tag).then_with(|| a.id.cmp(&b.id)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMESTATE: &[u8] = br#"
+        country_manager={
+            database={
+                1={
+                    definition=GBR
+                    gdp={ channels={ 0={ date=1836.1.1 index=0 values={ 10 20 } } } }
+                }
+                2={ definition=FRA }
+                3=none
+                4={
+                    definition=PRU
+                    literacy={ channels={ 0={ date=1836.1.1 index=0 values={ 0.4 } } } }
+                }
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_economy_series_filters_countries() {
+        let save = SaveFileImpl::from_gamestate(GAMESTATE);
+        let payload = CountryFilterPayload {
+            tags: vec![String::from("PRU"), String::from("GBR")],
+        };
+
+        let series = save.get_economy_series(payload);
+        let tags: Vec<_> = series.iter().map(|x| x.tag.as_str()).collect();
+        assert_eq!(tags, vec!["GBR", "PRU"]);
+        assert_eq!(series[0].gdp.len(), 2);
+        assert!(series[0].literacy.is_empty());
+        assert!(series[1].gdp.is_empty());
+        assert_eq!(series[1].literacy.len(), 1);
+
+        let all = save.get_economy_series(CountryFilterPayload { tags: Vec::new() });
+        let tags: Vec<_> = all.iter().map(|x| x.tag.as_str()).collect();
+        assert_eq!(tags, vec!["FRA", "GBR", "PRU"]);
+
+        let none = CountryFilterPayload {
+            tags: vec![String::from("USA")],
+        };
+        assert!(save.get_economy_series(none).is_empty());
+    }
+}
@@ -1,10 +1,17 @@
+use pdxgame::GameData;
 use serde::{Deserialize, Serialize};
+use vic3game::Game;
 use vic3save::{
-    FailedResolveStrategy, PdsDate, SaveHeader, SaveHeaderKind, Vic3Date, Vic3Error, Vic3File,
+    models::Vic3Gamestate, FailedResolveStrategy, PdsDate, SaveHeader, SaveHeaderKind, Vic3Date,
+    Vic3Error, Vic3File,
 };
 use wasm_bindgen::prelude::*;
 
+mod countries;
+mod map;
+mod pops;
 mod tokens;
+pub use pdxmap::map_fill_borders;
 pub use tokens::*;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,11 +25,22 @@ pub struct Vic3Save {
 pub struct Vic3Metadata {
     date: Vic3Date,
     is_meltable: bool,
+
+    /// Set when the gamestate could not be deserialized, in which case only
+    /// the header is available and the analytics are empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gamestate_error: Option<String>,
 }
 
 pub struct SaveFileImpl {
     save: Vic3Save,
+    gamestate: Vic3Gamestate,
+    gamestate_error: Option<String>,
     header: SaveHeader,
+
+    /// Localization from the game data, when the caller has supplied it.
+    /// Without it, names fall back to the keys found in the save.
+    game: Option<GameData<Game<'static>>>,
 }
 
 #[wasm_bindgen]
@@ -38,6 +56,48 @@ impl SaveFile {
     pub fn metadata(&self) -> JsValue {
         to_json_value(&self.0.metadata())
     }
+
+    pub fn set_game_data(&mut self, data: &[u8]) -> Result<(), JsValue> {
+        self.0.set_game_data(data).map_err(js_err)
+    }
+
+    pub fn get_countries(&self) -> JsValue {
+        to_json_value(&self.0.get_countries())
+    }
+
+    pub fn get_top_countries(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_top_countries(payload)))
+    }
+
+    pub fn get_economy_series(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_economy_series(payload)))
+    }
+
+    pub fn get_state_demographics(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_state_demographics(payload)))
+    }
+
+    pub fn get_country_demographics(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_country_demographics(payload)))
+    }
+
+    pub fn get_pop_ranking(&self, payload: JsValue) -> Result<JsValue, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(to_json_value(&self.0.get_pop_ranking(payload)))
+    }
+
+    pub fn map_colors(&self, payload: JsValue) -> Result<Vec<u8>, JsValue> {
+        let payload = serde_wasm_bindgen::from_value(payload).map_err(js_err)?;
+        Ok(self.0.map_colors(payload))
+    }
+}
+
+fn js_err(err: impl std::error::Error) -> JsValue {
+    JsValue::from(err.to_string())
 }
 
 impl SaveFileImpl {
@@ -50,6 +110,7 @@ impl SaveFileImpl {
                 0,
             ),
             is_meltable: self.is_meltable(),
+            gamestate_error: self.gamestate_error.clone(),
         }
     }
 
@@ -59,6 +120,35 @@ impl SaveFileImpl {
             SaveHeaderKind::Binary | SaveHeaderKind::SplitBinary | SaveHeaderKind::UnifiedBinary
         )
     }
+
+    /// Loads the zstd compressed game data written by compile_assets
+    pub fn set_game_data(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.game = Some(GameData::from_zstd(data, Game::from_flatbuffer)?);
+        Ok(())
+    }
+
+    /// The localized name of a country, falling back to its tag
+    pub(crate) fn country_name(&self, tag: &str) -> String {
+        let localized = self.game.as_ref().and_then(|x| x.localize_country(tag));
+        localized.map_or_else(|| String::from(tag), |x| String::from(x.name))
+    }
+}
+
+#[cfg(test)]
+impl SaveFileImpl {
+    /// A save of the plaintext gamestate without game data
+    pub(crate) fn from_gamestate(gamestate: &[u8]) -> Self {
+        let header = SaveHeader::from_slice(b"SAV01000000000000000000\n").unwrap();
+        SaveFileImpl {
+            save: Vic3Save {
+                date: Vic3Date::from_ymdh(1836, 1, 1, 0),
+            },
+            gamestate: jomini::text::de::from_utf8_slice(gamestate).unwrap(),
+            gamestate_error: None,
+            header,
+            game: None,
+        }
+    }
 }
 
 fn _parse_save(data: &[u8]) -> Result<SaveFile, Vic3Error> {
@@ -67,9 +157,17 @@ fn _parse_save(data: &[u8]) -> Result<SaveFile, Vic3Error> {
     let mut zip_sink = Vec::new();
     let parsed = file.parse(&mut zip_sink)?;
     let save = parsed.deserializer(tokens::get_tokens()).deserialize()?;
+    let gamestate = parsed.deserializer(tokens::get_tokens()).deserialize();
+    let (gamestate, gamestate_error) = match gamestate {
+        Ok(gamestate) => (gamestate, None),
+        Err(e) => (Vic3Gamestate::default(), Some(e.to_string())),
+    };
     Ok(SaveFile(SaveFileImpl {
         save,
+        gamestate,
+        gamestate_error,
         header: header.clone(),
+        game: None,
     }))
 }
 
@@ -0,0 +1,33 @@
+use crate::SaveFileImpl;
+use pdxmap::{tag_color, ProvinceColors, UNOWNED_COLOR};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapPayload {
+    /// The number of province colors to allocate, one more than the largest
+    /// province index in the raster
+    pub provinces: usize,
+}
+
+impl SaveFileImpl {
+    /// Political map colors where the provinces of each state are painted by
+    /// the state's owner. The raster's province indices are assumed to follow
+    /// the order of the state region files (see compile_assets), which has
+    /// not been verified against the game.
+    pub fn map_colors(&self, payload: MapPayload) -> Vec<u8> {
+        let mut colors = ProvinceColors::new(payload.provinces, UNOWNED_COLOR);
+        for (_, state) in self.gamestate.states.iter() {
+            let Some(country) = self.gamestate.country_manager.get(state.country) else {
+                continue;
+            };
+
+            let color = tag_color(&country.definition);
+            for province in state.provinces.iter() {
+                colors.set(province as usize, color);
+            }
+        }
+
+        colors.into_data()
+    }
+}
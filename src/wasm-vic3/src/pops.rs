@@ -0,0 +1,443 @@
+use crate::SaveFileImpl;
+use pdxfilter::CountryFilterPayload;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use vic3save::models::{Vic3Pop, Vic3Strata};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PopRankingKind {
+    Radical,
+    Loyal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopRankingPayload {
+    pub kind: PopRankingKind,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopShare<T> {
+    pub key: T,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3Demographics {
+    pub population: i64,
+    pub workforce: i64,
+
+    /// Share of the workforce that is literate
+    pub literacy: f64,
+
+    /// Average pop wealth weighted by pop size
+    pub wealth: f64,
+    pub cultures: Vec<PopShare<String>>,
+    pub religions: Vec<PopShare<String>>,
+    pub professions: Vec<PopShare<String>>,
+    pub strata: Vec<PopShare<Vic3Strata>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3StateDemographics {
+    pub id: u32,
+    pub tag: String,
+    pub country_name: String,
+    pub region: Option<String>,
+
+    /// The localized region name, falling back to the region key
+    pub name: Option<String>,
+    pub demographics: Vic3Demographics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vic3CountryDemographics {
+    pub id: u32,
+    pub tag: String,
+    pub name: String,
+    pub demographics: Vic3Demographics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedPop {
+    pub id: u32,
+    pub profession: String,
+    pub culture: String,
+    pub religion: Option<String>,
+    pub state: u32,
+    pub tag: String,
+    pub size: i64,
+    pub radicals: i64,
+    pub loyalists: i64,
+
+    /// Share of the pop that is radical or loyal, depending on the ranking
+    pub share: f64,
+}
+
+#[derive(Debug, Default)]
+struct DemographicsBuilder {
+    population: i64,
+    workforce: i64,
+    literate: i64,
+    wealth: f64,
+    cultures: HashMap<u32, i64>,
+    religions: HashMap<String, i64>,
+    professions: HashMap<String, i64>,
+    strata: HashMap<Vic3Strata, i64>,
+}
+
+impl DemographicsBuilder {
+    fn add(&mut self, pop: &Vic3Pop) {
+        let size = pop.size();
+        self.population += size;
+        self.workforce += pop.workforce;
+        self.literate += pop.num_literate;
+        self.wealth += pop.wealth * size as f64;
+        *self.cultures.entry(pop.culture).or_default() += size;
+        *self.professions.entry(pop.kind.clone()).or_default() += size;
+
+        if let Some(religion) = &pop.religion {
+            *self.religions.entry(religion.clone()).or_default() += size;
+        }
+
+        if let Some(strata) = pop.strata() {
+            *self.strata.entry(strata).or_default() += size;
+        }
+    }
+
+    /// Cultures are tallied by id and only resolved to their key here so
+    /// that aggregating pops doesn't allocate per pop
+    fn build(self, culture_key: impl Fn(u32) -> String) -> Vic3Demographics {
+        fn shares<T: Ord>(counts: impl IntoIterator<Item = (T, i64)>) -> Vec<PopShare<T>> {
+            let mut result: Vec<_> = counts
+                .into_iter()
+                .map(|(key, size)| PopShare { key, size })
+                .collect();
+            result.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.key.cmp(&b.key)));
+            result
+        }
+
+        Vic3Demographics {
+            population: self.population,
+            workforce: self.workforce,
+            literacy: ratio(self.literate as f64, self.workforce as f64),
+            wealth: ratio(self.wealth, self.population as f64),
+            cultures: shares(
+                self.cultures
+                    .into_iter()
+                    .map(|(id, size)| (culture_key(id), size)),
+            ),
+            religions: shares(self.religions),
+            professions: shares(self.professions),
+            strata: shares(self.strata),
+        }
+    }
+}
+
+fn ratio(num: f64, denom: f64) -> f64 {
+    if denom > 0.0 {
+        num / denom
+    } else {
+        0.0
+    }
+}
+
+impl SaveFileImpl {
+    fn culture_key(&self, id: u32) -> String {
+        self.gamestate
+            .cultures
+            .get(id)
+            .map(|x| x.kind.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn state_name(&self, region: &str) -> String {
+        let localized = self.game.as_ref().and_then(|x| x.localize_state(region));
+        String::from(localized.unwrap_or(region))
+    }
+
+    fn country_tag(&self, id: u32) -> Option<&str> {
+        self.gamestate
+            .country_manager
+            .get(id)
+            .map(|x| x.definition.as_str())
+    }
+
+    /// The owner of every state owned by a country matching the filter
+    fn matching_state_owners(&self, payload: &CountryFilterPayload) -> HashMap<u32, u32> {
+        self.gamestate
+            .states
+            .iter()
+            .filter(|(_, state)| {
+                self.country_tag(state.country)
+                    .is_some_and(|tag| payload.matches(tag))
+            })
+            .map(|(id, state)| (id, state.country))
+            .collect()
+    }
+
+    /// Pops aggregated by the state they live in for states owned by
+    /// countries matching the filter, ordered by population
+    pub fn get_state_demographics(
+        &self,
+        payload: CountryFilterPayload,
+    ) -> Vec<Vic3StateDemographics> {
+        let owners = self.matching_state_owners(&payload);
+        let mut states: HashMap<u32, DemographicsBuilder> = HashMap::new();
+        for (_, pop) in self.gamestate.pops.iter() {
+            if owners.contains_key(&pop.location) {
+                states.entry(pop.location).or_default().add(pop);
+            }
+        }
+
+        let mut result: Vec<_> = states
+            .into_iter()
+            .filter_map(|(id, builder)| {
+                let state = self.gamestate.states.get(id)?;
+                let tag = self.country_tag(state.country)?;
+                Some(Vic3StateDemographics {
+                    id,
+                    tag: String::from(tag),
+                    country_name: self.country_name(tag),
+                    region: state.region.clone(),
+                    name: state.region.as_deref().map(|x| self.state_name(x)),
+                    demographics: builder.build(|x| self.culture_key(x)),
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.demographics
+                .population
+                .cmp(&a.demographics.population)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        result
+    }
+
+    /// Pops aggregated by the country that owns the state they live in,
+    /// ordered by population
+    pub fn get_country_demographics(
+        &self,
+        payload: CountryFilterPayload,
+    ) -> Vec<Vic3CountryDemographics> {
+        let owners = self.matching_state_owners(&payload);
+        let mut countries: HashMap<u32, DemographicsBuilder> = HashMap::new();
+        for (_, pop) in self.gamestate.pops.iter() {
+            if let Some(country) = owners.get(&pop.location) {
+                countries.entry(*country).or_default().add(pop);
+            }
+        }
+
+        let mut result: Vec<_> = countries
+            .into_iter()
+            .filter_map(|(id, builder)| {
+                let tag = self.country_tag(id)?;
+                Some(Vic3CountryDemographics {
+                    id,
+                    tag: String::from(tag),
+                    name: self.country_name(tag),
+                    demographics: builder.build(|x| self.culture_key(x)),
+                })
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.demographics
+                .population
+                .cmp(&a.demographics.population)
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        result
+    }
+
+    /// The most radical or most loyal pops in the save, ranked by the number
+    /// of radicals or loyalists
+    pub fn get_pop_ranking(&self, payload: PopRankingPayload) -> Vec<RankedPop> {
+        let count = |x: &Vic3Pop| match payload.kind {
+            PopRankingKind::Radical => x.radicals,
+            PopRankingKind::Loyal => x.loyalists,
+        };
+
+        let mut pops: Vec<_> = self
+            .gamestate
+            .pops
+            .iter()
+            .filter(|(_, pop)| count(pop) > 0)
+            .filter_map(|(id, pop)| {
+                let state = self.gamestate.states.get(pop.location)?;
+                let tag = self.country_tag(state.country)?;
+                Some((id, pop, tag))
+            })
+            .collect();
+
+        pops.sort_by(|(a_id, a, _), (b_id, b, _)| {
+            count(b).cmp(&count(a)).then_with(|| a_id.cmp(b_id))
+        });
+        pops.truncate(payload.limit);
+
+        pops.into_iter()
+            .map(|(id, pop, tag)| {
+                let size = pop.size();
+                RankedPop {
+                    id,
+                    profession: pop.kind.clone(),
+                    culture: self.culture_key(pop.culture),
+                    religion: pop.religion.clone(),
+                    state: pop.location,
+                    tag: String::from(tag),
+                    size,
+                    radicals: pop.radicals,
+                    loyalists: pop.loyalists,
+                    share: ratio(count(pop) as f64, size as f64),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pop(kind: &str, culture: u32, workforce: i64, dependents: i64, wealth: f64) -> Vic3Pop {
+        Vic3Pop {
+            kind: String::from(kind),
+            location: 1,
+            culture,
+            religion: None,
+            workforce,
+            dependents,
+            wealth,
+            num_literate: 0,
+            radicals: 0,
+            loyalists: 0,
+        }
+    }
+
+    #[test]
+    fn test_demographics_without_workforce() {
+        let mut builder = DemographicsBuilder::default();
+        builder.add(&pop("aristocrats", 1, 0, 50, 20.0));
+        let demographics = builder.build(|x| x.to_string());
+        assert_eq!(demographics.population, 50);
+        assert_eq!(demographics.workforce, 0);
+        assert_eq!(demographics.literacy, 0.0);
+        assert_eq!(demographics.wealth, 20.0);
+
+        let empty = DemographicsBuilder::default().build(|x| x.to_string());
+        assert_eq!(empty.literacy, 0.0);
+        assert_eq!(empty.wealth, 0.0);
+        assert!(empty.cultures.is_empty());
+    }
+
+    #[test]
+    fn test_demographics_weighted() {
+        let mut builder = DemographicsBuilder::default();
+        let mut literate = pop("clerks", 1, 100, 100, 10.0);
+        literate.num_literate = 75;
+        builder.add(&literate);
+        builder.add(&pop("peasants", 2, 500, 300, 5.0));
+        builder.add(&pop("peasants", 1, 0, 0, 100.0));
+
+        let demographics = builder.build(|x| format!("culture_{x}"));
+        assert_eq!(demographics.population, 1000);
+        assert_eq!(demographics.workforce, 600);
+        assert_eq!(demographics.literacy, 0.125);
+        assert_eq!(demographics.wealth, (200.0 * 10.0 + 800.0 * 5.0) / 1000.0);
+
+        let cultures: Vec<_> = demographics
+            .cultures
+            .iter()
+            .map(|x| (x.key.as_str(), x.size))
+            .collect();
+        assert_eq!(cultures, vec![("culture_2", 800), ("culture_1", 200)]);
+
+        let strata: Vec<_> = demographics
+            .strata
+            .iter()
+            .map(|x| (x.key, x.size))
+            .collect();
+        assert_eq!(
+            strata,
+            vec![(Vic3Strata::Lower, 800), (Vic3Strata::Middle, 200)]
+        );
+    }
+
+    const GAMESTATE: &[u8] = br#"
+        country_manager={ database={ 1={ definition=GBR } 2={ definition=FRA } } }
+        states={ database={ 10={ country=1 } 20={ country=2 } 30={ country=3 } } }
+        cultures={ database={ 5={ type=british } } }
+        pops={
+            database={
+                100={ type=laborers location=10 culture=5 workforce=80 dependents=20 radicals=10 }
+                101={ type=clerks location=20 culture=6 workforce=40 radicals=30 loyalists=5 }
+                102={ type=farmers location=10 culture=5 workforce=50 radicals=10 }
+                103={ type=peasants location=30 culture=5 workforce=90 radicals=90 }
+                104={ type=peasants location=20 culture=6 workforce=60 loyalists=50 }
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_pop_ranking() {
+        let save = SaveFileImpl::from_gamestate(GAMESTATE);
+        let payload = PopRankingPayload {
+            kind: PopRankingKind::Radical,
+            limit: 10,
+        };
+
+        // pops in states without a known owner are skipped and ties are
+        // broken by pop id
+        let ranking = save.get_pop_ranking(payload);
+        let ids: Vec<_> = ranking.iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![101, 100, 102]);
+        assert_eq!(ranking[1].culture, "british");
+        assert_eq!(ranking[1].tag, "GBR");
+        assert_eq!(ranking[1].share, 0.1);
+        assert_eq!(ranking[0].culture, "6");
+
+        let payload = PopRankingPayload {
+            kind: PopRankingKind::Radical,
+            limit: 2,
+        };
+        let ids: Vec<_> = save.get_pop_ranking(payload).iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![101, 100]);
+
+        let payload = PopRankingPayload {
+            kind: PopRankingKind::Loyal,
+            limit: 10,
+        };
+        let ids: Vec<_> = save.get_pop_ranking(payload).iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![104, 101]);
+    }
+
+    #[test]
+    fn test_demographics_filter_countries() {
+        let save = SaveFileImpl::from_gamestate(GAMESTATE);
+        let payload = CountryFilterPayload {
+            tags: vec![String::from("GBR")],
+        };
+
+        let states = save.get_state_demographics(payload.clone());
+        let ids: Vec<_> = states.iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![10]);
+        assert_eq!(states[0].demographics.population, 150);
+
+        let countries = save.get_country_demographics(payload);
+        assert_eq!(countries.len(), 1);
+        assert_eq!(countries[0].tag, "GBR");
+        assert_eq!(countries[0].demographics.population, 150);
+
+        let all = save.get_country_demographics(CountryFilterPayload { tags: Vec::new() });
+        let tags: Vec<_> = all.iter().map(|x| x.tag.as_str()).collect();
+        assert_eq!(tags, vec!["GBR", "FRA"]);
+    }
+}